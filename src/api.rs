@@ -0,0 +1,342 @@
+use wasm_bindgen::prelude::*;
+
+use crate::app::App;
+use crate::cell::CellValue;
+use crate::cell_ref::{CellRef, CellRefError};
+use crate::format::NumberFormat;
+use crate::grid::{Grid, IterativeCalc};
+use crate::history::{Command, History};
+use crate::range::CellRange;
+use crate::style::CellStyle;
+
+// Handle exported to JavaScript. Cells are addressed with A1 strings and
+// values cross the boundary as plain JS numbers, strings or `null`.
+#[wasm_bindgen]
+pub struct Spreadsheet {
+    grid: Grid,
+    history: History,
+}
+
+#[wasm_bindgen]
+impl Spreadsheet {
+    #[wasm_bindgen(constructor)]
+    pub fn new(num_rows: u32, num_cols: u32) -> Spreadsheet {
+        Self {
+            grid: Grid::new(num_rows, num_cols),
+            history: History::new(),
+        }
+    }
+
+    #[wasm_bindgen(js_name = setCell)]
+    pub fn set_cell(&mut self, cell: &str, value: JsValue) -> Result<(), JsValue> {
+        let cell = parse_cell(cell)?;
+        let command = match value.as_string() {
+            Some(text) => Command::SetInput { cell, text },
+            None => Command::SetValue {
+                cell,
+                value: from_js(&value),
+            },
+        };
+        self.execute(command)?;
+        self.grid.recalculate();
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = getCell)]
+    pub fn get_cell(&self, cell: &str) -> Result<JsValue, JsValue> {
+        let cell = parse_cell(cell)?;
+        Ok(to_js(&self.grid.get_value(cell)))
+    }
+
+    // Changes a column's type (`"general"`, `"string"`, `"int"`, ...) and
+    // returns the cells that could not be converted.
+    #[wasm_bindgen(js_name = setColumnType)]
+    pub fn set_column_type(
+        &mut self,
+        column: u32,
+        column_type: &str,
+    ) -> Result<Vec<String>, JsValue> {
+        let column_type = column_type
+            .parse()
+            .map_err(|err: String| JsError::new(&err))?;
+        let failed = self.execute(Command::SetColumnType {
+            column,
+            column_type,
+        })?;
+        self.grid.recalculate();
+        Ok(failed.iter().map(|c| c.to_string()).collect())
+    }
+
+    // Each cycle as `A1 -> B1 -> A1`.
+    #[wasm_bindgen(js_name = circularReferences)]
+    pub fn circular_references(&self) -> Vec<String> {
+        self.grid
+            .circular_references()
+            .iter()
+            .map(|cycle| {
+                let mut names: Vec<String> = cycle.iter().map(|c| c.to_string()).collect();
+                names.push(names[0].clone());
+                names.join(" -> ")
+            })
+            .collect()
+    }
+
+    #[wasm_bindgen(js_name = setIterativeCalculation)]
+    pub fn set_iterative_calculation(
+        &mut self,
+        enabled: bool,
+        max_iterations: u32,
+        max_change: f64,
+    ) {
+        let settings = enabled.then_some(IterativeCalc {
+            max_iterations,
+            max_change,
+        });
+        self.grid.set_iterative_calculation(settings);
+        self.grid.recalculate();
+    }
+
+    // Shows `cell` using an Excel-style format code; `null` clears it.
+    #[wasm_bindgen(js_name = setCellFormat)]
+    pub fn set_cell_format(&mut self, cell: &str, code: Option<String>) -> Result<(), JsValue> {
+        let cell = parse_cell(cell)?;
+        let format = parse_format(code)?;
+        self.execute(Command::SetCellFormat { cell, format })?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = setColumnFormat)]
+    pub fn set_column_format(&mut self, column: u32, code: Option<String>) -> Result<(), JsValue> {
+        let format = parse_format(code)?;
+        self.execute(Command::SetColumnFormat { column, format })?;
+        Ok(())
+    }
+
+    // Alignment (`"general"`, `"left"`, `"center"`, `"right"` and `"top"`,
+    // `"middle"`, `"bottom"`) and overflow (`"overflow"`, `"clip"`,
+    // `"ellipsis"`) of `cell`.
+    #[wasm_bindgen(js_name = setCellStyle)]
+    pub fn set_cell_style(
+        &mut self,
+        cell: &str,
+        h_align: &str,
+        v_align: &str,
+        overflow: &str,
+    ) -> Result<(), JsValue> {
+        let cell = parse_cell(cell)?;
+        let style = CellStyle {
+            h_align: h_align.parse().map_err(|err: String| JsError::new(&err))?,
+            v_align: v_align.parse().map_err(|err: String| JsError::new(&err))?,
+            overflow: overflow.parse().map_err(|err: String| JsError::new(&err))?,
+        };
+        self.execute(Command::SetCellStyle { cell, style })?;
+        Ok(())
+    }
+
+    // A width of zero hides the column.
+    #[wasm_bindgen(js_name = setColumnWidth)]
+    pub fn set_column_width(&mut self, column: u32, width: f64) -> Result<(), JsValue> {
+        self.execute(Command::SetColumnWidth { column, width })?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = setRowHeight)]
+    pub fn set_row_height(&mut self, row: u32, height: f64) -> Result<(), JsValue> {
+        self.execute(Command::SetRowHeight { row, height })?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = setColumnHidden)]
+    pub fn set_column_hidden(&mut self, column: u32, hidden: bool) -> Result<(), JsValue> {
+        self.execute(Command::SetColumnHidden { column, hidden })?;
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = setRowHidden)]
+    pub fn set_row_hidden(&mut self, row: u32, hidden: bool) -> Result<(), JsValue> {
+        self.execute(Command::SetRowHidden { row, hidden })?;
+        Ok(())
+    }
+
+    // The formatted text of `cell`, as it is drawn.
+    #[wasm_bindgen(js_name = getDisplay)]
+    pub fn get_display(&self, cell: &str) -> Result<String, JsValue> {
+        let cell = parse_cell(cell)?;
+        Ok(self.grid.display_value(cell).text)
+    }
+
+    // Inserts `count` blank rows before row `at` (zero-based).
+    #[wasm_bindgen(js_name = insertRows)]
+    pub fn insert_rows(&mut self, at: u32, count: u32) -> Result<(), JsValue> {
+        self.execute(Command::InsertRows { at, count })?;
+        self.grid.recalculate();
+        Ok(())
+    }
+
+    // References to deleted cells become `#REF!`.
+    #[wasm_bindgen(js_name = deleteRows)]
+    pub fn delete_rows(&mut self, at: u32, count: u32) -> Result<(), JsValue> {
+        self.execute(Command::DeleteRows { at, count })?;
+        self.grid.recalculate();
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = insertColumns)]
+    pub fn insert_columns(&mut self, at: u32, count: u32) -> Result<(), JsValue> {
+        self.execute(Command::InsertColumns { at, count })?;
+        self.grid.recalculate();
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = deleteColumns)]
+    pub fn delete_columns(&mut self, at: u32, count: u32) -> Result<(), JsValue> {
+        self.execute(Command::DeleteColumns { at, count })?;
+        self.grid.recalculate();
+        Ok(())
+    }
+
+    // Undoes the last change. Returns false if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let done = self.history.undo(&mut self.grid);
+        self.grid.recalculate();
+        done
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let done = self.history.redo(&mut self.grid);
+        self.grid.recalculate();
+        done
+    }
+
+    #[wasm_bindgen(js_name = canUndo)]
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    #[wasm_bindgen(js_name = canRedo)]
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    // Caps how far the sheet can grow, by inserting rows and columns or
+    // when it is scrolled.
+    #[wasm_bindgen(js_name = setSizeLimit)]
+    pub fn set_size_limit(&mut self, rows: u32, columns: u32) -> Result<(), JsValue> {
+        self.grid
+            .set_size_limit(rows, columns)
+            .map_err(|err| JsError::new(&err.to_string()).into())
+    }
+
+    // The area holding every value, as `A1:C10`, or `undefined` when the
+    // sheet is blank.
+    #[wasm_bindgen(js_name = usedRange)]
+    pub fn used_range(&self) -> Option<String> {
+        self.grid.used_range().map(|area| area.to_string())
+    }
+
+    #[wasm_bindgen(js_name = getFormula)]
+    pub fn get_formula(&self, cell: &str) -> Result<Option<String>, JsValue> {
+        let cell = parse_cell(cell)?;
+        Ok(self.grid.get_formula(cell).map(|f| f.to_string()))
+    }
+}
+
+impl Spreadsheet {
+    // Every change goes through here so that it can be undone.
+    fn execute(&mut self, command: Command) -> Result<Vec<CellRef>, JsValue> {
+        self.history
+            .execute(&mut self.grid, command)
+            .map_err(|err| JsError::new(&err.to_string()).into())
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+}
+
+// The selection of the sheet mounted by `start()`, as `A1:B3,D4`.
+#[wasm_bindgen(js_name = getSelection)]
+pub fn get_selection() -> Option<String> {
+    let app = App::mounted()?;
+    let range = app.borrow().selection().to_range();
+    Some(range.to_string())
+}
+
+#[wasm_bindgen(js_name = getActiveCell)]
+pub fn get_active_cell() -> Option<String> {
+    let app = App::mounted()?;
+    let active = app.borrow().selection().active();
+    Some(active.to_string())
+}
+
+#[wasm_bindgen(js_name = setSelection)]
+pub fn set_selection(range: &str) -> Result<(), JsValue> {
+    let range: CellRange = range
+        .parse()
+        .map_err(|err: CellRefError| JsError::new(&err.to_string()))?;
+    if let Some(app) = App::mounted() {
+        app.borrow_mut().selection_mut().select_range(&range);
+        App::request_redraw(&app);
+    }
+    Ok(())
+}
+
+// Undoes the last change to the sheet mounted by `start()`.
+#[wasm_bindgen]
+pub fn undo() -> bool {
+    App::mounted().is_some_and(|app| {
+        let done = app.borrow_mut().undo();
+        App::request_redraw(&app);
+        done
+    })
+}
+
+#[wasm_bindgen]
+pub fn redo() -> bool {
+    App::mounted().is_some_and(|app| {
+        let done = app.borrow_mut().redo();
+        App::request_redraw(&app);
+        done
+    })
+}
+
+#[wasm_bindgen(js_name = canUndo)]
+pub fn can_undo() -> bool {
+    App::mounted().is_some_and(|app| app.borrow().can_undo())
+}
+
+#[wasm_bindgen(js_name = canRedo)]
+pub fn can_redo() -> bool {
+    App::mounted().is_some_and(|app| app.borrow().can_redo())
+}
+
+fn parse_cell(cell: &str) -> Result<CellRef, JsValue> {
+    cell.parse::<CellRef>()
+        .map_err(|err| JsError::new(&err.to_string()).into())
+}
+
+fn parse_format(code: Option<String>) -> Result<Option<NumberFormat>, JsValue> {
+    code.map(|code| code.parse::<NumberFormat>())
+        .transpose()
+        .map_err(|err| JsError::new(&err.to_string()).into())
+}
+
+fn from_js(value: &JsValue) -> CellValue {
+    if let Some(n) = value.as_f64() {
+        CellValue::from_number(n)
+    } else if let Some(b) = value.as_bool() {
+        CellValue::Bool(b)
+    } else {
+        CellValue::Empty
+    }
+}
+
+fn to_js(value: &CellValue) -> JsValue {
+    match value {
+        CellValue::Empty => JsValue::NULL,
+        CellValue::Bool(b) => JsValue::from_bool(*b),
+        CellValue::Int(n) => JsValue::from_f64(*n as f64),
+        CellValue::Float(n) => JsValue::from_f64(*n),
+        value => JsValue::from_str(&value.to_string()),
+    }
+}
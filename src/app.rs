@@ -0,0 +1,953 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    CanvasRenderingContext2d, ClipboardEvent, Document, Event, EventTarget, HtmlCanvasElement,
+    HtmlElement, HtmlInputElement, KeyboardEvent, MouseEvent,
+};
+
+use crate::cell::CellValue;
+use crate::cell_ref::CellRef;
+use crate::clipboard::{self, copy_extent};
+use crate::console_log as log;
+use crate::editor::Editor;
+use crate::fill::{fill_down_extent, fill_target};
+use crate::grid::{Grid, GridError, DEFAULT_ROW_HEIGHT};
+use crate::history::{Command, History};
+use crate::layout::fit_column_width;
+use crate::navigate::{navigate, Direction, Motion};
+use crate::range::{Area, CellRange};
+use crate::render::Renderer;
+use crate::selection::Selection;
+use crate::viewport::{ResizeHandle, Viewport};
+
+// How far the grid reaches past the active cell and past the last row and
+// column on screen. It grows by at least this much, up to its size limit,
+// whenever scrolling or navigation gets closer to its end.
+const GROW_ROWS: u32 = 100;
+const GROW_COLUMNS: u32 = 26;
+
+// Id of the datalist the input suggests enum options from.
+const OPTIONS_ID: &str = "spreadsheet-options";
+
+// A header border being dragged: where the drag started and the size of
+// the column or row at that point.
+#[derive(Clone, Copy, Debug)]
+struct Resize {
+    handle: ResizeHandle,
+    start: f64,
+    size: f64,
+}
+
+// A fill handle being dragged: the selection it started from and the area
+// the drag covers so far.
+#[derive(Clone, Copy, Debug)]
+struct Fill {
+    source: Area,
+    target: Area,
+}
+
+// The last area copied or cut from this sheet, and the text that went on
+// the clipboard for it. A paste of that same text copies or moves the cells
+// themselves, formulas and formatting included, rather than their text.
+#[derive(Clone, Debug)]
+struct Clip {
+    area: Area,
+    text: String,
+    cut: bool,
+}
+
+// The mounted spreadsheet: the model, the part of it on screen and the DOM
+// elements it is drawn into. Event handlers share it through
+// `Rc<RefCell<App>>`.
+//
+// The canvas only ever covers the window. A transparent scroller laid over
+// it holds a spacer as large as the grid, so the browser supplies native
+// scrollbars, wheel and touch scrolling, and the canvas is redrawn from the
+// scroll position.
+pub struct App {
+    grid: Grid,
+    history: History,
+    viewport: Viewport,
+    selection: Selection,
+    context: CanvasRenderingContext2d,
+    canvas: HtmlCanvasElement,
+    scroller: HtmlElement,
+    spacer: HtmlElement,
+    input: HtmlInputElement,
+    // The options offered under the input while editing an enum column.
+    options: HtmlElement,
+    editor: Option<Editor>,
+    resizing: Option<Resize>,
+    filling: Option<Fill>,
+    // Whether a press in the body or headers is being dragged out.
+    selecting: bool,
+    // Where a drag that inserts a reference into a formula started.
+    pointing: Option<CellRef>,
+    clip: Option<Clip>,
+    frame_requested: bool,
+}
+
+thread_local! {
+    // The app mounted by `start()`, for the JS-facing functions.
+    static MOUNTED: RefCell<Option<Rc<RefCell<App>>>> = const { RefCell::new(None) };
+}
+
+impl App {
+    pub fn mount(
+        document: &Document,
+        parent: &HtmlElement,
+        grid: Grid,
+    ) -> Result<Rc<RefCell<App>>, JsValue> {
+        let host = create::<HtmlElement>(document, "div")?;
+        host.style()
+            .set_css_text("position: relative; width: 100vw; height: 100vh; overflow: hidden;");
+        parent.append_child(&host)?;
+
+        let canvas = create::<HtmlCanvasElement>(document, "canvas")?;
+        canvas
+            .style()
+            .set_css_text("position: absolute; top: 0; left: 0;");
+        host.append_child(&canvas)?;
+
+        let scroller = create::<HtmlElement>(document, "div")?;
+        scroller
+            .style()
+            .set_css_text("position: absolute; inset: 0; overflow: auto;");
+        host.append_child(&scroller)?;
+
+        let spacer = create::<HtmlElement>(document, "div")?;
+        scroller.append_child(&spacer)?;
+
+        let input = create::<HtmlInputElement>(document, "input")?;
+        input.style().set_css_text(
+            "position: absolute; display: none; box-sizing: border-box; margin: 0; \
+             padding: 0 4px; border: 2px solid #1a73e8; outline: none; \
+             font: 14px sans-serif;",
+        );
+        host.append_child(&input)?;
+
+        let options = create::<HtmlElement>(document, "datalist")?;
+        options.set_id(OPTIONS_ID);
+        host.append_child(&options)?;
+
+        let context = canvas
+            .get_context("2d")?
+            .unwrap()
+            .dyn_into::<CanvasRenderingContext2d>()?;
+
+        let app = Rc::new(RefCell::new(App {
+            grid,
+            history: History::new(),
+            viewport: Viewport::with_headers(0.0, 0.0),
+            selection: Selection::default(),
+            context,
+            canvas,
+            scroller: scroller.clone(),
+            spacer,
+            input: input.clone(),
+            options,
+            editor: None,
+            resizing: None,
+            filling: None,
+            selecting: false,
+            pointing: None,
+            clip: None,
+            frame_requested: false,
+        }));
+        app.borrow_mut().resize()?;
+        let active = app.borrow().selection.active();
+        app.borrow_mut().grow_past(active);
+
+        let window: EventTarget = web_sys::window().expect("no global `window` exists").into();
+        listen(&app, &scroller, "scroll", App::on_scroll)?;
+        listen(&app, &window, "resize", |app, _: &Event| {
+            app.resize().is_ok()
+        })?;
+        listen(&app, &scroller, "mousedown", App::on_mouse_down)?;
+        listen(&app, &scroller, "mousemove", App::on_mouse_move)?;
+        listen(&app, &window, "mouseup", App::on_mouse_up)?;
+        listen(&app, &scroller, "dblclick", App::on_double_click)?;
+        listen(&app, &window, "keydown", App::on_key_down)?;
+        listen(&app, &input, "input", App::on_input)?;
+        listen(&app, &window, "copy", |app, event: &ClipboardEvent| {
+            app.on_copy(event, false)
+        })?;
+        listen(&app, &window, "cut", |app, event: &ClipboardEvent| {
+            app.on_copy(event, true)
+        })?;
+        listen(&app, &window, "paste", App::on_paste)?;
+
+        app.borrow().draw();
+        MOUNTED.with(|mounted| *mounted.borrow_mut() = Some(app.clone()));
+        Ok(app)
+    }
+
+    pub fn mounted() -> Option<Rc<RefCell<App>>> {
+        MOUNTED.with(|mounted| mounted.borrow().clone())
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    pub fn viewport(&self) -> &Viewport {
+        &self.viewport
+    }
+
+    pub fn selection(&self) -> &Selection {
+        &self.selection
+    }
+
+    pub fn selection_mut(&mut self) -> &mut Selection {
+        &mut self.selection
+    }
+
+    // Position of `event` relative to the canvas's top-left corner.
+    pub fn canvas_point(&self, event: &MouseEvent) -> (f64, f64) {
+        let rect = self.canvas.get_bounding_client_rect();
+        (
+            event.client_x() as f64 - rect.left(),
+            event.client_y() as f64 - rect.top(),
+        )
+    }
+
+    // Schedules a single redraw for the next animation frame, however many
+    // times it is called before then.
+    pub fn request_redraw(app: &Rc<RefCell<App>>) {
+        if std::mem::replace(&mut app.borrow_mut().frame_requested, true) {
+            return;
+        }
+        let callback = {
+            let app = app.clone();
+            Closure::once_into_js(move || {
+                let mut this = app.borrow_mut();
+                this.frame_requested = false;
+                this.draw();
+            })
+        };
+        web_sys::window()
+            .expect("no global `window` exists")
+            .request_animation_frame(callback.unchecked_ref())
+            .expect("requestAnimationFrame failed");
+    }
+
+    pub fn draw(&self) {
+        let fill = self.filling.map(|fill| fill.target);
+        Renderer::new(&self.context).draw(
+            &self.grid,
+            &self.viewport,
+            &self.selection,
+            fill.as_ref(),
+        );
+    }
+
+    fn on_scroll(&mut self, _: &Event) -> bool {
+        let (left, top) = (
+            self.scroller.scroll_left() as f64,
+            self.scroller.scroll_top() as f64,
+        );
+        let moved = self.viewport.scroll_to_native(&self.grid, left, top);
+        self.grow_past(self.selection.active());
+        self.place_input();
+        moved
+    }
+
+    // Grabbing a header border starts a resize. Anything else selects:
+    // shift extends the current area, ctrl/cmd adds a new one, and the
+    // headers pick whole columns or rows.
+    fn on_mouse_down(&mut self, event: &MouseEvent) -> bool {
+        let (x, y) = self.canvas_point(event);
+        if let Some(handle) = self.viewport.resize_handle_at(&self.grid, x, y) {
+            let (start, size) = match handle {
+                ResizeHandle::Column(column) => (x, self.grid.column_width(column)),
+                ResizeHandle::Row(row) => (y, self.grid.row_height(row)),
+            };
+            // The whole drag is undone at once.
+            self.history.begin_group();
+            self.resizing = Some(Resize {
+                handle,
+                start,
+                size,
+            });
+            event.prevent_default();
+            return false;
+        }
+        if let Some(source) = self.fill_handle_at(x, y) {
+            self.filling = Some(Fill {
+                source,
+                target: source,
+            });
+            event.prevent_default();
+            return false;
+        }
+
+        if let Some(editor) = &mut self.editor {
+            // Clicking a cell while a formula expects an operand inserts a
+            // reference to it; anywhere else the edit is committed first.
+            let caret = self.input.selection_start().ok().flatten().unwrap_or(0) as usize;
+            if let Some(cell) = self.viewport.cell_at(&self.grid, x, y) {
+                if editor.can_point(caret) {
+                    let caret = editor.point_at(caret, Area::cell(cell)) as u32;
+                    self.input.set_value(editor.text());
+                    let _ = self.input.set_selection_range(caret, caret);
+                    self.pointing = Some(cell);
+                    event.prevent_default();
+                    return true;
+                }
+            }
+            if !self.commit_edit() {
+                event.prevent_default();
+                return false;
+            }
+        }
+
+        let extend = event.shift_key();
+        let add = event.ctrl_key() || event.meta_key();
+        let column = self.viewport.column_header_at(&self.grid, x);
+        let row = self.viewport.row_header_at(&self.grid, y);
+        let over_column_header = y < self.viewport.header_height();
+        let over_row_header = x < self.viewport.header_width();
+        let selection = &mut self.selection;
+        match (column, row) {
+            _ if over_column_header && over_row_header => {
+                let all = Area::from_bounds(
+                    0,
+                    0,
+                    self.grid.num_rows().saturating_sub(1),
+                    self.grid.num_cols().saturating_sub(1),
+                );
+                selection.select_range(&all.into());
+            }
+            (Some(column), _) if over_column_header => {
+                if extend {
+                    selection.extend_to(CellRef::new(0, column));
+                } else {
+                    selection.select_columns(column, column, add);
+                }
+            }
+            (_, Some(row)) if over_row_header => {
+                if extend {
+                    selection.extend_to(CellRef::new(row, 0));
+                } else {
+                    selection.select_rows(row, row, add);
+                }
+            }
+            (Some(column), Some(row)) => {
+                let cell = CellRef::new(row, column);
+                if extend {
+                    selection.extend_to(cell);
+                } else if add {
+                    selection.add_cell(cell);
+                } else {
+                    selection.select_cell(cell);
+                }
+            }
+            _ => return false,
+        }
+        self.selecting = true;
+        event.prevent_default();
+        true
+    }
+
+    fn on_mouse_move(&mut self, event: &MouseEvent) -> bool {
+        let (x, y) = self.canvas_point(event);
+        if let Some(resize) = self.resizing {
+            // Dragging a border past the start of its column hides the column.
+            let _ = match resize.handle {
+                ResizeHandle::Column(column) => {
+                    let width = (resize.size + x - resize.start).max(0.0);
+                    self.execute(Command::SetColumnWidth { column, width })
+                }
+                ResizeHandle::Row(row) => {
+                    let height = (resize.size + y - resize.start).max(0.0);
+                    self.execute(Command::SetRowHeight { row, height })
+                }
+            };
+            return self.resize().is_ok();
+        }
+        if let Some(fill) = &mut self.filling {
+            let Some(cell) = self.viewport.cell_at(&self.grid, x, y) else {
+                return false;
+            };
+            let target = fill_target(&fill.source, cell);
+            let changed = target != fill.target;
+            fill.target = target;
+            return changed;
+        }
+        if let (Some(anchor), Some(editor)) = (self.pointing, &mut self.editor) {
+            let Some(cell) = self.viewport.cell_at(&self.grid, x, y) else {
+                return false;
+            };
+            let caret = self.input.selection_start().ok().flatten().unwrap_or(0) as usize;
+            let caret = editor.point_at(caret, Area::new(anchor, cell)) as u32;
+            self.input.set_value(editor.text());
+            let _ = self.input.set_selection_range(caret, caret);
+            return false;
+        }
+        if self.selecting {
+            // Over the headers only the other coordinate matters.
+            let column = self.viewport.column_header_at(&self.grid, x);
+            let row = self.viewport.row_header_at(&self.grid, y);
+            let anchor = self.selection.anchor();
+            let target = match (column, row) {
+                (Some(column), Some(row)) => CellRef::new(row, column),
+                (Some(column), None) => CellRef::new(anchor.row, column),
+                (None, Some(row)) => CellRef::new(row, anchor.column),
+                (None, None) => return false,
+            };
+            self.selection.extend_to(target);
+            return true;
+        }
+        let cursor = match self.viewport.resize_handle_at(&self.grid, x, y) {
+            Some(ResizeHandle::Column(_)) => "col-resize",
+            Some(ResizeHandle::Row(_)) => "row-resize",
+            None if self.fill_handle_at(x, y).is_some() => "crosshair",
+            None => "",
+        };
+        let _ = self.scroller.style().set_property("cursor", cursor);
+        false
+    }
+
+    fn on_mouse_up(&mut self, _: &MouseEvent) -> bool {
+        if self.resizing.take().is_some() {
+            self.history.end_group();
+        }
+        self.selecting = false;
+        self.pointing = None;
+        match self.filling.take() {
+            Some(fill) => {
+                if fill.target != fill.source {
+                    self.fill(fill.source, fill.target);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Double-clicking a column border fits the column to its content; a row
+    // border restores the default height. Double-clicking a cell edits it,
+    // and double-clicking the fill handle fills down as far as the data
+    // beside the selection goes.
+    fn on_double_click(&mut self, event: &MouseEvent) -> bool {
+        let (x, y) = self.canvas_point(event);
+        if let Some(source) = self.fill_handle_at(x, y) {
+            let Some(last) = fill_down_extent(&self.grid, &source) else {
+                return false;
+            };
+            let dest = Area::from_bounds(source.top(), source.left(), last, source.right());
+            self.fill(source, dest);
+            return true;
+        }
+        if let Some(cell) = self.viewport.cell_at(&self.grid, x, y) {
+            if self.viewport.resize_handle_at(&self.grid, x, y).is_none() {
+                self.open_editor(cell, None);
+                return true;
+            }
+        }
+        let _ = match self.viewport.resize_handle_at(&self.grid, x, y) {
+            Some(ResizeHandle::Column(column)) => {
+                let width = fit_column_width(&self.grid, column, &Renderer::new(&self.context));
+                self.execute(Command::SetColumnWidth { column, width })
+            }
+            Some(ResizeHandle::Row(row)) => self.execute(Command::SetRowHeight {
+                row,
+                height: DEFAULT_ROW_HEIGHT,
+            }),
+            None => return false,
+        };
+        self.resize().is_ok()
+    }
+
+    // Arrows, Tab, Enter, Home/End and PageUp/PageDown move the active cell;
+    // with shift, arrows and paging extend the selection instead and Tab
+    // and Enter go backwards. F2 or typing a character starts editing,
+    // Space ticks or unticks a checkbox, Ctrl+Z and Ctrl+Y undo and redo,
+    // and with whole rows or columns selected Ctrl+Shift+= and Ctrl+-
+    // insert and delete them.
+    fn on_key_down(&mut self, event: &KeyboardEvent) -> bool {
+        let ctrl = event.ctrl_key() || event.meta_key();
+        let shift = event.shift_key();
+        let key = event.key();
+
+        if self.editor.is_some() {
+            // While editing, the input gets every key except the ones that
+            // end the edit.
+            match key.as_str() {
+                "Escape" => {
+                    self.cancel_edit();
+                    return true;
+                }
+                "Enter" | "Tab" => {
+                    if !self.commit_edit() {
+                        event.prevent_default();
+                        return false;
+                    }
+                }
+                _ => return false,
+            }
+        } else if ctrl && matches!(key.as_str(), "z" | "Z" | "y" | "Y") {
+            // Ctrl+Z undoes; Ctrl+Y or Ctrl+Shift+Z redoes.
+            event.prevent_default();
+            return if key.eq_ignore_ascii_case("y") || shift {
+                self.redo()
+            } else {
+                self.undo()
+            };
+        } else if ctrl && (shift && matches!(key.as_str(), "+" | "=") || !shift && key == "-") {
+            // Only with whole rows or columns selected; otherwise the
+            // browser keeps these keys for zooming.
+            let area = self.selection.current_area();
+            if area.is_whole_columns() || area.is_whole_rows() {
+                event.prevent_default();
+                return self.edit_structure(key == "-");
+            }
+            return false;
+        } else if key == "F2" {
+            event.prevent_default();
+            self.open_editor(self.selection.active(), None);
+            return true;
+        } else if key == " " && self.grid.checkbox(self.selection.active()).is_some() {
+            event.prevent_default();
+            return self.toggle(self.selection.active());
+        } else if key.chars().count() == 1 && !ctrl && !event.alt_key() {
+            // Typing replaces the content of the active cell.
+            event.prevent_default();
+            self.open_editor(self.selection.active(), Some(&key));
+            return true;
+        }
+
+        let page = |count: usize| (count as u32).saturating_sub(1).max(1);
+        let rows = page(self.viewport.visible_rows(&self.grid).len());
+        let columns = page(self.viewport.visible_columns(&self.grid).len());
+        let arrow = |direction| {
+            if ctrl {
+                Motion::Jump(direction)
+            } else {
+                Motion::Step(direction)
+            }
+        };
+
+        let motion = match key.as_str() {
+            "ArrowUp" => arrow(Direction::Up),
+            "ArrowDown" => arrow(Direction::Down),
+            "ArrowLeft" => arrow(Direction::Left),
+            "ArrowRight" => arrow(Direction::Right),
+            "PageUp" if event.alt_key() => Motion::Page(Direction::Left, columns),
+            "PageDown" if event.alt_key() => Motion::Page(Direction::Right, columns),
+            "PageUp" => Motion::Page(Direction::Up, rows),
+            "PageDown" => Motion::Page(Direction::Down, rows),
+            "Home" if ctrl => Motion::SheetStart,
+            "End" if ctrl => Motion::SheetEnd,
+            "Home" => Motion::RowStart,
+            "End" => Motion::RowEnd,
+            "Tab" | "Enter" => {
+                let direction = match (key.as_str(), shift) {
+                    ("Tab", false) => Direction::Right,
+                    ("Tab", true) => Direction::Left,
+                    (_, false) => Direction::Down,
+                    (_, true) => Direction::Up,
+                };
+                event.prevent_default();
+                self.grow_past(self.selection.active());
+                if !self.selection.advance(direction) {
+                    let to = navigate(&self.grid, self.selection.active(), Motion::Step(direction));
+                    self.selection.select_cell(to);
+                }
+                let active = self.selection.active();
+                self.scroll_into_view(active);
+                return true;
+            }
+            _ => return false,
+        };
+        event.prevent_default();
+
+        let from = if shift {
+            self.selection.focus()
+        } else {
+            self.selection.active()
+        };
+        self.grow_past(from);
+        let to = navigate(&self.grid, from, motion);
+        self.selection.move_to(to, shift);
+        self.scroll_into_view(to);
+        true
+    }
+
+    fn on_input(&mut self, _: &Event) -> bool {
+        if let Some(editor) = &mut self.editor {
+            editor.set_text(&self.input.value());
+            let _ = self.input.style().remove_property("border-color");
+            self.input.set_title("");
+        }
+        false
+    }
+
+    // Puts the current area on the clipboard. The cells of a cut stay where
+    // they are until it is pasted. While editing, the input handles the
+    // clipboard itself.
+    fn on_copy(&mut self, event: &ClipboardEvent, cut: bool) -> bool {
+        if self.editor.is_some() {
+            return false;
+        }
+        let Some(data) = event.clipboard_data() else {
+            return false;
+        };
+        let Some(area) = copy_extent(&self.grid, &self.selection.current_area()) else {
+            return false;
+        };
+        let copied = clipboard::copy(&self.grid, &area);
+        if data.set_data("text/plain", &copied.text).is_err()
+            || data.set_data("text/html", &copied.html).is_err()
+        {
+            return false;
+        }
+        event.prevent_default();
+        self.clip = Some(Clip {
+            area,
+            text: copied.text,
+            cut,
+        });
+        false
+    }
+
+    // Pastes at the active cell. Cells copied from this sheet come across
+    // whole; anything else is entered as text, preferring an HTML table
+    // over tab-separated text.
+    fn on_paste(&mut self, event: &ClipboardEvent) -> bool {
+        if self.editor.is_some() {
+            return false;
+        }
+        let Some(data) = event.clipboard_data() else {
+            return false;
+        };
+        event.prevent_default();
+        let at = self.selection.active();
+        let text = data.get_data("text/plain").unwrap_or_default();
+        self.history.begin_group();
+        let pasted = match self.clip.take() {
+            // A cut can only be pasted once; a copy stays on the clipboard.
+            Some(clip) if clip.text == text => {
+                let area = clip.area;
+                if clip.cut {
+                    let _ = self.execute(Command::MoveArea {
+                        source: area,
+                        dest: at,
+                    });
+                } else {
+                    let _ = self.execute(Command::CopyArea {
+                        source: area,
+                        dest: at,
+                    });
+                    self.clip = Some(clip);
+                }
+                Area::from_bounds(
+                    at.row,
+                    at.column,
+                    at.row.saturating_add(area.num_rows() - 1),
+                    at.column.saturating_add(area.num_cols() - 1),
+                )
+            }
+            clip => {
+                self.clip = clip;
+                let rows = data
+                    .get_data("text/html")
+                    .ok()
+                    .and_then(|html| clipboard::parse_html(&html))
+                    .filter(|rows| !rows.is_empty())
+                    .unwrap_or_else(|| clipboard::parse_tsv(&text));
+                let failed = clipboard::paste_commands(&self.grid, at, &rows)
+                    .into_iter()
+                    .filter(|command| self.execute(command.clone()).is_err())
+                    .count();
+                if failed > 0 {
+                    log!("{} pasted value(s) did not fit their column", failed);
+                }
+                clipboard::paste_area(at, &rows)
+            }
+        };
+        self.history.end_group();
+        self.grid.recalculate();
+        let last = CellRef::new(
+            pasted.bottom().min(self.grid.num_rows() - 1),
+            pasted.right().min(self.grid.num_cols() - 1),
+        );
+        self.selection.select_cell(at);
+        self.selection.extend_to(last);
+        true
+    }
+
+    // Shows the editor for `cell` over it, starting with `typed` or else
+    // with what is in the cell, and the caret at the end. A checkbox is
+    // toggled instead.
+    pub fn open_editor(&mut self, cell: CellRef, typed: Option<&str>) {
+        self.scroll_into_view(cell);
+        self.selection.select_cell(cell);
+        let Some(editor) = Editor::open(&self.grid, cell, typed) else {
+            self.toggle(cell);
+            return;
+        };
+        let text = editor.text().to_string();
+        self.input.set_type(editor.input_type());
+        self.options.set_inner_html("");
+        if editor.options().is_empty() {
+            let _ = self.input.remove_attribute("list");
+        } else {
+            let document = self.options.owner_document();
+            for value in editor.options() {
+                if let Some(option) = document
+                    .as_ref()
+                    .and_then(|d| d.create_element("option").ok())
+                {
+                    let _ = option.set_attribute("value", value);
+                    let _ = self.options.append_child(&option);
+                }
+            }
+            let _ = self.input.set_attribute("list", OPTIONS_ID);
+        }
+        self.editor = Some(editor);
+        self.input.set_value(&text);
+        let _ = self.input.style().set_property("display", "block");
+        self.place_input();
+        let _ = self.input.focus();
+        let end = text.chars().count() as u32;
+        let _ = self.input.set_selection_range(end, end);
+    }
+
+    // Writes the edited text into the grid through the same path as any
+    // other input, so the column type is enforced. If the text is rejected
+    // the editor stays open with the error as its tooltip, and this returns
+    // false.
+    pub fn commit_edit(&mut self) -> bool {
+        let Some(editor) = self.editor.take() else {
+            return true;
+        };
+        let command = Command::SetInput {
+            cell: editor.cell(),
+            text: editor.text().to_string(),
+        };
+        match self.execute(command) {
+            Ok(_) => {
+                self.grid.recalculate();
+                self.close_input();
+                true
+            }
+            Err(err) => {
+                let _ = self.input.style().set_property("border-color", "#d93025");
+                self.input.set_title(&err.to_string());
+                self.editor = Some(editor);
+                false
+            }
+        }
+    }
+
+    // The selection, if it is a single area whose fill handle is under
+    // canvas position (`x`, `y`).
+    fn fill_handle_at(&self, x: f64, y: f64) -> Option<Area> {
+        if self.editor.is_some() {
+            return None;
+        }
+        match self.selection.areas() {
+            [area] if self.viewport.is_over_fill_handle(&self.grid, area, x, y) => Some(*area),
+            _ => None,
+        }
+    }
+
+    // Extends `source` over `dest` and selects the result.
+    fn fill(&mut self, source: Area, dest: Area) {
+        if self.execute(Command::Fill { source, dest }).is_ok() {
+            self.grid.recalculate();
+            self.selection.select_range(&CellRange::new(vec![dest]));
+        }
+    }
+
+    // Inserts blank rows before the current area, as many as it spans, or
+    // deletes its rows. Columns instead when whole columns are selected.
+    fn edit_structure(&mut self, delete: bool) -> bool {
+        let area = self.selection.current_area();
+        let command = match (area.is_whole_columns(), delete) {
+            (true, false) => Command::InsertColumns {
+                at: area.left(),
+                count: area.num_cols(),
+            },
+            (true, true) => Command::DeleteColumns {
+                at: area.left(),
+                count: area.num_cols(),
+            },
+            (false, false) => Command::InsertRows {
+                at: area.top(),
+                count: area.num_rows(),
+            },
+            (false, true) => Command::DeleteRows {
+                at: area.top(),
+                count: area.num_rows(),
+            },
+        };
+        if let Err(err) = self.execute(command) {
+            log!("{}", err);
+            return false;
+        }
+        if delete {
+            let start = CellRef::new(
+                area.top().min(self.grid.num_rows() - 1),
+                area.left().min(self.grid.num_cols() - 1),
+            );
+            self.selection.select_cell(start);
+        }
+        self.refresh(true)
+    }
+
+    // Makes a change to the grid that can be undone.
+    pub fn execute(&mut self, command: Command) -> Result<Vec<CellRef>, GridError> {
+        self.history.execute(&mut self.grid, command)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    // Undoes the last change and shows the result. Returns false if there
+    // was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let done = self.history.undo(&mut self.grid);
+        self.refresh(done)
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let done = self.history.redo(&mut self.grid);
+        self.refresh(done)
+    }
+
+    // After the grid changed outside of an edit: recalculates, and refits
+    // the spacer in case sizes changed.
+    fn refresh(&mut self, changed: bool) -> bool {
+        if changed {
+            self.grid.recalculate();
+            let _ = self.resize();
+        }
+        changed
+    }
+
+    // Ticks or unticks the checkbox in `cell`.
+    fn toggle(&mut self, cell: CellRef) -> bool {
+        let Some(checked) = self.grid.checkbox(cell) else {
+            return false;
+        };
+        let command = Command::SetValue {
+            cell,
+            value: CellValue::Bool(!checked),
+        };
+        let changed = self.execute(command).is_ok();
+        self.refresh(changed)
+    }
+
+    pub fn cancel_edit(&mut self) {
+        self.editor = None;
+        self.close_input();
+    }
+
+    fn close_input(&mut self) {
+        self.pointing = None;
+        let style = self.input.style();
+        let _ = style.set_property("display", "none");
+        let _ = style.remove_property("border-color");
+        self.input.set_type("text");
+        let _ = self.input.remove_attribute("list");
+        self.input.set_title("");
+        let _ = self.input.blur();
+    }
+
+    // Keeps the input over the cell being edited.
+    fn place_input(&self) {
+        let Some(editor) = &self.editor else {
+            return;
+        };
+        let (x, y, width, height) = self.viewport.cell_rect(&self.grid, editor.cell());
+        let style = self.input.style();
+        let _ = style.set_property("left", &format!("{}px", x));
+        let _ = style.set_property("top", &format!("{}px", y));
+        let _ = style.set_property("min-width", &format!("{}px", width + 1.0));
+        let _ = style.set_property("height", &format!("{}px", height + 1.0));
+    }
+
+    // Scrolls the viewport, and the native scrollbars with it, to show
+    // `cell`.
+    pub fn scroll_into_view(&mut self, cell: CellRef) {
+        if self.viewport.scroll_into_view(&self.grid, cell) {
+            let (left, top) = self.viewport.native_scroll(&self.grid);
+            self.scroller.set_scroll_left(left.round() as i32);
+            self.scroller.set_scroll_top(top.round() as i32);
+        }
+    }
+
+    // Grows the grid to reach `GROW_ROWS` and `GROW_COLUMNS` past both
+    // `cell` and the edge of the screen, and fits the scrollbars to it.
+    fn grow_past(&mut self, cell: CellRef) {
+        let rows = self.viewport.visible_rows(&self.grid).end.max(cell.row + 1);
+        let columns = self
+            .viewport
+            .visible_columns(&self.grid)
+            .end
+            .max(cell.column + 1);
+        if self.grid.grow_to(
+            rows.saturating_add(GROW_ROWS),
+            columns.saturating_add(GROW_COLUMNS),
+        ) {
+            let _ = self.resize();
+            // A capped spacer maps the same scrollbar position elsewhere
+            // once the grid is larger.
+            let (left, top) = self.viewport.native_scroll(&self.grid);
+            self.scroller.set_scroll_left(left.round() as i32);
+            self.scroller.set_scroll_top(top.round() as i32);
+        }
+    }
+
+    // Fits the spacer to the grid and the canvas to the scroller.
+    fn resize(&mut self) -> Result<(), JsValue> {
+        let (spacer_w, spacer_h) = self.viewport.spacer_size(&self.grid);
+        let style = self.spacer.style();
+        style.set_property("width", &format!("{}px", spacer_w))?;
+        style.set_property("height", &format!("{}px", spacer_h))?;
+
+        let (width, height) = (
+            self.scroller.client_width() as f64,
+            self.scroller.client_height() as f64,
+        );
+        self.canvas.set_width(width as u32);
+        self.canvas.set_height(height as u32);
+        self.viewport.resize(&self.grid, width, height);
+        self.place_input();
+        Ok(())
+    }
+}
+
+// Calls `handler` with the app for every `event` on `target`, and redraws
+// when it returns true.
+fn listen<E: JsCast + 'static>(
+    app: &Rc<RefCell<App>>,
+    target: &EventTarget,
+    event: &str,
+    handler: fn(&mut App, &E) -> bool,
+) -> Result<(), JsValue> {
+    let app = app.clone();
+    let closure = Closure::<dyn FnMut(Event)>::new(move |event: Event| {
+        let redraw = handler(&mut app.borrow_mut(), event.unchecked_ref());
+        if redraw {
+            App::request_redraw(&app);
+        }
+    });
+    target.add_event_listener_with_callback(event, closure.as_ref().unchecked_ref())?;
+    closure.forget();
+    Ok(())
+}
+
+fn create<T: JsCast>(document: &Document, tag: &str) -> Result<T, JsValue> {
+    Ok(document.create_element(tag)?.dyn_into::<T>()?)
+}
@@ -0,0 +1,201 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
+
+// Sizes of the columns (or rows) along one side of the grid. Only sizes
+// that differ from the default are stored, and offsets are found with a
+// binary search over the running total of those differences, so a million
+// rows cost nothing until they are resized.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Axis {
+    len: u32,
+    default_size: f64,
+    sizes: BTreeMap<u32, f64>,
+    // Hidden entries keep their size for when they are shown again.
+    hidden: BTreeSet<u32>,
+    // Every entry whose effective size is not the default, in order, with
+    // the sum of `size - default_size` over the entries before it.
+    overrides: Vec<(u32, f64)>,
+    deltas: Vec<f64>,
+}
+
+impl Axis {
+    pub fn new(len: u32, default_size: f64) -> Self {
+        Self {
+            len,
+            default_size,
+            sizes: BTreeMap::new(),
+            hidden: BTreeSet::new(),
+            overrides: Vec::new(),
+            deltas: vec![0.0],
+        }
+    }
+
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn default_size(&self) -> f64 {
+        self.default_size
+    }
+
+    // Size on screen: zero when hidden.
+    pub fn size(&self, index: u32) -> f64 {
+        if index >= self.len || self.hidden.contains(&index) {
+            0.0
+        } else {
+            self.sizes.get(&index).copied().unwrap_or(self.default_size)
+        }
+    }
+
+    // The size the entry has when shown, hidden or not.
+    pub fn stored_size(&self, index: u32) -> f64 {
+        self.sizes.get(&index).copied().unwrap_or(self.default_size)
+    }
+
+    // Resizing to zero hides the entry; any other size also shows it again.
+    pub fn set_size(&mut self, index: u32, size: f64) {
+        if index >= self.len {
+            return;
+        }
+        if size <= 0.0 {
+            self.hidden.insert(index);
+        } else {
+            self.hidden.remove(&index);
+            if size == self.default_size {
+                self.sizes.remove(&index);
+            } else {
+                self.sizes.insert(index, size);
+            }
+        }
+        self.rebuild();
+    }
+
+    pub fn reset(&mut self, index: u32) {
+        self.sizes.remove(&index);
+        self.hidden.remove(&index);
+        self.rebuild();
+    }
+
+    pub fn is_hidden(&self, index: u32) -> bool {
+        self.hidden.contains(&index)
+    }
+
+    pub fn set_hidden(&mut self, index: u32, hidden: bool) {
+        if index >= self.len {
+            return;
+        }
+        if hidden {
+            self.hidden.insert(index);
+        } else {
+            self.hidden.remove(&index);
+        }
+        self.rebuild();
+    }
+
+    // Adds `count` entries of the default size before `index`.
+    pub fn insert(&mut self, index: u32, count: u32) {
+        let shift = |i: u32| if i >= index { i + count } else { i };
+        self.sizes = self.sizes.iter().map(|(i, s)| (shift(*i), *s)).collect();
+        self.hidden = self.hidden.iter().map(|i| shift(*i)).collect();
+        self.len += count;
+        self.rebuild();
+    }
+
+    // Removes the entries `index..index + count`; later ones move up.
+    pub fn remove(&mut self, index: u32, count: u32) {
+        let end = index.saturating_add(count).min(self.len);
+        let shift = |i: u32| {
+            if i < index {
+                Some(i)
+            } else if i >= end {
+                Some(i - (end - index))
+            } else {
+                None
+            }
+        };
+        self.sizes = self
+            .sizes
+            .iter()
+            .filter_map(|(i, s)| Some((shift(*i)?, *s)))
+            .collect();
+        self.hidden = self.hidden.iter().filter_map(|i| shift(*i)).collect();
+        self.len -= end - index.min(end);
+        self.rebuild();
+    }
+
+    // Entries in `range` that are resized or hidden.
+    pub fn customized(&self, range: Range<u32>) -> Vec<u32> {
+        let indices: BTreeSet<u32> = self
+            .sizes
+            .range(range.clone())
+            .map(|(i, _)| *i)
+            .chain(self.hidden.range(range).copied())
+            .collect();
+        indices.into_iter().collect()
+    }
+
+    // Distance from the start of the axis to the start of `index`.
+    pub fn offset(&self, index: u32) -> f64 {
+        let index = index.min(self.len);
+        let k = self.overrides.partition_point(|(i, _)| *i < index);
+        index as f64 * self.default_size + self.deltas[k]
+    }
+
+    pub fn total(&self) -> f64 {
+        self.offset(self.len)
+    }
+
+    // The visible entry covering position `pos`, if any.
+    pub fn index_at(&self, pos: f64) -> Option<u32> {
+        if pos < 0.0 || pos >= self.total() {
+            return None;
+        }
+        // Overrides that start at or before `pos`.
+        let k = self
+            .overrides
+            .partition_point(|(i, _)| self.offset(*i) <= pos);
+        let (base_index, base_pos) = match k.checked_sub(1).map(|k| self.overrides[k]) {
+            Some((index, size)) => {
+                let start = self.offset(index);
+                if pos < start + size {
+                    return Some(index);
+                }
+                (index + 1, start + size)
+            }
+            None => (0, 0.0),
+        };
+        if self.default_size <= 0.0 {
+            return None;
+        }
+        let index = base_index + ((pos - base_pos) / self.default_size) as u32;
+        (index < self.len).then_some(index)
+    }
+
+    // The nearest visible entry at or after `index`, then at or before it.
+    pub fn visible_near(&self, index: u32) -> Option<u32> {
+        (index..self.len)
+            .find(|i| self.size(*i) > 0.0)
+            .or_else(|| (0..index.min(self.len)).rev().find(|i| self.size(*i) > 0.0))
+    }
+
+    fn rebuild(&mut self) {
+        let indices: BTreeSet<u32> = self
+            .sizes
+            .keys()
+            .chain(self.hidden.iter())
+            .copied()
+            .collect();
+        self.overrides = indices.into_iter().map(|i| (i, self.size(i))).collect();
+        self.deltas = Vec::with_capacity(self.overrides.len() + 1);
+        let mut total = 0.0;
+        self.deltas.push(total);
+        for (_, size) in &self.overrides {
+            total += size - self.default_size;
+            self.deltas.push(total);
+        }
+    }
+}
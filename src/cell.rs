@@ -0,0 +1,244 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::datetime::DateTime;
+use crate::format::NumberFormat;
+use crate::formula::Formula;
+use crate::style::CellStyle;
+
+// Spreadsheet error values, as shown in a cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    Div0,
+    Ref,
+    Name,
+    Value,
+    NA,
+    Num,
+    Circular,
+}
+
+impl ErrorKind {
+    pub const ALL: [ErrorKind; 7] = [
+        ErrorKind::Div0,
+        ErrorKind::Ref,
+        ErrorKind::Name,
+        ErrorKind::Value,
+        ErrorKind::NA,
+        ErrorKind::Num,
+        ErrorKind::Circular,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorKind::Div0 => "#DIV/0!",
+            ErrorKind::Ref => "#REF!",
+            ErrorKind::Name => "#NAME?",
+            ErrorKind::Value => "#VALUE!",
+            ErrorKind::NA => "#N/A",
+            ErrorKind::Num => "#NUM!",
+            ErrorKind::Circular => "#CIRC!",
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for ErrorKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ErrorKind::ALL
+            .into_iter()
+            .find(|kind| kind.as_str().eq_ignore_ascii_case(s))
+            .ok_or(())
+    }
+}
+
+// Largest integer an f64 holds exactly. Whole numbers up to this size are
+// kept as `Int` so IDs and money in cents never pick up float noise.
+pub const MAX_EXACT_INT: i64 = 1 << 53;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CellValue {
+    Empty,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    DateTime(DateTime),
+    Error(ErrorKind),
+}
+
+impl CellValue {
+    // Builds a number, preferring `Int` for whole values. Infinite and NaN
+    // results become `#NUM!`.
+    pub fn from_number(n: f64) -> Self {
+        if !n.is_finite() {
+            CellValue::Error(ErrorKind::Num)
+        } else if n.fract() == 0.0 && n.abs() <= MAX_EXACT_INT as f64 {
+            CellValue::Int(n as i64)
+        } else {
+            CellValue::Float(n)
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        matches!(self, CellValue::Empty)
+    }
+
+    pub fn is_number(&self) -> bool {
+        matches!(
+            self,
+            CellValue::Int(_) | CellValue::Float(_) | CellValue::DateTime(_)
+        )
+    }
+
+    // Numbers and dates as an f64; dates give their serial number.
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            CellValue::Int(n) => Some(*n as f64),
+            CellValue::Float(n) => Some(*n),
+            CellValue::DateTime(dt) => Some(dt.serial()),
+            _ => None,
+        }
+    }
+
+    // Position in a sorted column: numbers and dates, then text, then
+    // booleans, then errors, with blanks always last.
+    fn sort_rank(&self) -> u8 {
+        match self {
+            CellValue::Int(_) | CellValue::Float(_) | CellValue::DateTime(_) => 0,
+            CellValue::String(_) => 1,
+            CellValue::Bool(_) => 2,
+            CellValue::Error(_) => 3,
+            CellValue::Empty => 4,
+        }
+    }
+
+    // Spreadsheet sort order. Numbers compare by value whatever their
+    // variant, text compares case-insensitively and FALSE sorts before TRUE.
+    pub fn compare(&self, other: &CellValue) -> Ordering {
+        match (self, other) {
+            (CellValue::String(a), CellValue::String(b)) => a.to_lowercase().cmp(&b.to_lowercase()),
+            (CellValue::Bool(a), CellValue::Bool(b)) => a.cmp(b),
+            (CellValue::Error(a), CellValue::Error(b)) => a.as_str().cmp(b.as_str()),
+            _ => match (self.as_number(), other.as_number()) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                _ => self.sort_rank().cmp(&other.sort_rank()),
+            },
+        }
+    }
+
+    // Equality as the `=` operator sees it: `1` equals `1.0`, "abc" equals
+    // "ABC", and a blank equals 0, "" and FALSE.
+    pub fn equals(&self, other: &CellValue) -> bool {
+        match (self, other) {
+            (CellValue::Empty, CellValue::Empty) => true,
+            (CellValue::Empty, value) | (value, CellValue::Empty) => match value {
+                CellValue::String(s) => s.is_empty(),
+                CellValue::Bool(b) => !b,
+                value => value.as_number() == Some(0.0),
+            },
+            _ => self.compare(other) == Ordering::Equal,
+        }
+    }
+}
+
+impl fmt::Display for CellValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CellValue::Empty => Ok(()),
+            CellValue::Bool(b) => write!(f, "{}", if *b { "TRUE" } else { "FALSE" }),
+            CellValue::Int(n) => write!(f, "{}", n),
+            CellValue::Float(n) => write!(f, "{}", format_number(*n)),
+            CellValue::String(s) => write!(f, "{}", s),
+            CellValue::DateTime(dt) => write!(f, "{}", dt),
+            CellValue::Error(kind) => write!(f, "{}", kind),
+        }
+    }
+}
+
+// Prints a number the way a General-formatted cell shows it: integers
+// without a decimal point, everything else rounded to 15 significant digits.
+pub fn format_number(n: f64) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        return format!("{}", n as i64);
+    }
+    let rounded: f64 = format!("{:.14e}", n).parse().unwrap_or(n);
+    format!("{}", rounded)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CellObject {
+    pub column_id: u32,
+    pub row_id: u32,
+    value: CellValue,
+    formula: Option<Formula>,
+    format: Option<NumberFormat>,
+    style: CellStyle,
+}
+
+impl CellObject {
+    pub fn new(column_id: u32, row_id: u32) -> Self {
+        Self::with_value(column_id, row_id, CellValue::Empty)
+    }
+
+    pub fn with_value(column_id: u32, row_id: u32, value: CellValue) -> Self {
+        Self {
+            column_id,
+            row_id,
+            value,
+            formula: None,
+            format: None,
+            style: CellStyle::default(),
+        }
+    }
+
+    // A cell with no value, formula, format or style need not be stored.
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+            && self.formula.is_none()
+            && self.format.is_none()
+            && self.style == CellStyle::default()
+    }
+
+    pub fn get_value(&self) -> CellValue {
+        self.value.clone()
+    }
+
+    pub fn set_value(&mut self, value: CellValue) {
+        self.value = value;
+    }
+
+    pub fn get_formula(&self) -> Option<&Formula> {
+        self.formula.as_ref()
+    }
+
+    // Attaching a formula keeps the current value as its cached result until
+    // the grid is recalculated.
+    pub fn set_formula(&mut self, formula: Option<Formula>) {
+        self.formula = formula;
+    }
+
+    pub fn get_format(&self) -> Option<&NumberFormat> {
+        self.format.as_ref()
+    }
+
+    pub fn set_format(&mut self, format: Option<NumberFormat>) {
+        self.format = format;
+    }
+
+    pub fn get_style(&self) -> CellStyle {
+        self.style
+    }
+
+    pub fn set_style(&mut self, style: CellStyle) {
+        self.style = style;
+    }
+}
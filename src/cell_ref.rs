@@ -0,0 +1,253 @@
+use std::fmt;
+use std::str::FromStr;
+
+pub const MAX_ROWS: u32 = 1_048_576;
+pub const MAX_COLUMNS: u32 = 16_384;
+
+// Converts a zero-based column index to its letters: 0 -> "A", 25 -> "Z",
+// 26 -> "AA".
+pub fn column_name(column: u32) -> String {
+    let mut n = column as u64 + 1;
+    let mut name = Vec::new();
+
+    while n > 0 {
+        n -= 1;
+        name.push(b'A' + (n % 26) as u8);
+        n /= 26;
+    }
+    name.iter().rev().map(|&c| c as char).collect()
+}
+
+// Inverse of `column_name`. Letters are case-insensitive; returns `None` for
+// anything that is not a run of ASCII letters within the sheet's width.
+pub fn column_index(name: &str) -> Option<u32> {
+    if name.is_empty() {
+        return None;
+    }
+    let mut n: u64 = 0;
+    for c in name.bytes() {
+        if !c.is_ascii_alphabetic() {
+            return None;
+        }
+        n = n * 26 + (c.to_ascii_uppercase() - b'A') as u64 + 1;
+        if n > MAX_COLUMNS as u64 {
+            return None;
+        }
+    }
+    Some(n as u32 - 1)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CellRefError {
+    Empty,
+    Malformed(String),
+    RowOutOfRange(i64),
+    ColumnOutOfRange(i64),
+}
+
+impl fmt::Display for CellRefError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CellRefError::Empty => write!(f, "empty cell reference"),
+            CellRefError::Malformed(s) => write!(f, "malformed cell reference `{}`", s),
+            CellRefError::RowOutOfRange(row) => {
+                write!(f, "row {} is outside 1..={}", row, MAX_ROWS)
+            }
+            CellRefError::ColumnOutOfRange(col) => {
+                write!(f, "column {} is outside 1..={}", col, MAX_COLUMNS)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CellRefError {}
+
+// A single cell address. `row` and `column` are zero-based; the `$` markers
+// of A1 notation are kept so references can be printed back unchanged and
+// adjusted correctly when formulas are copied.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CellRef {
+    pub row: u32,
+    pub column: u32,
+    pub row_absolute: bool,
+    pub column_absolute: bool,
+}
+
+impl CellRef {
+    pub fn new(row: u32, column: u32) -> Self {
+        Self {
+            row,
+            column,
+            row_absolute: false,
+            column_absolute: false,
+        }
+    }
+
+    pub fn absolute(row: u32, column: u32) -> Self {
+        Self {
+            row,
+            column,
+            row_absolute: true,
+            column_absolute: true,
+        }
+    }
+
+    // Checks bounds and builds a relative reference from zero-based indices.
+    pub fn try_new(row: i64, column: i64) -> Result<Self, CellRefError> {
+        if row < 0 || row >= MAX_ROWS as i64 {
+            return Err(CellRefError::RowOutOfRange(row + 1));
+        }
+        if column < 0 || column >= MAX_COLUMNS as i64 {
+            return Err(CellRefError::ColumnOutOfRange(column + 1));
+        }
+        Ok(Self::new(row as u32, column as u32))
+    }
+
+    // The cell `rows` down and `columns` across, keeping the `$` markers, or
+    // `None` if that is off the sheet.
+    pub fn offset(self, rows: i64, columns: i64) -> Option<Self> {
+        let moved = Self::try_new(self.row as i64 + rows, self.column as i64 + columns).ok()?;
+        Some(Self {
+            row_absolute: self.row_absolute,
+            column_absolute: self.column_absolute,
+            ..moved
+        })
+    }
+
+    // How a reference changes when the formula holding it is copied `rows`
+    // down and `columns` across: relative parts move, `$` parts stay.
+    pub fn shift_relative(self, rows: i64, columns: i64) -> Option<Self> {
+        self.offset(
+            if self.row_absolute { 0 } else { rows },
+            if self.column_absolute { 0 } else { columns },
+        )
+    }
+
+    // The same cell with the `$` markers dropped, for use as a lookup key.
+    pub fn to_relative(self) -> Self {
+        Self::new(self.row, self.column)
+    }
+
+    pub fn column_name(&self) -> String {
+        column_name(self.column)
+    }
+
+    // Parses R1C1 notation. Absolute parts (`R3`) are one-based indices,
+    // bracketed parts (`R[-1]`) and bare `R`/`C` are offsets from `origin`.
+    pub fn parse_r1c1(s: &str, origin: CellRef) -> Result<Self, CellRefError> {
+        let malformed = || CellRefError::Malformed(s.to_string());
+        let upper = s.trim().to_ascii_uppercase();
+        if upper.is_empty() {
+            return Err(CellRefError::Empty);
+        }
+        let rest = upper.strip_prefix('R').ok_or_else(malformed)?;
+        let c_at = rest.find('C').ok_or_else(malformed)?;
+        let (row_part, col_part) = (&rest[..c_at], &rest[c_at + 1..]);
+
+        let (row, row_absolute) = parse_r1c1_part(row_part, origin.row).ok_or_else(malformed)?;
+        let (column, column_absolute) =
+            parse_r1c1_part(col_part, origin.column).ok_or_else(malformed)?;
+
+        let cell = CellRef::try_new(row, column)?;
+        Ok(Self {
+            row_absolute,
+            column_absolute,
+            ..cell
+        })
+    }
+
+    pub fn to_r1c1(&self, origin: CellRef) -> String {
+        let part = |value: u32, origin: u32, absolute: bool| {
+            if absolute {
+                (value + 1).to_string()
+            } else if value == origin {
+                String::new()
+            } else {
+                format!("[{}]", value as i64 - origin as i64)
+            }
+        };
+        format!(
+            "R{}C{}",
+            part(self.row, origin.row, self.row_absolute),
+            part(self.column, origin.column, self.column_absolute)
+        )
+    }
+}
+
+// Returns the zero-based index and whether the part was absolute.
+fn parse_r1c1_part(part: &str, origin: u32) -> Option<(i64, bool)> {
+    if part.is_empty() {
+        Some((origin as i64, false))
+    } else if let Some(offset) = part.strip_prefix('[').and_then(|p| p.strip_suffix(']')) {
+        let offset: i64 = offset.parse().ok()?;
+        Some(((origin as i64).checked_add(offset)?, false))
+    } else if part.bytes().all(|b| b.is_ascii_digit()) {
+        let index: i64 = part.parse().ok()?;
+        Some((index - 1, true))
+    } else {
+        None
+    }
+}
+
+impl fmt::Display for CellRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}{}",
+            if self.column_absolute { "$" } else { "" },
+            column_name(self.column),
+            if self.row_absolute { "$" } else { "" },
+            self.row + 1
+        )
+    }
+}
+
+impl FromStr for CellRef {
+    type Err = CellRefError;
+
+    // Parses A1 notation such as `B12`, `$B$12` or `aa1`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(CellRefError::Empty);
+        }
+        let malformed = || CellRefError::Malformed(s.to_string());
+
+        let (column_absolute, rest) = match s.strip_prefix('$') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let letters = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let (col_part, rest) = rest.split_at(letters);
+        let (row_absolute, row_part) = match rest.strip_prefix('$') {
+            Some(rest) => (true, rest),
+            None => (false, rest),
+        };
+        if col_part.is_empty()
+            || row_part.is_empty()
+            || !row_part.bytes().all(|b| b.is_ascii_digit())
+        {
+            return Err(malformed());
+        }
+
+        let column = column_index(col_part).ok_or_else(|| {
+            let width = col_part.bytes().fold(0i64, |n, c| {
+                n.saturating_mul(26)
+                    .saturating_add(c.to_ascii_uppercase() as i64 - 64)
+            });
+            CellRefError::ColumnOutOfRange(width)
+        })?;
+        let row: i64 = row_part
+            .parse()
+            .map_err(|_| CellRefError::RowOutOfRange(i64::MAX))?;
+
+        let cell = CellRef::try_new(row - 1, column as i64)?;
+        Ok(Self {
+            row_absolute,
+            column_absolute,
+            ..cell
+        })
+    }
+}
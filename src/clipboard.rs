@@ -0,0 +1,303 @@
+use crate::cell_ref::CellRef;
+use crate::grid::Grid;
+use crate::history::Command;
+use crate::range::Area;
+
+// What a copy puts on the system clipboard: tab-separated text for plain
+// editors and an HTML table for other spreadsheets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClipboardData {
+    pub text: String,
+    pub html: String,
+}
+
+// The part of `area` worth copying: whole columns or rows stop at the last
+// stored cell instead of running to the end of the sheet.
+pub fn copy_extent(grid: &Grid, area: &Area) -> Option<Area> {
+    if grid.num_rows() == 0 || grid.num_cols() == 0 {
+        return None;
+    }
+    let mut bottom = grid.num_rows() - 1;
+    let mut right = grid.num_cols() - 1;
+    if area.is_whole_columns() || area.is_whole_rows() {
+        let stored = grid.cells().area(area);
+        let (rows, columns) = stored.fold((0, 0), |(r, c), cell| {
+            (r.max(cell.row_id), c.max(cell.column_id))
+        });
+        if area.is_whole_columns() {
+            bottom = bottom.min(rows);
+        }
+        if area.is_whole_rows() {
+            right = right.min(columns);
+        }
+    }
+    area.intersect(&Area::from_bounds(0, 0, bottom, right))
+}
+
+// Renders `area` as the user sees it, row by row.
+pub fn copy(grid: &Grid, area: &Area) -> ClipboardData {
+    let rows: Vec<Vec<String>> = match copy_extent(grid, area) {
+        Some(area) => (area.top()..=area.bottom())
+            .map(|row| {
+                (area.left()..=area.right())
+                    .map(|column| grid.display_value(CellRef::new(row, column)).text)
+                    .collect()
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+    ClipboardData {
+        text: to_tsv(&rows),
+        html: to_html(&rows),
+    }
+}
+
+pub fn to_tsv(rows: &[Vec<String>]) -> String {
+    let mut out = String::new();
+    for row in rows {
+        let fields: Vec<String> = row.iter().map(|f| quote_field(f)).collect();
+        out.push_str(&fields.join("\t"));
+        out.push('\n');
+    }
+    out
+}
+
+fn quote_field(field: &str) -> String {
+    if field.contains(['\t', '\n', '\r', '"']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub fn to_html(rows: &[Vec<String>]) -> String {
+    let mut out = String::from("<table>");
+    for row in rows {
+        out.push_str("<tr>");
+        for field in row {
+            out.push_str("<td>");
+            out.push_str(&escape_html(field));
+            out.push_str("</td>");
+        }
+        out.push_str("</tr>");
+    }
+    out.push_str("</table>");
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            // Raw tabs would collapse to a space when read back.
+            '\t' => out.push_str("&#9;"),
+            '\n' => out.push_str("<br>"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// Splits tab-separated text into rows of fields. Quoted fields may hold
+// tabs, newlines and doubled quotes; a trailing newline adds no row.
+pub fn parse_tsv(text: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut chars = text.chars().peekable();
+    let mut at_field_start = true;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if at_field_start => {
+                while let Some(c) = chars.next() {
+                    if c != '"' {
+                        field.push(c);
+                    } else if chars.peek() == Some(&'"') {
+                        chars.next();
+                        field.push('"');
+                    } else {
+                        break;
+                    }
+                }
+                at_field_start = false;
+            }
+            '\t' => {
+                row.push(std::mem::take(&mut field));
+                at_field_start = true;
+            }
+            '\r' | '\n' => {
+                if c == '\r' && chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+                at_field_start = true;
+            }
+            c => {
+                field.push(c);
+                at_field_start = false;
+            }
+        }
+    }
+    if !at_field_start || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+// Reads the cells of the first `<table>` in `html`, as other spreadsheets
+// put on the clipboard. Returns `None` when there is no table.
+pub fn parse_html(html: &str) -> Option<Vec<Vec<String>>> {
+    let start = html.to_ascii_lowercase().find("<table")?;
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut cell: Option<String> = None;
+    let mut rest = &html[start..];
+    while !rest.is_empty() {
+        if let Some(tag_start) = rest.strip_prefix('<') {
+            let end = tag_start.find('>').map_or(tag_start.len(), |i| i + 1);
+            let tag = tag_start[..end.saturating_sub(1)]
+                .trim()
+                .to_ascii_lowercase();
+            let name: String = tag
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '/')
+                .collect();
+            match name.as_str() {
+                "tr" => rows.push(Vec::new()),
+                "td" | "th" => {
+                    if rows.is_empty() {
+                        rows.push(Vec::new());
+                    }
+                    cell = Some(String::new());
+                }
+                "/td" | "/th" => {
+                    if let (Some(text), Some(row)) = (cell.take(), rows.last_mut()) {
+                        row.push(decode_entities(text.trim()));
+                    }
+                }
+                "br" | "br/" => {
+                    if let Some(text) = cell.as_mut() {
+                        text.push('\n');
+                    }
+                }
+                "/table" => break,
+                _ => {}
+            }
+            rest = &tag_start[end..];
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            if let Some(text) = cell.as_mut() {
+                text.push_str(&collapse_whitespace(&rest[..end]));
+            }
+            rest = &rest[end..];
+        }
+    }
+    Some(rows)
+}
+
+// HTML source whitespace is layout, not content.
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::new();
+    let mut space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            space = true;
+        } else {
+            if space {
+                out.push(' ');
+            }
+            space = false;
+            out.push(c);
+        }
+    }
+    if space {
+        out.push(' ');
+    }
+    out
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let decoded = rest.find(';').and_then(|end| {
+            let c = match &rest[1..end] {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                "nbsp" => ' ',
+                entity => {
+                    let code = match entity.strip_prefix("#x").or(entity.strip_prefix("#X")) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => entity.strip_prefix('#')?.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((c, end + 1))
+        });
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// The edits that enter `rows` into the grid with its top-left field at
+// `at`, as if each had been typed, so every column's type applies. Fields
+// that fall off the sheet are dropped.
+pub fn paste_commands(grid: &Grid, at: CellRef, rows: &[Vec<String>]) -> Vec<Command> {
+    let mut commands = Vec::new();
+    for (i, row) in rows.iter().enumerate() {
+        for (j, field) in row.iter().enumerate() {
+            if let Some(cell) = at.offset(i as i64, j as i64).filter(|c| grid.contains(*c)) {
+                commands.push(Command::SetInput {
+                    cell,
+                    text: field.clone(),
+                });
+            }
+        }
+    }
+    commands
+}
+
+// Applies `paste_commands` directly. Returns the cells whose text the
+// column rejected.
+pub fn paste_text(grid: &mut Grid, at: CellRef, rows: &[Vec<String>]) -> Vec<CellRef> {
+    let mut failed = Vec::new();
+    for command in paste_commands(grid, at, rows) {
+        if let (Err(_), Command::SetInput { cell, .. }) = (command.apply(grid), &command) {
+            failed.push(*cell);
+        }
+    }
+    failed
+}
+
+// The area `rows` covers when pasted at `at`.
+pub fn paste_area(at: CellRef, rows: &[Vec<String>]) -> Area {
+    let height = rows.len().max(1) as u32;
+    let width = rows.iter().map(Vec::len).max().unwrap_or(1).max(1) as u32;
+    Area::from_bounds(
+        at.row,
+        at.column,
+        at.row.saturating_add(height - 1),
+        at.column.saturating_add(width - 1),
+    )
+}
@@ -0,0 +1,365 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::cell::{format_number, CellValue, ErrorKind};
+use crate::cell_ref::{column_name, CellRef};
+use crate::datetime::DateTime;
+use crate::format::NumberFormat;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColumnType {
+    // Untyped: input is interpreted the way a spreadsheet would.
+    General,
+    String,
+    Int,
+    Float,
+    // Shown as a checkbox.
+    Bool,
+    Date,
+    DateTime,
+    // Amount in the currency with the given ISO 4217 code, e.g. "USD".
+    Currency(String),
+    // Stored as a fraction: 50% is 0.5.
+    Percent,
+    // One of a fixed list of values.
+    Enum(Vec<String>),
+}
+
+// How a cell of a given column type is edited.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EditorKind {
+    Text,
+    Checkbox,
+    Date,
+    DateTime,
+    Dropdown(Vec<String>),
+}
+
+// Input or an existing value that does not fit a column's type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoercionError {
+    pub input: String,
+    pub expected: ColumnType,
+}
+
+impl fmt::Display for CoercionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` is not a valid {}", self.input, self.expected)
+    }
+}
+
+impl std::error::Error for CoercionError {}
+
+// Outcome of changing a column's type. Cells that could not be converted
+// keep their old value and are listed in `failed`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConversionReport {
+    pub converted: usize,
+    pub failed: Vec<CellRef>,
+}
+
+impl ColumnType {
+    // Turns text typed into a cell of this column into a value. Blank input
+    // clears the cell in every column type.
+    pub fn parse(&self, input: &str) -> Result<CellValue, CoercionError> {
+        let trimmed = input.trim();
+        if trimmed.is_empty() {
+            return Ok(CellValue::Empty);
+        }
+        let value = match self {
+            ColumnType::General => Some(infer(input)),
+            ColumnType::String => Some(CellValue::String(input.to_string())),
+            ColumnType::Int => parse_number(trimmed)
+                .filter(|n| n.fract() == 0.0)
+                .map(CellValue::from_number)
+                .filter(|v| matches!(v, CellValue::Int(_))),
+            ColumnType::Float => parse_number(trimmed).map(CellValue::Float),
+            ColumnType::Bool => parse_bool(trimmed).map(CellValue::Bool),
+            ColumnType::Date => trimmed
+                .parse::<DateTime>()
+                .ok()
+                .filter(|dt| !dt.has_time())
+                .map(CellValue::DateTime),
+            ColumnType::DateTime => trimmed.parse().ok().map(CellValue::DateTime),
+            ColumnType::Currency(code) => parse_currency(trimmed, code).map(CellValue::Float),
+            ColumnType::Percent => parse_number(trimmed.strip_suffix('%').unwrap_or(trimmed))
+                .map(|n| CellValue::Float(n / 100.0)),
+            ColumnType::Enum(options) => options
+                .iter()
+                .find(|option| option.eq_ignore_ascii_case(trimmed))
+                .map(|option| CellValue::String(option.clone())),
+        };
+        value.ok_or_else(|| self.error(input))
+    }
+
+    // Converts a value already in the grid to this column's type, as when
+    // the type of a column changes. Blanks and error values pass through.
+    pub fn convert(&self, value: &CellValue) -> Result<CellValue, CoercionError> {
+        use CellValue as V;
+        use ColumnType as T;
+
+        let converted = match (self, value) {
+            (_, V::Empty) | (_, V::Error(_)) | (T::General, _) => Some(value.clone()),
+            (T::String, V::String(_)) => Some(value.clone()),
+            (T::String, value) => Some(V::String(value.to_string())),
+            (T::Enum(_), V::String(s)) => return self.parse(s),
+            (T::Enum(_), value) => return self.parse(&value.to_string()),
+            (_, V::String(s)) => return self.parse(s),
+            (T::Int, V::Int(_)) => Some(value.clone()),
+            (T::Int, V::Float(n)) if n.fract() == 0.0 => Some(V::from_number(*n)),
+            (T::Float | T::Currency(_) | T::Percent, V::Int(_) | V::Float(_)) => {
+                value.as_number().map(V::Float)
+            }
+            (T::Bool, V::Bool(_)) => Some(value.clone()),
+            (T::Bool, V::Int(n)) if *n == 0 || *n == 1 => Some(V::Bool(*n == 1)),
+            (T::Date, V::DateTime(dt)) => {
+                Some(V::DateTime(DateTime::from_serial(dt.serial().floor())))
+            }
+            (T::Date, V::Int(n)) => Some(V::DateTime(DateTime::from_serial(*n as f64))),
+            (T::DateTime, V::DateTime(_)) => Some(value.clone()),
+            (T::DateTime, V::Int(_) | V::Float(_)) => value
+                .as_number()
+                .map(|n| V::DateTime(DateTime::from_serial(n))),
+            _ => None,
+        };
+        converted.ok_or_else(|| self.error(&value.to_string()))
+    }
+
+    // Text shown for `value` in a column of this type.
+    pub fn display(&self, value: &CellValue) -> String {
+        match (self, value) {
+            (ColumnType::Date, CellValue::DateTime(dt)) => {
+                let (year, month, day) = dt.ymd();
+                format!("{:04}-{:02}-{:02}", year, month, day)
+            }
+            (ColumnType::Currency(code), value) if value.is_number() => {
+                let n = value.as_number().unwrap_or_default();
+                let (symbol, decimals) = currency_symbol(code);
+                let amount = group_thousands(&format!("{:.*}", decimals, n.abs()));
+                let sign = if n < 0.0 { "-" } else { "" };
+                format!("{}{}{}", sign, symbol, amount)
+            }
+            (ColumnType::Percent, value) if value.is_number() => {
+                let n = value.as_number().unwrap_or_default();
+                format!("{}%", format_number(n * 100.0))
+            }
+            _ => value.to_string(),
+        }
+    }
+
+    pub fn editor(&self) -> EditorKind {
+        match self {
+            ColumnType::Bool => EditorKind::Checkbox,
+            ColumnType::Date => EditorKind::Date,
+            ColumnType::DateTime => EditorKind::DateTime,
+            ColumnType::Enum(options) => EditorKind::Dropdown(options.clone()),
+            _ => EditorKind::Text,
+        }
+    }
+
+    // Whether `value` is acceptable as the content of a cell of this type.
+    pub fn validate(&self, value: &CellValue) -> bool {
+        self.convert(value)
+            .is_ok_and(|converted| converted == *value)
+    }
+
+    fn error(&self, input: &str) -> CoercionError {
+        CoercionError {
+            input: input.to_string(),
+            expected: self.clone(),
+        }
+    }
+}
+
+fn parse_bool(input: &str) -> Option<bool> {
+    match input.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "y" | "1" | "x" => Some(true),
+        "false" | "no" | "n" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+// Symbol and minor-unit digits for the common currencies; other codes are
+// shown as the code itself.
+fn currency_symbol(code: &str) -> (String, usize) {
+    match code.to_ascii_uppercase().as_str() {
+        "USD" => ("$".to_string(), 2),
+        "EUR" => ("€".to_string(), 2),
+        "GBP" => ("£".to_string(), 2),
+        "JPY" => ("¥".to_string(), 0),
+        "CNY" => ("¥".to_string(), 2),
+        "INR" => ("₹".to_string(), 2),
+        code => (format!("{} ", code), 2),
+    }
+}
+
+// Accepts an amount with an optional symbol or code before or after it, and
+// a leading `-` or accounting-style parentheses for negatives: `$1,200.50`,
+// `-$3`, `(12) EUR`.
+fn parse_currency(input: &str, code: &str) -> Option<f64> {
+    let (symbol, _) = currency_symbol(code);
+    let strip_markers = |mut amount: &'_ str| {
+        for marker in [symbol.trim(), code] {
+            amount = amount.strip_prefix(marker).unwrap_or(amount).trim();
+            amount = amount.strip_suffix(marker).unwrap_or(amount).trim();
+        }
+        amount.to_string()
+    };
+
+    let amount = strip_markers(input);
+    let (mut negative, amount) = match amount.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+        Some(inner) => (true, inner.trim().to_string()),
+        None => (false, amount.clone()),
+    };
+    let amount = match amount.strip_prefix('-') {
+        Some(rest) => {
+            negative = !negative;
+            strip_markers(rest)
+        }
+        None => strip_markers(&amount),
+    };
+    let n = parse_number(&amount)?;
+    Some(if negative { -n } else { n })
+}
+
+// Inserts `,` between groups of three digits in the integer part.
+pub fn group_thousands(number: &str) -> String {
+    let (integer, fraction) = match number.find('.') {
+        Some(dot) => number.split_at(dot),
+        None => (number, ""),
+    };
+    let (sign, digits) = match integer.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", integer),
+    };
+    let mut grouped = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    format!("{}{}{}", sign, grouped, fraction)
+}
+
+// Guesses the value of untyped input: booleans, numbers (with thousands
+// separators or a trailing %), ISO dates and error literals, else text.
+pub fn infer(input: &str) -> CellValue {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        CellValue::Empty
+    } else if trimmed.eq_ignore_ascii_case("TRUE") {
+        CellValue::Bool(true)
+    } else if trimmed.eq_ignore_ascii_case("FALSE") {
+        CellValue::Bool(false)
+    } else if let Some(n) = parse_number(trimmed) {
+        CellValue::from_number(n)
+    } else if let Some(n) = trimmed.strip_suffix('%').and_then(parse_number) {
+        CellValue::from_number(n / 100.0)
+    } else if let Ok(date) = trimmed.parse::<DateTime>() {
+        CellValue::DateTime(date)
+    } else if let Ok(kind) = trimmed.parse::<ErrorKind>() {
+        CellValue::Error(kind)
+    } else {
+        CellValue::String(input.to_string())
+    }
+}
+
+// Parses a plain decimal number, allowing `,` only as a thousands separator.
+pub fn parse_number(input: &str) -> Option<f64> {
+    let input = input.trim();
+    let digits = input.trim_start_matches(['-', '+']);
+    let integer = digits.split(['.', 'e', 'E']).next().unwrap_or("");
+    if integer.contains(',') {
+        let mut groups = integer.split(',');
+        let first = groups.next()?;
+        if first.is_empty() || first.len() > 3 || groups.any(|g| g.len() != 3) {
+            return None;
+        }
+    }
+    let plain = input.replace(',', "");
+    if plain.is_empty() || !plain.bytes().any(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    // Rust accepts "inf" and "NaN"; spreadsheets do not.
+    if plain
+        .bytes()
+        .any(|b| b.is_ascii_alphabetic() && b != b'e' && b != b'E')
+    {
+        return None;
+    }
+    // Overflowing to infinity is no more a number than "inf" is.
+    plain.parse().ok().filter(|n: &f64| n.is_finite())
+}
+
+impl fmt::Display for ColumnType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColumnType::General => write!(f, "general"),
+            ColumnType::String => write!(f, "string"),
+            ColumnType::Int => write!(f, "int"),
+            ColumnType::Float => write!(f, "float"),
+            ColumnType::Bool => write!(f, "bool"),
+            ColumnType::Date => write!(f, "date"),
+            ColumnType::DateTime => write!(f, "datetime"),
+            ColumnType::Currency(code) => write!(f, "currency:{}", code),
+            ColumnType::Percent => write!(f, "percent"),
+            ColumnType::Enum(options) => write!(f, "enum:{}", options.join("|")),
+        }
+    }
+}
+
+impl FromStr for ColumnType {
+    type Err = String;
+
+    // Parses the names printed by `Display`. Parameterised types take their
+    // argument after a colon: `currency:EUR`, `enum:low|medium|high`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (name, argument) = match s.split_once(':') {
+            Some((name, argument)) => (name, Some(argument.trim())),
+            None => (s, None),
+        };
+        match (name.to_ascii_lowercase().as_str(), argument) {
+            ("general", None) => Ok(ColumnType::General),
+            ("string" | "text", None) => Ok(ColumnType::String),
+            ("int" | "integer", None) => Ok(ColumnType::Int),
+            ("float" | "number", None) => Ok(ColumnType::Float),
+            ("bool" | "boolean", None) => Ok(ColumnType::Bool),
+            ("date", None) => Ok(ColumnType::Date),
+            ("datetime", None) => Ok(ColumnType::DateTime),
+            ("percent", None) => Ok(ColumnType::Percent),
+            ("currency", Some(code)) if !code.is_empty() => {
+                Ok(ColumnType::Currency(code.to_ascii_uppercase()))
+            }
+            ("enum", Some(options)) if !options.is_empty() => Ok(ColumnType::Enum(
+                options.split('|').map(|o| o.trim().to_string()).collect(),
+            )),
+            _ => Err(format!("unknown column type `{}`", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Column {
+    pub column_id: u32,
+    pub column_type: ColumnType,
+    // Default number format for cells without one of their own.
+    pub format: Option<NumberFormat>,
+}
+
+impl Column {
+    pub fn new(column_id: u32) -> Self {
+        let column_type = ColumnType::General;
+
+        Self {
+            column_id,
+            column_type,
+            format: None,
+        }
+    }
+
+    pub fn get_column_name(&self) -> String {
+        column_name(self.column_id)
+    }
+}
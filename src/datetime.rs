@@ -0,0 +1,202 @@
+use std::fmt;
+use std::str::FromStr;
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+// Days from 0000-03-01 (the epoch of `days_from_civil`) to 1899-12-30.
+const SERIAL_EPOCH: i64 = 693_899;
+// Serials of 0001-01-01 and 10000-01-01, the range dates can be shown in.
+const MIN_SERIAL: f64 = -693_593.0;
+const MAX_SERIAL: f64 = 2_958_466.0;
+
+pub const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+// Indexed by `DateTime::weekday`.
+pub const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+// A date and time stored the way spreadsheets store them: a serial number
+// of days since 1899-12-30, with the time of day as the fractional part.
+// Serial 1 is 1899-12-31 and serial 45000 is 2023-03-15.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct DateTime {
+    serial: f64,
+}
+
+impl DateTime {
+    pub fn from_serial(serial: f64) -> Self {
+        Self { serial }
+    }
+
+    pub fn serial(&self) -> f64 {
+        self.serial
+    }
+
+    pub fn from_ymd(year: i32, month: u32, day: u32) -> Option<Self> {
+        Self::from_ymd_hms(year, month, day, 0, 0, 0)
+    }
+
+    pub fn from_ymd_hms(
+        year: i32,
+        month: u32,
+        day: u32,
+        hour: u32,
+        minute: u32,
+        second: u32,
+    ) -> Option<Self> {
+        if !(1..=12).contains(&month)
+            || day == 0
+            || day > days_in_month(year, month)
+            || hour > 23
+            || minute > 59
+            || second > 59
+        {
+            return None;
+        }
+        let days = days_from_civil(year, month, day) - SERIAL_EPOCH;
+        let seconds = (hour * 3600 + minute * 60 + second) as f64;
+        Some(Self::from_serial(days as f64 + seconds / SECONDS_PER_DAY))
+    }
+
+    // Whether the serial falls in years 1 to 9999. Outside them the date
+    // parts are meaningless and formats show `#####` instead.
+    pub fn in_range(&self) -> bool {
+        (MIN_SERIAL..MAX_SERIAL).contains(&self.serial)
+    }
+
+    // Out of range serials are clamped to the first or last day.
+    pub fn ymd(&self) -> (i32, u32, u32) {
+        civil_from_days(self.day() + SERIAL_EPOCH)
+    }
+
+    pub fn hms(&self) -> (u32, u32, u32) {
+        let seconds = (self.serial.fract().abs() * SECONDS_PER_DAY).round() as u32;
+        let seconds = seconds.min(86_399);
+        (seconds / 3600, seconds / 60 % 60, seconds % 60)
+    }
+
+    pub fn has_time(&self) -> bool {
+        self.hms() != (0, 0, 0)
+    }
+
+    // 0 is Sunday, 6 is Saturday.
+    pub fn weekday(&self) -> u32 {
+        (self.day() + 6).rem_euclid(7) as u32
+    }
+
+    fn day(&self) -> i64 {
+        self.serial.floor().clamp(MIN_SERIAL, MAX_SERIAL - 1.0) as i64
+    }
+
+    // The same time of day, `months` months later. The day is clamped to the
+    // end of the target month, so Jan 31 + 1 month is Feb 28/29.
+    pub fn add_months(&self, months: i32) -> Option<Self> {
+        let (year, month, day) = self.ymd();
+        let total = (year * 12 + month as i32 - 1).checked_add(months)?;
+        let (year, month) = (total.div_euclid(12), total.rem_euclid(12) as u32 + 1);
+        let day = day.min(days_in_month(year, month));
+        let (h, m, s) = self.hms();
+        Self::from_ymd_hms(year, month, day, h, m, s)
+    }
+}
+
+pub fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+pub fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+// Howard Hinnant's civil calendar algorithms, counting days from 0000-03-01.
+fn days_from_civil(year: i32, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year } as i64;
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe
+}
+
+fn civil_from_days(days: i64) -> (i32, u32, u32) {
+    let era = days.div_euclid(146_097);
+    let doe = days - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year as i32, month, day)
+}
+
+impl fmt::Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.in_range() {
+            return write!(f, "#####");
+        }
+        let (year, month, day) = self.ymd();
+        write!(f, "{:04}-{:02}-{:02}", year, month, day)?;
+        if self.has_time() {
+            let (h, m, s) = self.hms();
+            write!(f, " {:02}:{:02}:{:02}", h, m, s)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for DateTime {
+    type Err = ();
+
+    // Accepts ISO dates with an optional time: `2024-01-31`,
+    // `2024-01-31 13:45`, `2024-01-31T13:45:30`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (date, time) = match s.split_once(['T', ' ']) {
+            Some((date, time)) => (date, Some(time.trim())),
+            None => (s, None),
+        };
+
+        let mut parts = date.splitn(3, '-');
+        let mut next = || parts.next().ok_or(())?.parse::<u32>().map_err(|_| ());
+        let (year, month, day) = (next()?, next()?, next()?);
+
+        let (h, m, sec) = match time {
+            None => (0, 0, 0),
+            Some(time) => {
+                let mut parts = time.splitn(3, ':');
+                let mut next = || parts.next().map(|p| p.parse::<u32>().map_err(|_| ()));
+                let h = next().ok_or(())??;
+                let m = next().ok_or(())??;
+                let s = next().unwrap_or(Ok(0))?;
+                (h, m, s)
+            }
+        };
+        Self::from_ymd_hms(year as i32, month, day, h, m, sec).ok_or(())
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use crate::cell_ref::CellRef;
+use crate::range::Area;
+
+// Tracks which formula cells read which cells. Single-cell references are
+// indexed by the cell they point at; multi-cell ranges are kept as areas and
+// matched on lookup, so `=SUM(A:A)` costs one entry rather than a million.
+// All keys are relative (`$` markers dropped).
+//
+// Circular references are found as edges are added: a new cycle has to run
+// through the cell whose precedents changed, so only that cell is checked.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DependencyGraph {
+    precedents: BTreeMap<CellRef, Vec<Area>>,
+    cell_dependents: BTreeMap<CellRef, BTreeSet<CellRef>>,
+    range_dependents: Vec<(Area, CellRef)>,
+    // Every formula cell that reads itself, directly or not.
+    circular: BTreeSet<CellRef>,
+}
+
+impl DependencyGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Replaces everything `cell` reads with `areas`.
+    pub fn set_precedents(&mut self, cell: CellRef, areas: Vec<Area>) {
+        let cell = cell.to_relative();
+        self.remove(cell);
+
+        for area in &areas {
+            if area.start.row == area.end.row && area.start.column == area.end.column {
+                self.cell_dependents
+                    .entry(area.start.to_relative())
+                    .or_default()
+                    .insert(cell);
+            } else {
+                self.range_dependents.push((*area, cell));
+            }
+        }
+        if !areas.is_empty() {
+            self.precedents.insert(cell, areas);
+            self.find_cycles_through(cell);
+        }
+    }
+
+    // Forgets everything `cell` reads, e.g. when its formula is replaced by
+    // a plain value.
+    pub fn remove(&mut self, cell: CellRef) {
+        let cell = cell.to_relative();
+        let Some(areas) = self.precedents.remove(&cell) else {
+            return;
+        };
+        for area in areas {
+            let key = area.start.to_relative();
+            if let Some(dependents) = self.cell_dependents.get_mut(&key) {
+                dependents.remove(&cell);
+                if dependents.is_empty() {
+                    self.cell_dependents.remove(&key);
+                }
+            }
+        }
+        self.range_dependents
+            .retain(|(_, dependent)| *dependent != cell);
+
+        // Cycles through `cell` may be broken now; others are untouched.
+        if self.circular.contains(&cell) {
+            let members = std::mem::take(&mut self.circular);
+            self.circular = members
+                .into_iter()
+                .filter(|member| self.transitive_dependents([*member]).contains(member))
+                .collect();
+        }
+    }
+
+    // Adds every cell on a cycle through `cell` to `circular`: those that
+    // both read `cell` and are read by it, through any chain of formulas.
+    fn find_cycles_through(&mut self, cell: CellRef) {
+        let downstream = self.transitive_dependents([cell]);
+        if !downstream.contains(&cell) {
+            return;
+        }
+        let members: Vec<CellRef> = downstream
+            .into_iter()
+            .filter(|d| *d == cell || self.transitive_dependents([*d]).contains(&cell))
+            .collect();
+        self.circular.extend(members);
+    }
+
+    // Whether `cell` is part of a circular reference.
+    pub fn is_circular(&self, cell: CellRef) -> bool {
+        self.circular.contains(&cell.to_relative())
+    }
+
+    pub fn precedents(&self, cell: CellRef) -> &[Area] {
+        self.precedents
+            .get(&cell.to_relative())
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    // Formula cells that read `cell` directly.
+    pub fn dependents(&self, cell: CellRef) -> BTreeSet<CellRef> {
+        let cell = cell.to_relative();
+        let mut dependents = self.cell_dependents.get(&cell).cloned().unwrap_or_default();
+        dependents.extend(
+            self.range_dependents
+                .iter()
+                .filter(|(area, _)| area.contains(cell))
+                .map(|(_, dependent)| *dependent),
+        );
+        dependents
+    }
+
+    // Every formula cell that reads any of `changed`, directly or through
+    // other formulas.
+    pub fn transitive_dependents(
+        &self,
+        changed: impl IntoIterator<Item = CellRef>,
+    ) -> BTreeSet<CellRef> {
+        let mut found = BTreeSet::new();
+        let mut queue: VecDeque<CellRef> = changed.into_iter().collect();
+
+        while let Some(cell) = queue.pop_front() {
+            for dependent in self.dependents(cell) {
+                if found.insert(dependent) {
+                    queue.push_back(dependent);
+                }
+            }
+        }
+        found
+    }
+
+    // Splits `cells` into groups and orders them so each group comes after
+    // the groups it reads. A group is either a single cell or the members of
+    // a cycle, which have no valid order among themselves.
+    pub fn evaluation_order(&self, cells: &BTreeSet<CellRef>) -> Vec<Vec<CellRef>> {
+        let edges: BTreeMap<CellRef, Vec<CellRef>> = cells
+            .iter()
+            .map(|cell| {
+                let dependents = self
+                    .dependents(*cell)
+                    .into_iter()
+                    .filter(|d| cells.contains(d))
+                    .collect();
+                (*cell, dependents)
+            })
+            .collect();
+
+        // Tarjan's algorithm, iterative so long chains of formulas cannot
+        // overflow the stack. Components come out sinks first.
+        let mut next_index = 0;
+        let mut index: BTreeMap<CellRef, usize> = BTreeMap::new();
+        let mut lowlink: BTreeMap<CellRef, usize> = BTreeMap::new();
+        let mut stack: Vec<CellRef> = Vec::new();
+        let mut on_stack: BTreeSet<CellRef> = BTreeSet::new();
+        let mut components = Vec::new();
+
+        for root in cells {
+            if index.contains_key(root) {
+                continue;
+            }
+            let mut work = vec![(*root, 0)];
+            index.insert(*root, next_index);
+            lowlink.insert(*root, next_index);
+            next_index += 1;
+            stack.push(*root);
+            on_stack.insert(*root);
+
+            while let Some((node, edge)) = work.last_mut() {
+                let node = *node;
+                if let Some(&next) = edges[&node].get(*edge) {
+                    *edge += 1;
+                    if let Entry::Vacant(entry) = index.entry(next) {
+                        entry.insert(next_index);
+                        lowlink.insert(next, next_index);
+                        next_index += 1;
+                        stack.push(next);
+                        on_stack.insert(next);
+                        work.push((next, 0));
+                    } else if on_stack.contains(&next) {
+                        let low = lowlink[&node].min(index[&next]);
+                        lowlink.insert(node, low);
+                    }
+                    continue;
+                }
+
+                work.pop();
+                if let Some((parent, _)) = work.last() {
+                    let low = lowlink[parent].min(lowlink[&node]);
+                    lowlink.insert(*parent, low);
+                }
+                if lowlink[&node] == index[&node] {
+                    let mut component = Vec::new();
+                    while let Some(member) = stack.pop() {
+                        on_stack.remove(&member);
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    component.sort();
+                    components.push(component);
+                }
+            }
+        }
+
+        components.reverse();
+        components
+    }
+
+    // True when `group`, as returned by `evaluation_order`, is a cycle.
+    pub fn is_cycle(&self, group: &[CellRef]) -> bool {
+        match group {
+            [cell] => self.dependents(*cell).contains(cell),
+            _ => group.len() > 1,
+        }
+    }
+
+    // If `cell` reads itself through a chain of formulas, returns that chain
+    // starting at `cell`: each cell is read by the one after it, and the
+    // last is read by `cell`.
+    pub fn find_cycle(&self, cell: CellRef) -> Option<Vec<CellRef>> {
+        let cell = cell.to_relative();
+        let mut parent: BTreeMap<CellRef, CellRef> = BTreeMap::new();
+        let mut queue = VecDeque::from([cell]);
+
+        while let Some(current) = queue.pop_front() {
+            for dependent in self.dependents(current) {
+                if dependent == cell {
+                    let mut path = vec![current];
+                    while let Some(previous) = parent.get(path.last().unwrap()) {
+                        path.push(*previous);
+                    }
+                    path.reverse();
+                    return Some(path);
+                }
+                if dependent != cell && !parent.contains_key(&dependent) {
+                    parent.insert(dependent, current);
+                    queue.push_back(dependent);
+                }
+            }
+        }
+        None
+    }
+
+    // Every cycle among the formulas in the graph, as kept up to date by
+    // `set_precedents` and `remove`.
+    pub fn cycles(&self) -> Vec<Vec<CellRef>> {
+        self.evaluation_order(&self.circular)
+            .into_iter()
+            .filter(|group| self.is_cycle(group))
+            .collect()
+    }
+}
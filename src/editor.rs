@@ -0,0 +1,158 @@
+use crate::cell::CellValue;
+use crate::cell_ref::CellRef;
+use crate::column::EditorKind;
+use crate::grid::Grid;
+use crate::range::Area;
+
+// Characters after which a formula expects an operand, so that clicking a
+// cell inserts a reference rather than ending the edit.
+const OPERAND_START: &[char] = &['=', '(', ',', '+', '-', '*', '/', '^', '&', '<', '>'];
+
+// The text being typed into a cell, before it is committed to the grid.
+// Positions are counted in characters.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Editor {
+    cell: CellRef,
+    text: String,
+    // The reference most recently inserted by pointing, so that pointing
+    // again replaces it instead of adding another.
+    pointed: Option<(usize, usize)>,
+    kind: EditorKind,
+}
+
+impl Editor {
+    pub fn new(cell: CellRef, text: &str) -> Self {
+        Self {
+            cell,
+            text: text.to_string(),
+            pointed: None,
+            kind: EditorKind::Text,
+        }
+    }
+
+    // Starts editing `cell` with the editor its column calls for, from what
+    // is in it or from `typed` when the edit began with typing. Typing
+    // always goes into a text box, which still suggests an enum's options,
+    // and formulas are always edited as text. A checkbox is toggled rather
+    // than edited, so there is no editor for it.
+    pub fn open(grid: &Grid, cell: CellRef, typed: Option<&str>) -> Option<Self> {
+        let kind = grid.column_type(cell.column).editor();
+        if let Some(typed) = typed {
+            let kind = match kind {
+                EditorKind::Dropdown(_) => kind,
+                _ => EditorKind::Text,
+            };
+            return Some(Self::new(cell, typed).with_kind(kind));
+        }
+        if grid.get_formula(cell).is_some() {
+            return Some(Self::new(cell, &grid.edit_text(cell)));
+        }
+        // Date pickers only take ISO dates, and nothing else.
+        let date = match grid.get_value(cell) {
+            CellValue::DateTime(dt) if dt.in_range() => Some(dt),
+            _ => None,
+        };
+        let text = match (&kind, date) {
+            (EditorKind::Checkbox, _) => return None,
+            (EditorKind::Date, Some(dt)) => {
+                let (year, month, day) = dt.ymd();
+                format!("{:04}-{:02}-{:02}", year, month, day)
+            }
+            (EditorKind::DateTime, Some(dt)) => {
+                let ((year, month, day), (h, m, s)) = (dt.ymd(), dt.hms());
+                format!(
+                    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                    year, month, day, h, m, s
+                )
+            }
+            (EditorKind::Date | EditorKind::DateTime, None) => String::new(),
+            _ => grid.edit_text(cell),
+        };
+        Some(Self::new(cell, &text).with_kind(kind))
+    }
+
+    fn with_kind(self, kind: EditorKind) -> Self {
+        Self { kind, ..self }
+    }
+
+    pub fn kind(&self) -> &EditorKind {
+        &self.kind
+    }
+
+    // The `type` of the input element the editor is shown in.
+    pub fn input_type(&self) -> &'static str {
+        match self.kind {
+            EditorKind::Date => "date",
+            EditorKind::DateTime => "datetime-local",
+            _ => "text",
+        }
+    }
+
+    // Values offered in a dropdown under the input.
+    pub fn options(&self) -> &[String] {
+        match &self.kind {
+            EditorKind::Dropdown(options) => options,
+            _ => &[],
+        }
+    }
+
+    pub fn cell(&self) -> CellRef {
+        self.cell
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    // Text typed by the user. Typing ends the current pointing.
+    pub fn set_text(&mut self, text: &str) {
+        if text != self.text {
+            self.text = text.to_string();
+            self.pointed = None;
+        }
+    }
+
+    // Unlike `Formula::is_formula`, a lone `=` counts: it is a formula
+    // still being typed.
+    pub fn is_formula(&self) -> bool {
+        self.text.starts_with('=')
+    }
+
+    // Whether clicking a cell with the caret at `caret` should insert a
+    // reference to it.
+    pub fn can_point(&self, caret: usize) -> bool {
+        if self.pointed.is_some_and(|(_, end)| end == caret) {
+            return true;
+        }
+        self.is_formula()
+            && self
+                .text
+                .chars()
+                .take(caret)
+                .filter(|c| !c.is_whitespace())
+                .last()
+                .is_some_and(|c| OPERAND_START.contains(&c))
+    }
+
+    // Inserts a reference to `area` at `caret`, or replaces the reference
+    // inserted by the previous click. Returns the caret after it.
+    pub fn point_at(&mut self, caret: usize, area: Area) -> usize {
+        let reference = if area.cell_count() == 1 {
+            area.start.to_string()
+        } else {
+            area.to_string()
+        };
+        let (start, end) = match self.pointed {
+            Some((start, end)) if end == caret => (start, end),
+            _ => (caret, caret),
+        };
+        let mut chars: Vec<char> = self.text.chars().collect();
+        let end = end.min(chars.len());
+        let start = start.min(end);
+        chars.splice(start..end, reference.chars());
+        self.text = chars.into_iter().collect();
+        let caret = start + reference.chars().count();
+        self.pointed = Some((start, caret));
+        caret
+    }
+}
@@ -0,0 +1,369 @@
+use crate::cell::CellValue;
+use crate::cell_ref::CellRef;
+use crate::datetime::{DateTime, MONTHS, WEEKDAYS};
+use crate::grid::Grid;
+use crate::range::Area;
+
+// Relative tolerance when deciding whether steps or ratios are equal.
+const EPSILON: f64 = 1e-9;
+
+// The area a fill handle dragged from `source` to `cell` covers: the
+// source stretched along whichever axis the pointer has moved further
+// past it.
+pub fn fill_target(source: &Area, cell: CellRef) -> Area {
+    let beyond = |pos: u32, first: u32, last: u32| {
+        if pos > last {
+            pos - last
+        } else {
+            first.saturating_sub(pos)
+        }
+    };
+    let rows = beyond(cell.row, source.top(), source.bottom());
+    let columns = beyond(cell.column, source.left(), source.right());
+    let (top, left, bottom, right) = if rows == 0 && columns == 0 {
+        return *source;
+    } else if rows >= columns {
+        (
+            source.top().min(cell.row),
+            source.left(),
+            source.bottom().max(cell.row),
+            source.right(),
+        )
+    } else {
+        (
+            source.top(),
+            source.left().min(cell.column),
+            source.bottom(),
+            source.right().max(cell.column),
+        )
+    };
+    Area::from_bounds(top, left, bottom, right)
+}
+
+// The last row a double-click on the fill handle fills down to: the end of
+// the data in the column beside `source`, on the left if there is any and
+// otherwise on the right.
+pub fn fill_down_extent(grid: &Grid, source: &Area) -> Option<u32> {
+    let block_end = |column: u32| {
+        let filled = |row: u32| !grid.get_value(CellRef::new(row, column)).is_empty();
+        let mut row = source.top();
+        if !filled(row) {
+            return None;
+        }
+        while row + 1 < grid.num_rows() && filled(row + 1) {
+            row += 1;
+        }
+        Some(row)
+    };
+    let left = source.left().checked_sub(1).and_then(block_end);
+    let right = (source.right() + 1 < grid.num_cols())
+        .then(|| block_end(source.right() + 1))
+        .flatten();
+    left.or(right).filter(|last| *last > source.bottom())
+}
+
+// Fills the part of `dest` outside `source` from `source`, as dragging the
+// fill handle does. Each row or column of the source continues as a series
+// where it forms one, and otherwise repeats, with formulas copied so their
+// relative references shift. Formats and styles are copied along.
+pub fn fill(grid: &mut Grid, source: &Area, dest: &Area) {
+    let down = dest.bottom() > source.bottom();
+    let up = dest.top() < source.top();
+    let right = dest.right() > source.right();
+    let left = dest.left() < source.left();
+    let vertical = up || down;
+    if !dest.contains_area(source) || !(vertical || left || right) {
+        return;
+    }
+    let target = if down {
+        Area::from_bounds(
+            source.bottom() + 1,
+            source.left(),
+            dest.bottom(),
+            source.right(),
+        )
+    } else if up {
+        Area::from_bounds(dest.top(), source.left(), source.top() - 1, source.right())
+    } else if right {
+        Area::from_bounds(
+            source.top(),
+            source.right() + 1,
+            source.bottom(),
+            dest.right(),
+        )
+    } else {
+        Area::from_bounds(
+            source.top(),
+            dest.left(),
+            source.bottom(),
+            source.left() - 1,
+        )
+    };
+    grid.clear_area(&target);
+
+    // Only lines with something stored need more than clearing.
+    let mut lines: Vec<u32> = grid
+        .cells()
+        .area(source)
+        .map(|c| if vertical { c.column_id } else { c.row_id })
+        .collect();
+    lines.sort_unstable();
+    lines.dedup();
+
+    // Positions along a line, walking away from the source.
+    let along: Vec<u32> = match (vertical, up || left) {
+        (true, false) => (source.top()..=source.bottom()).collect(),
+        (true, true) => (source.top()..=source.bottom()).rev().collect(),
+        (false, false) => (source.left()..=source.right()).collect(),
+        (false, true) => (source.left()..=source.right()).rev().collect(),
+    };
+    let beyond: Vec<u32> = match (vertical, up || left) {
+        (true, false) => (target.top()..=target.bottom()).collect(),
+        (true, true) => (target.top()..=target.bottom()).rev().collect(),
+        (false, false) => (target.left()..=target.right()).collect(),
+        (false, true) => (target.left()..=target.right()).rev().collect(),
+    };
+    let at = |line: u32, pos: u32| {
+        if vertical {
+            CellRef::new(pos, line)
+        } else {
+            CellRef::new(line, pos)
+        }
+    };
+
+    for line in lines {
+        let sources: Vec<CellRef> = along.iter().map(|pos| at(line, *pos)).collect();
+        let has_formula = sources.iter().any(|c| grid.get_formula(*c).is_some());
+        let values: Vec<CellValue> = sources.iter().map(|c| grid.get_value(*c)).collect();
+        let series = if has_formula {
+            None
+        } else {
+            extend_series(&values, beyond.len(), up || left)
+        };
+        for (k, pos) in beyond.iter().enumerate() {
+            let cell = at(line, *pos);
+            grid.copy_area(&Area::cell(sources[k % sources.len()]), cell);
+            if let Some(series) = &series {
+                // A value the column rejects keeps the copied one.
+                let _ = grid.set_value(cell, series[k].clone());
+            }
+        }
+    }
+}
+
+// The next `count` values after `values`, if they form a series: numbers
+// with a constant step or ratio (or else their linear trend), dates by
+// day, week or month, weekday and month names, and text ending in a
+// number. Returns `None` when the values should simply repeat.
+//
+// `values` are in the order the fill moves away from them. A lone date,
+// name or numbered text counts up, or down when `backwards`.
+pub fn extend_series(
+    values: &[CellValue],
+    count: usize,
+    backwards: bool,
+) -> Option<Vec<CellValue>> {
+    let n = values.len();
+    let unit = if backwards { -1 } else { 1 };
+    let next = |f: &dyn Fn(usize) -> Option<CellValue>| (n..n + count).map(f).collect();
+
+    if let Some(numbers) = all(values, |v| match v {
+        CellValue::Int(i) => Some(*i as f64),
+        CellValue::Float(f) => Some(*f),
+        _ => None,
+    }) {
+        if n < 2 {
+            return None;
+        }
+        let step = constant_step(&numbers);
+        let ratio = step.is_none().then(|| constant_ratio(&numbers)).flatten();
+        let (start, step) = match step {
+            Some(step) => (numbers[0], step),
+            None => linear_fit(&numbers),
+        };
+        return next(&|i| {
+            let number = match ratio {
+                Some(ratio) => numbers[0] * ratio.powi(i as i32),
+                None => start + step * i as f64,
+            };
+            Some(CellValue::from_number(number))
+        });
+    }
+
+    if let Some(dates) = all(values, |v| match v {
+        CellValue::DateTime(dt) => Some(*dt),
+        _ => None,
+    }) {
+        if let Some(months) = month_step(&dates) {
+            return next(&|i| {
+                let dt = dates[0].add_months(months.checked_mul(i as i32)?)?;
+                Some(CellValue::DateTime(dt))
+            });
+        }
+        let serials: Vec<f64> = dates.iter().map(DateTime::serial).collect();
+        let step = if n == 1 {
+            unit as f64
+        } else {
+            constant_step(&serials)?
+        };
+        return next(&|i| {
+            let serial = serials[0] + step * i as f64;
+            Some(CellValue::DateTime(DateTime::from_serial(serial)))
+        });
+    }
+
+    let texts = all(values, |v| match v {
+        CellValue::String(s) => Some(s.as_str()),
+        _ => None,
+    })?;
+    let last = texts[n - 1];
+    let lists = [
+        (&WEEKDAYS[..], false),
+        (&WEEKDAYS[..], true),
+        (&MONTHS[..], false),
+        (&MONTHS[..], true),
+    ];
+    for (names, short) in lists {
+        let Some(indices) = all(&texts, |t| name_index(names, short, t)) else {
+            continue;
+        };
+        let step = integer_step(&indices, unit)?;
+        let len = names.len() as i64;
+        return next(&|i| {
+            let index = (indices[0] + step * i as i64).rem_euclid(len) as usize;
+            let name = if short {
+                &names[index][..3]
+            } else {
+                names[index]
+            };
+            Some(CellValue::String(match_case(name, last)))
+        });
+    }
+
+    let parts = all(&texts, |t| split_trailing_number(t))?;
+    let prefix = parts[0].0;
+    if parts.iter().any(|(p, _, _)| *p != prefix) {
+        return None;
+    }
+    let numbers: Vec<i64> = parts.iter().map(|(_, n, _)| *n).collect();
+    let width = parts[n - 1].2;
+    let step = integer_step(&numbers, unit)?;
+    next(&|i| {
+        let number = step
+            .checked_mul(i as i64)
+            .and_then(|offset| numbers[0].checked_add(offset))?;
+        Some(CellValue::String(format!(
+            "{}{:0width$}",
+            prefix,
+            number,
+            width = width
+        )))
+    })
+}
+
+// `f` applied to every value, if it accepts them all.
+fn all<'a, T, U>(values: &'a [T], f: impl Fn(&'a T) -> Option<U>) -> Option<Vec<U>> {
+    values.iter().map(f).collect()
+}
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() <= EPSILON * a.abs().max(b.abs()).max(1.0)
+}
+
+fn constant_step(values: &[f64]) -> Option<f64> {
+    let step = values.get(1)? - values[0];
+    values
+        .windows(2)
+        .all(|w| close(w[1] - w[0], step))
+        .then_some(step)
+}
+
+fn constant_ratio(values: &[f64]) -> Option<f64> {
+    if values.len() < 3 || values.contains(&0.0) {
+        return None;
+    }
+    let ratio = values[1] / values[0];
+    values
+        .windows(2)
+        .all(|w| close(w[1] / w[0], ratio))
+        .then_some(ratio)
+}
+
+// Least-squares line through (i, values[i]), as (intercept, slope).
+fn linear_fit(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = values.iter().sum::<f64>() / n;
+    let (mut covariance, mut variance) = (0.0, 0.0);
+    for (i, y) in values.iter().enumerate() {
+        let dx = i as f64 - mean_x;
+        covariance += dx * (y - mean_y);
+        variance += dx * dx;
+    }
+    let slope = covariance / variance;
+    (mean_y - slope * mean_x, slope)
+}
+
+// A single value steps by `unit`. Steps too large for an i64 are no
+// series.
+fn integer_step(values: &[i64], unit: i64) -> Option<i64> {
+    let Some(second) = values.get(1) else {
+        return Some(unit);
+    };
+    let step = second.checked_sub(values[0])?;
+    values
+        .windows(2)
+        .all(|w| w[1].checked_sub(w[0]) == Some(step))
+        .then_some(step)
+}
+
+// Months between dates that all fall on the same day of the month and time
+// of day, if they are evenly spaced.
+fn month_step(dates: &[DateTime]) -> Option<i32> {
+    if dates.len() < 2 {
+        return None;
+    }
+    let (_, _, day) = dates[0].ymd();
+    let hms = dates[0].hms();
+    let months: Vec<i64> = dates
+        .iter()
+        .map(|dt| {
+            let (year, month, d) = dt.ymd();
+            (d == day && dt.hms() == hms).then_some(year as i64 * 12 + month as i64)
+        })
+        .collect::<Option<_>>()?;
+    let step = integer_step(&months, 1)?;
+    (step != 0).then_some(step as i32)
+}
+
+fn name_index(names: &[&str], short: bool, text: &str) -> Option<i64> {
+    names
+        .iter()
+        .position(|name| {
+            let name = if short { &name[..3] } else { name };
+            name.eq_ignore_ascii_case(text)
+        })
+        .map(|i| i as i64)
+}
+
+// `name` in the capitalisation of `like`: all upper, all lower or as is.
+fn match_case(name: &str, like: &str) -> String {
+    if like.chars().all(|c| !c.is_lowercase()) {
+        name.to_uppercase()
+    } else if like.chars().all(|c| !c.is_uppercase()) {
+        name.to_lowercase()
+    } else {
+        name.to_string()
+    }
+}
+
+// "Item 07" is ("Item ", 7, 2): the text before the trailing digits, their
+// value and how many there are.
+fn split_trailing_number(text: &str) -> Option<(&str, i64, usize)> {
+    let digits = text.chars().rev().take_while(char::is_ascii_digit).count();
+    if digits == 0 {
+        return None;
+    }
+    let (prefix, number) = text.split_at(text.len() - digits);
+    Some((prefix, number.parse().ok()?, digits))
+}
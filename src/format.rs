@@ -0,0 +1,710 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::cell::{format_number, CellValue};
+use crate::column::group_thousands;
+use crate::datetime::{DateTime, MONTHS, WEEKDAYS};
+
+const COLORS: [&str; 8] = [
+    "Black", "Blue", "Cyan", "Green", "Magenta", "Red", "White", "Yellow",
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FormatError(pub String);
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid format code: {}", self.0)
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+// The text to paint for a cell, and the colour a `[Red]`-style section asked
+// for, if any.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FormattedValue {
+    pub text: String,
+    pub color: Option<String>,
+}
+
+impl FormattedValue {
+    fn plain(text: String) -> Self {
+        Self { text, color: None }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Condition {
+    Lt(f64),
+    Le(f64),
+    Gt(f64),
+    Ge(f64),
+    Eq(f64),
+    Ne(f64),
+}
+
+impl Condition {
+    fn matches(self, n: f64) -> bool {
+        match self {
+            Condition::Lt(v) => n < v,
+            Condition::Le(v) => n <= v,
+            Condition::Gt(v) => n > v,
+            Condition::Ge(v) => n >= v,
+            Condition::Eq(v) => n == v,
+            Condition::Ne(v) => n != v,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DatePart {
+    Year(usize),
+    Month(usize),
+    Day(usize),
+    Hour(usize),
+    Minute(usize),
+    Second(usize),
+    AmPm,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    // `0`, `#` or `?`.
+    Digit(char),
+    Point,
+    Comma,
+    Percent,
+    // `E+` or `E-`; the flag is whether a `+` is shown for positive exponents.
+    Exponent(bool),
+    Literal(String),
+    Text,
+    Date(DatePart),
+    General,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Section {
+    tokens: Vec<Token>,
+    color: Option<String>,
+    condition: Option<Condition>,
+}
+
+impl Section {
+    fn is_date(&self) -> bool {
+        self.tokens.iter().any(|t| matches!(t, Token::Date(_)))
+    }
+
+    fn has_text(&self) -> bool {
+        self.tokens.contains(&Token::Text)
+    }
+}
+
+// An Excel-style number format such as `#,##0.00`, `0.0%`,
+// `$#,##0;[Red]($#,##0)`, `yyyy-mm-dd`, `@` or `0.00E+00`. Up to four
+// `;`-separated sections apply to positive numbers, negative numbers, zero
+// and text, in that order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NumberFormat {
+    code: String,
+    sections: Vec<Section>,
+}
+
+impl NumberFormat {
+    pub fn general() -> Self {
+        Self::parse("General").unwrap()
+    }
+
+    pub fn parse(code: &str) -> Result<Self, FormatError> {
+        let sections = split_sections(code)?
+            .iter()
+            .map(|section| parse_section(section))
+            .collect::<Result<Vec<_>, _>>()?;
+        if sections.len() > 4 {
+            return Err(FormatError("more than four sections".into()));
+        }
+        Ok(Self {
+            code: code.to_string(),
+            sections,
+        })
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn format(&self, value: &CellValue) -> FormattedValue {
+        match value {
+            CellValue::String(s) => self.format_text(s),
+            CellValue::Int(_) | CellValue::Float(_) | CellValue::DateTime(_) => {
+                self.format_number(value.as_number().unwrap_or_default())
+            }
+            value => FormattedValue::plain(value.to_string()),
+        }
+    }
+
+    fn format_text(&self, text: &str) -> FormattedValue {
+        let section = match self.sections.len() {
+            4 => Some(&self.sections[3]),
+            _ => self.sections.iter().find(|s| s.has_text()),
+        };
+        match section {
+            Some(section) => FormattedValue {
+                text: render_literal(section, text),
+                color: section.color.clone(),
+            },
+            None => FormattedValue::plain(text.to_string()),
+        }
+    }
+
+    fn format_number(&self, n: f64) -> FormattedValue {
+        let numeric: Vec<&Section> = self.sections.iter().take(3).collect();
+        let has_conditions = numeric.iter().any(|s| s.condition.is_some());
+
+        // Which section applies, and whether it prints its own sign.
+        let (section, signed) = if has_conditions {
+            let chosen = numeric
+                .iter()
+                .find(|s| s.condition.is_some_and(|c| c.matches(n)))
+                .or_else(|| numeric.iter().find(|s| s.condition.is_none()))
+                .copied()
+                .unwrap_or(numeric[0]);
+            (chosen, n < 0.0 && chosen.condition.is_none())
+        } else {
+            match numeric.len() {
+                0 => return FormattedValue::plain(format_number(n)),
+                1 => (numeric[0], true),
+                2 if n < 0.0 => (numeric[1], false),
+                2 => (numeric[0], false),
+                _ if n < 0.0 => (numeric[1], false),
+                _ if n == 0.0 => (numeric[2], false),
+                _ => (numeric[0], false),
+            }
+        };
+
+        let text = if section.tokens.iter().all(|t| *t == Token::General) {
+            format_number(if signed { n } else { n.abs() })
+        } else if section.is_date() {
+            if DateTime::from_serial(n).in_range() {
+                render_date(section, n)
+            } else {
+                "#####".to_string()
+            }
+        } else {
+            let text = render_number(section, n.abs());
+            // A negative number that rounds to zero loses its sign.
+            let is_zero = !text.bytes().any(|b| (b'1'..=b'9').contains(&b));
+            if signed && n < 0.0 && !is_zero {
+                format!("-{}", text)
+            } else {
+                text
+            }
+        };
+        FormattedValue {
+            text,
+            color: section.color.clone(),
+        }
+    }
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self::general()
+    }
+}
+
+impl fmt::Display for NumberFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code)
+    }
+}
+
+impl FromStr for NumberFormat {
+    type Err = FormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+// Splits on `;` outside quotes and brackets.
+fn split_sections(code: &str) -> Result<Vec<String>, FormatError> {
+    let mut sections = vec![String::new()];
+    let mut chars = code.chars();
+    while let Some(c) = chars.next() {
+        let current = sections.last_mut().unwrap();
+        match c {
+            ';' => sections.push(String::new()),
+            '"' => {
+                current.push(c);
+                loop {
+                    match chars.next() {
+                        Some('"') => break current.push('"'),
+                        Some(c) => current.push(c),
+                        None => return Err(FormatError("unterminated quote".into())),
+                    }
+                }
+            }
+            '[' => {
+                current.push(c);
+                loop {
+                    match chars.next() {
+                        Some(']') => break current.push(']'),
+                        Some(c) => current.push(c),
+                        None => return Err(FormatError("unterminated `[`".into())),
+                    }
+                }
+            }
+            '\\' => {
+                current.push(c);
+                current.extend(chars.next());
+            }
+            c => current.push(c),
+        }
+    }
+    Ok(sections)
+}
+
+fn parse_section(code: &str) -> Result<Section, FormatError> {
+    let chars: Vec<char> = code.chars().collect();
+    let mut section = Section::default();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    let run = |i: usize, c: char| {
+        chars[i..]
+            .iter()
+            .take_while(|x| x.eq_ignore_ascii_case(&c))
+            .count()
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '"' => {
+                let end = (i + 1..chars.len())
+                    .find(|&j| chars[j] == '"')
+                    .unwrap_or(chars.len());
+                tokens.push(Token::Literal(chars[i + 1..end].iter().collect()));
+                i = end + 1;
+            }
+            '\\' => {
+                if let Some(next) = chars.get(i + 1) {
+                    tokens.push(Token::Literal(next.to_string()));
+                }
+                i += 2;
+            }
+            '[' => {
+                let end = (i..chars.len())
+                    .find(|&j| chars[j] == ']')
+                    .unwrap_or(chars.len());
+                let inner: String = chars[i + 1..end].iter().collect();
+                parse_bracket(&inner, &mut section, &mut tokens)?;
+                i = end + 1;
+            }
+            // `_x` leaves room for x; `*x` repeats x to fill the cell. Both
+            // become a single space or nothing here.
+            '_' => {
+                tokens.push(Token::Literal(" ".into()));
+                i += 2;
+            }
+            '*' => i += 2,
+            '0' | '#' | '?' => {
+                tokens.push(Token::Digit(c));
+                i += 1;
+            }
+            '.' => {
+                tokens.push(Token::Point);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            '%' => {
+                tokens.push(Token::Percent);
+                i += 1;
+            }
+            '@' => {
+                tokens.push(Token::Text);
+                i += 1;
+            }
+            'E' | 'e' if matches!(chars.get(i + 1), Some('+') | Some('-')) => {
+                tokens.push(Token::Exponent(chars[i + 1] == '+'));
+                i += 2;
+            }
+            'G' | 'g'
+                if code[char_offset(code, i)..]
+                    .to_ascii_lowercase()
+                    .starts_with("general") =>
+            {
+                tokens.push(Token::General);
+                i += 7;
+            }
+            'A' | 'a'
+                if code[char_offset(code, i)..]
+                    .to_ascii_uppercase()
+                    .starts_with("AM/PM") =>
+            {
+                tokens.push(Token::Date(DatePart::AmPm));
+                i += 5;
+            }
+            'y' | 'Y' | 'm' | 'M' | 'd' | 'D' | 'h' | 'H' | 's' | 'S' => {
+                let n = run(i, c);
+                let part = match c.to_ascii_lowercase() {
+                    'y' => DatePart::Year(n),
+                    'm' => DatePart::Month(n),
+                    'd' => DatePart::Day(n),
+                    'h' => DatePart::Hour(n),
+                    _ => DatePart::Second(n),
+                };
+                tokens.push(Token::Date(part));
+                i += n;
+            }
+            c => {
+                tokens.push(Token::Literal(c.to_string()));
+                i += 1;
+            }
+        }
+    }
+
+    resolve_minutes(&mut tokens);
+    section.tokens = tokens;
+    Ok(section)
+}
+
+fn char_offset(s: &str, chars: usize) -> usize {
+    s.char_indices()
+        .nth(chars)
+        .map(|(i, _)| i)
+        .unwrap_or(s.len())
+}
+
+fn parse_bracket(
+    inner: &str,
+    section: &mut Section,
+    tokens: &mut Vec<Token>,
+) -> Result<(), FormatError> {
+    if let Some(color) = COLORS.iter().find(|c| c.eq_ignore_ascii_case(inner)) {
+        section.color = Some(color.to_string());
+    } else if let Some(currency) = inner.strip_prefix('$') {
+        // `[$€-407]`: a currency symbol with a locale id.
+        let symbol = currency.split('-').next().unwrap_or("");
+        tokens.push(Token::Literal(symbol.to_string()));
+    } else if let Some(condition) = parse_condition(inner) {
+        section.condition = Some(condition);
+    } else if ["h", "hh", "m", "mm", "s", "ss"].contains(&inner.to_ascii_lowercase().as_str()) {
+        // Elapsed time; treated as the plain clock part.
+        let n = inner.len();
+        tokens.push(Token::Date(
+            match inner.to_ascii_lowercase().as_bytes()[0] {
+                b'h' => DatePart::Hour(n),
+                b'm' => DatePart::Minute(n),
+                _ => DatePart::Second(n),
+            },
+        ));
+    } else {
+        return Err(FormatError(format!("unknown `[{}]`", inner)));
+    }
+    Ok(())
+}
+
+fn parse_condition(inner: &str) -> Option<Condition> {
+    let (build, rest): (fn(f64) -> Condition, &str) = if let Some(rest) = inner.strip_prefix("<=") {
+        (Condition::Le, rest)
+    } else if let Some(rest) = inner.strip_prefix(">=") {
+        (Condition::Ge, rest)
+    } else if let Some(rest) = inner.strip_prefix("<>") {
+        (Condition::Ne, rest)
+    } else if let Some(rest) = inner.strip_prefix('<') {
+        (Condition::Lt, rest)
+    } else if let Some(rest) = inner.strip_prefix('>') {
+        (Condition::Gt, rest)
+    } else {
+        (Condition::Eq, inner.strip_prefix('=')?)
+    };
+    rest.trim().parse().ok().map(build)
+}
+
+// `m` means minutes right after an hour or right before a second, and
+// months everywhere else.
+fn resolve_minutes(tokens: &mut [Token]) {
+    let parts: Vec<usize> = (0..tokens.len())
+        .filter(|&i| matches!(tokens[i], Token::Date(_)))
+        .collect();
+    for (k, &i) in parts.iter().enumerate() {
+        let Token::Date(DatePart::Month(n)) = tokens[i] else {
+            continue;
+        };
+        if n > 2 {
+            continue;
+        }
+        let after_hour = k > 0 && matches!(tokens[parts[k - 1]], Token::Date(DatePart::Hour(_)));
+        let before_second = parts
+            .get(k + 1)
+            .is_some_and(|&j| matches!(tokens[j], Token::Date(DatePart::Second(_))));
+        if after_hour || before_second {
+            tokens[i] = Token::Date(DatePart::Minute(n));
+        }
+    }
+}
+
+// Renders a text section, substituting `text` for `@`.
+fn render_literal(section: &Section, text: &str) -> String {
+    section
+        .tokens
+        .iter()
+        .map(|token| match token {
+            Token::Text => text.to_string(),
+            Token::Literal(s) => s.clone(),
+            _ => String::new(),
+        })
+        .collect()
+}
+
+fn render_date(section: &Section, serial: f64) -> String {
+    let dt = DateTime::from_serial(serial);
+    let (year, month, day) = dt.ymd();
+    let (hour, minute, second) = dt.hms();
+    let twelve_hour = section.tokens.contains(&Token::Date(DatePart::AmPm));
+
+    section
+        .tokens
+        .iter()
+        .map(|token| match token {
+            Token::Date(part) => match *part {
+                DatePart::Year(n) if n <= 2 => format!("{:02}", year.rem_euclid(100)),
+                DatePart::Year(_) => format!("{:04}", year),
+                DatePart::Month(1) => month.to_string(),
+                DatePart::Month(2) => format!("{:02}", month),
+                DatePart::Month(3) => MONTHS[month as usize - 1][..3].to_string(),
+                DatePart::Month(4) => MONTHS[month as usize - 1].to_string(),
+                DatePart::Month(_) => MONTHS[month as usize - 1][..1].to_string(),
+                DatePart::Day(1) => day.to_string(),
+                DatePart::Day(2) => format!("{:02}", day),
+                DatePart::Day(3) => WEEKDAYS[dt.weekday() as usize][..3].to_string(),
+                DatePart::Day(_) => WEEKDAYS[dt.weekday() as usize].to_string(),
+                DatePart::Hour(n) => {
+                    let hour = if twelve_hour {
+                        (hour + 11) % 12 + 1
+                    } else {
+                        hour
+                    };
+                    pad(hour, n)
+                }
+                DatePart::Minute(n) => pad(minute, n),
+                DatePart::Second(n) => pad(second, n),
+                DatePart::AmPm => if hour < 12 { "AM" } else { "PM" }.to_string(),
+            },
+            Token::Literal(s) => s.clone(),
+            Token::Point => ".".to_string(),
+            Token::Comma => ",".to_string(),
+            _ => String::new(),
+        })
+        .collect()
+}
+
+fn pad(value: u32, width: usize) -> String {
+    if width >= 2 {
+        format!("{:02}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+// Renders the absolute value `n` through a numeric section.
+fn render_number(section: &Section, mut n: f64) -> String {
+    let tokens = &section.tokens;
+    let exponent_at = tokens.iter().position(|t| matches!(t, Token::Exponent(_)));
+    let mantissa_end = exponent_at.unwrap_or(tokens.len());
+    let point_at = tokens[..mantissa_end]
+        .iter()
+        .position(|t| *t == Token::Point);
+    let integer_end = point_at.unwrap_or(mantissa_end);
+
+    let digit_positions = |range: std::ops::Range<usize>| -> Vec<usize> {
+        range
+            .filter(|&i| matches!(tokens[i], Token::Digit(_)))
+            .collect()
+    };
+    let integer_digits = digit_positions(0..integer_end);
+    let fraction_digits = point_at.map_or(Vec::new(), |p| digit_positions(p + 1..mantissa_end));
+
+    // Commas between integer placeholders group thousands; commas right
+    // after the last placeholder scale by 1000 each.
+    let last_integer_digit = integer_digits.last().copied();
+    let mut grouping = false;
+    let mut scale = 0;
+    for (i, token) in tokens[..integer_end].iter().enumerate() {
+        if *token != Token::Comma {
+            continue;
+        }
+        let trailing = tokens[i..integer_end]
+            .iter()
+            .all(|t| !matches!(t, Token::Digit(_)));
+        if trailing && last_integer_digit.is_some_and(|last| last < i) {
+            scale += 1;
+        } else if integer_digits.first().is_some_and(|first| *first < i) {
+            grouping = true;
+        }
+    }
+
+    let percents = tokens.iter().filter(|t| **t == Token::Percent).count();
+    n *= 100f64.powi(percents as i32);
+    n /= 1000f64.powi(scale);
+
+    let mut exponent = 0;
+    if exponent_at.is_some() && n != 0.0 {
+        let step = integer_digits.len().max(1) as i32;
+        exponent = n.log10().floor() as i32;
+        // `##0.0E+0` style formats keep the exponent a multiple of the
+        // integer width (engineering notation).
+        if step > 1 {
+            exponent = exponent.div_euclid(step) * step;
+        }
+        n /= 10f64.powi(exponent);
+    }
+
+    let rounded = format!("{:.*}", fraction_digits.len(), n);
+    let integer_len = rounded.split('.').next().unwrap_or("").len();
+    if exponent_at.is_some() && integer_len > integer_digits.len().max(1) {
+        // Rounding carried into a new digit, e.g. 9.99 -> 10.0.
+        exponent += 1;
+        let shifted = format!("{:.*}", fraction_digits.len(), n / 10.0);
+        return render_number_with(section, &shifted, exponent, grouping, &integer_digits);
+    }
+    render_number_with(section, &rounded, exponent, grouping, &integer_digits)
+}
+
+// Lays the digits of `rounded` (no sign, integer part may be empty) out
+// over the section's placeholders and literals.
+fn render_number_with(
+    section: &Section,
+    rounded: &str,
+    exponent: i32,
+    grouping: bool,
+    integer_digits: &[usize],
+) -> String {
+    let tokens = &section.tokens;
+    let (integer, fraction) = rounded.split_once('.').unwrap_or((rounded, ""));
+    let integer = if integer == "0" { "" } else { integer };
+
+    // Integer digits: placeholders take digits from the right; the leftmost
+    // placeholder takes whatever is left over. Missing digits are padded
+    // according to the placeholder: `0` shows 0, `?` a space, `#` nothing.
+    let mut integer_out: Vec<String> = vec![String::new(); integer_digits.len()];
+    let mut remaining: Vec<char> = integer.chars().collect();
+    for (slot, &at) in integer_digits.iter().enumerate().rev() {
+        let Token::Digit(kind) = tokens[at] else {
+            continue;
+        };
+        integer_out[slot] = match remaining.pop() {
+            Some(d) => d.to_string(),
+            None => match kind {
+                '0' => "0".to_string(),
+                '?' => " ".to_string(),
+                _ => String::new(),
+            },
+        };
+        if slot == 0 {
+            let rest: String = remaining.drain(..).collect();
+            integer_out[0] = rest + &integer_out[0];
+        }
+    }
+    if grouping {
+        let joined: String = integer_out.concat();
+        let digits: String = joined.trim_start().to_string();
+        let padding = joined.len() - digits.len();
+        integer_out = vec![String::new(); integer_digits.len()];
+        if let Some(first) = integer_out.first_mut() {
+            *first = " ".repeat(padding) + &group_thousands(&digits);
+        }
+    }
+
+    // Fraction digits: `#` drops trailing zeros, `?` turns them into spaces.
+    let mut fraction_out: Vec<String> = Vec::new();
+    let fraction_chars: Vec<char> = fraction.chars().collect();
+    let fraction_kinds: Vec<char> = tokens
+        .iter()
+        .skip_while(|t| **t != Token::Point)
+        .take_while(|t| !matches!(t, Token::Exponent(_)))
+        .filter_map(|t| match t {
+            Token::Digit(kind) => Some(*kind),
+            _ => None,
+        })
+        .collect();
+    let mut trailing = true;
+    for (i, kind) in fraction_kinds.iter().enumerate().rev() {
+        let digit = fraction_chars.get(i).copied().unwrap_or('0');
+        let shown = if trailing && digit == '0' && *kind != '0' {
+            if *kind == '?' {
+                " ".to_string()
+            } else {
+                String::new()
+            }
+        } else {
+            trailing = false;
+            digit.to_string()
+        };
+        fraction_out.push(shown);
+    }
+    fraction_out.reverse();
+
+    let mut out = String::new();
+    let mut integer_slot = 0;
+    let mut fraction_slot = 0;
+    let mut in_fraction = false;
+    let mut in_exponent = false;
+    let mut exponent_slots: Vec<usize> = Vec::new();
+
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Digit(_) if in_exponent => {
+                exponent_slots.push(out.len());
+            }
+            Token::Digit(_) if in_fraction => {
+                out.push_str(fraction_out.get(fraction_slot).map_or("", |s| s.as_str()));
+                fraction_slot += 1;
+            }
+            Token::Digit(_) => {
+                if integer_digits.contains(&i) {
+                    out.push_str(&integer_out[integer_slot]);
+                    integer_slot += 1;
+                }
+            }
+            Token::Point if !in_exponent => {
+                // With no integer placeholders the digits still show, as
+                // `.00` renders 12.5 as `12.50`.
+                if integer_digits.is_empty() && !in_fraction {
+                    if grouping {
+                        out.push_str(&group_thousands(integer));
+                    } else {
+                        out.push_str(integer);
+                    }
+                }
+                in_fraction = true;
+                out.push('.');
+            }
+            Token::Exponent(plus) => {
+                in_fraction = false;
+                in_exponent = true;
+                out.push('E');
+                if exponent < 0 {
+                    out.push('-');
+                } else if *plus {
+                    out.push('+');
+                }
+            }
+            Token::Comma => {}
+            Token::Percent => out.push('%'),
+            Token::Literal(s) => out.push_str(s),
+            Token::Text | Token::Date(_) | Token::General | Token::Point => {}
+        }
+    }
+
+    if let Some(&at) = exponent_slots.first() {
+        let digits = format!("{:0width$}", exponent.abs(), width = exponent_slots.len());
+        out.insert_str(at, &digits);
+    }
+    out
+}
@@ -0,0 +1,206 @@
+use std::fmt;
+
+use crate::cell::ErrorKind;
+use crate::cell_ref::CellRef;
+use crate::range::Area;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnaryOp {
+    Plus,
+    Minus,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Concat,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+impl BinaryOp {
+    // Binding strength, following Excel: comparisons bind loosest, then `&`,
+    // `+ -`, `* /` and finally `^`.
+    pub fn precedence(self) -> u8 {
+        match self {
+            BinaryOp::Eq
+            | BinaryOp::Ne
+            | BinaryOp::Lt
+            | BinaryOp::Le
+            | BinaryOp::Gt
+            | BinaryOp::Ge => 1,
+            BinaryOp::Concat => 2,
+            BinaryOp::Add | BinaryOp::Sub => 3,
+            BinaryOp::Mul | BinaryOp::Div => 4,
+            BinaryOp::Pow => 5,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinaryOp::Eq => "=",
+            BinaryOp::Ne => "<>",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+            BinaryOp::Concat => "&",
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Pow => "^",
+        }
+    }
+}
+
+const PERCENT_PRECEDENCE: u8 = 6;
+const UNARY_PRECEDENCE: u8 = 7;
+const ATOM_PRECEDENCE: u8 = 8;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    Error(ErrorKind),
+    Ref(CellRef),
+    Range(Area),
+    // An identifier that is neither a function call nor a reference.
+    Name(String),
+    // Parentheses written by the user, kept so formulas print back as typed.
+    Group(Box<Expr>),
+    Unary(UnaryOp, Box<Expr>),
+    Percent(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+impl Expr {
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Binary(op, _, _) => op.precedence(),
+            Expr::Percent(_) => PERCENT_PRECEDENCE,
+            Expr::Unary(_, _) => UNARY_PRECEDENCE,
+            _ => ATOM_PRECEDENCE,
+        }
+    }
+
+    // Calls `f` on this node and every node below it.
+    pub fn visit<'a>(&'a self, f: &mut impl FnMut(&'a Expr)) {
+        f(self);
+        match self {
+            Expr::Group(inner) | Expr::Unary(_, inner) | Expr::Percent(inner) => inner.visit(f),
+            Expr::Binary(_, lhs, rhs) => {
+                lhs.visit(f);
+                rhs.visit(f);
+            }
+            Expr::Call(_, args) => args.iter().for_each(|arg| arg.visit(f)),
+            _ => {}
+        }
+    }
+
+    // Every cell and range this expression reads, as areas.
+    pub fn references(&self) -> Vec<Area> {
+        let mut areas = Vec::new();
+        self.visit(&mut |expr| match expr {
+            Expr::Ref(cell) => areas.push(Area::cell(*cell)),
+            Expr::Range(area) => areas.push(*area),
+            _ => {}
+        });
+        areas
+    }
+
+    // Rebuilds the expression with every reference passed through `f`.
+    // References that `f` rejects become `#REF!`.
+    pub fn map_references(&self, f: &mut impl FnMut(Area) -> Option<Area>) -> Expr {
+        let mut map = |expr: &Expr| Box::new(expr.map_references(f));
+        match self {
+            Expr::Ref(cell) => match f(Area::cell(*cell)) {
+                Some(area) => Expr::Ref(area.start),
+                None => Expr::Error(ErrorKind::Ref),
+            },
+            Expr::Range(area) => f(*area).map_or(Expr::Error(ErrorKind::Ref), Expr::Range),
+            Expr::Group(inner) => Expr::Group(map(inner)),
+            Expr::Unary(op, inner) => Expr::Unary(*op, map(inner)),
+            Expr::Percent(inner) => Expr::Percent(map(inner)),
+            Expr::Binary(op, lhs, rhs) => Expr::Binary(*op, map(lhs), map(rhs)),
+            Expr::Call(name, args) => Expr::Call(
+                name.clone(),
+                args.iter().map(|arg| arg.map_references(f)).collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+}
+
+struct Operand<'a>(&'a Expr, u8);
+
+impl fmt::Display for Operand<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.precedence() < self.1 {
+            write!(f, "({})", self.0)
+        } else {
+            write!(f, "{}", self.0)
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Number(n) => write!(f, "{}", n),
+            Expr::Text(s) => write!(f, "\"{}\"", s.replace('"', "\"\"")),
+            Expr::Bool(b) => write!(f, "{}", if *b { "TRUE" } else { "FALSE" }),
+            Expr::Error(kind) => write!(f, "{}", kind),
+            Expr::Ref(cell) => write!(f, "{}", cell),
+            Expr::Range(area) => {
+                if area.start == area.end {
+                    write!(f, "{}:{}", area.start, area.end)
+                } else {
+                    write!(f, "{}", area)
+                }
+            }
+            Expr::Name(name) => write!(f, "{}", name),
+            Expr::Group(inner) => write!(f, "({})", inner),
+            Expr::Unary(op, inner) => {
+                let sign = match op {
+                    UnaryOp::Plus => "+",
+                    UnaryOp::Minus => "-",
+                };
+                write!(f, "{}{}", sign, Operand(inner, UNARY_PRECEDENCE))
+            }
+            Expr::Percent(inner) => write!(f, "{}%", Operand(inner, PERCENT_PRECEDENCE)),
+            Expr::Binary(op, lhs, rhs) => {
+                // Binary operators are left-associative, so the right operand
+                // needs parentheses at equal precedence.
+                let p = op.precedence();
+                write!(
+                    f,
+                    "{}{}{}",
+                    Operand(lhs, p),
+                    op.symbol(),
+                    Operand(rhs, p + 1)
+                )
+            }
+            Expr::Call(name, args) => {
+                write!(f, "{}(", name)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
@@ -0,0 +1,470 @@
+use std::cmp::Ordering;
+
+use super::ast::{BinaryOp, Expr, UnaryOp};
+use crate::cell::{format_number, CellValue, ErrorKind};
+use crate::cell_ref::CellRef;
+use crate::column::parse_number;
+use crate::range::Area;
+
+// Read access to the cells a formula refers to.
+pub trait EvalContext {
+    fn value(&self, cell: CellRef) -> CellValue;
+
+    // The non-empty values inside `area`, in row-major order.
+    fn values(&self, area: &Area) -> Vec<CellValue>;
+}
+
+pub fn evaluate(expr: &Expr, ctx: &dyn EvalContext) -> CellValue {
+    into_cell_value(Evaluator { ctx }.scalar(expr))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    Empty,
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    Error(ErrorKind),
+}
+
+// A function argument. References are kept apart from literal scalars
+// because aggregate functions treat them differently: `SUM("1")` is 1, but
+// a referenced cell holding the text "1" is skipped.
+enum Arg {
+    Scalar(Value),
+    Cells(Vec<Value>),
+}
+
+type Result<T> = std::result::Result<T, ErrorKind>;
+
+fn from_cell_value(value: CellValue) -> Value {
+    match value {
+        CellValue::Empty => Value::Empty,
+        CellValue::Bool(b) => Value::Bool(b),
+        CellValue::String(s) => Value::Text(s),
+        CellValue::Error(kind) => Value::Error(kind),
+        number => Value::Number(number.as_number().unwrap_or_default()),
+    }
+}
+
+fn to_cell_value(value: &Value) -> CellValue {
+    match value {
+        Value::Empty => CellValue::Empty,
+        Value::Number(n) => CellValue::from_number(*n),
+        Value::Text(s) => CellValue::String(s.clone()),
+        Value::Bool(b) => CellValue::Bool(*b),
+        Value::Error(kind) => CellValue::Error(*kind),
+    }
+}
+
+// A formula that evaluates to a blank shows 0, as in Excel.
+fn into_cell_value(value: Value) -> CellValue {
+    match value {
+        Value::Empty => CellValue::Int(0),
+        value => to_cell_value(&value),
+    }
+}
+
+fn bool_text(b: bool) -> &'static str {
+    if b {
+        "TRUE"
+    } else {
+        "FALSE"
+    }
+}
+
+fn to_number(value: &Value) -> Result<f64> {
+    match value {
+        Value::Empty => Ok(0.0),
+        Value::Number(n) => Ok(*n),
+        Value::Bool(b) => Ok(if *b { 1.0 } else { 0.0 }),
+        Value::Text(s) => parse_number(s).ok_or(ErrorKind::Value),
+        Value::Error(kind) => Err(*kind),
+    }
+}
+
+fn to_text(value: &Value) -> Result<String> {
+    match value {
+        Value::Empty => Ok(String::new()),
+        Value::Number(n) => Ok(format_number(*n)),
+        Value::Bool(b) => Ok(bool_text(*b).to_string()),
+        Value::Text(s) => Ok(s.clone()),
+        Value::Error(kind) => Err(*kind),
+    }
+}
+
+fn to_bool(value: &Value) -> Result<bool> {
+    match value {
+        Value::Empty => Ok(false),
+        Value::Number(n) => Ok(*n != 0.0),
+        Value::Bool(b) => Ok(*b),
+        Value::Text(s) if s.eq_ignore_ascii_case("TRUE") => Ok(true),
+        Value::Text(s) if s.eq_ignore_ascii_case("FALSE") => Ok(false),
+        Value::Text(_) => Err(ErrorKind::Value),
+        Value::Error(kind) => Err(*kind),
+    }
+}
+
+// Orders two operands of a comparison. A blank behaves like 0, "" or FALSE
+// depending on what it is compared with; everything else follows
+// `CellValue::compare`.
+fn compare(lhs: &Value, rhs: &Value) -> Ordering {
+    fn blank_like(other: &Value) -> Value {
+        match other {
+            Value::Text(_) => Value::Text(String::new()),
+            Value::Bool(_) => Value::Bool(false),
+            _ => Value::Number(0.0),
+        }
+    }
+
+    let lhs = if *lhs == Value::Empty {
+        blank_like(rhs)
+    } else {
+        lhs.clone()
+    };
+    let rhs = if *rhs == Value::Empty {
+        blank_like(&lhs)
+    } else {
+        rhs.clone()
+    };
+    to_cell_value(&lhs).compare(&to_cell_value(&rhs))
+}
+
+fn number_result(n: f64) -> Value {
+    if n.is_finite() {
+        Value::Number(n)
+    } else {
+        Value::Error(ErrorKind::Num)
+    }
+}
+
+struct Evaluator<'a> {
+    ctx: &'a dyn EvalContext,
+}
+
+impl Evaluator<'_> {
+    fn scalar(&self, expr: &Expr) -> Value {
+        match expr {
+            Expr::Number(n) => Value::Number(*n),
+            Expr::Text(s) => Value::Text(s.clone()),
+            Expr::Bool(b) => Value::Bool(*b),
+            Expr::Error(kind) => Value::Error(*kind),
+            Expr::Ref(cell) => from_cell_value(self.ctx.value(*cell)),
+            // No implicit intersection: a range where one value is expected
+            // is an error.
+            Expr::Range(_) => Value::Error(ErrorKind::Value),
+            Expr::Name(_) => Value::Error(ErrorKind::Name),
+            Expr::Group(inner) => self.scalar(inner),
+            Expr::Unary(op, inner) => match to_number(&self.scalar(inner)) {
+                Ok(n) if *op == UnaryOp::Minus => Value::Number(-n),
+                Ok(n) => Value::Number(n),
+                Err(kind) => Value::Error(kind),
+            },
+            Expr::Percent(inner) => match to_number(&self.scalar(inner)) {
+                Ok(n) => Value::Number(n / 100.0),
+                Err(kind) => Value::Error(kind),
+            },
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (self.scalar(lhs), self.scalar(rhs));
+                binary(*op, &lhs, &rhs).unwrap_or_else(Value::Error)
+            }
+            Expr::Call(name, args) => self.call(name, args).unwrap_or_else(Value::Error),
+        }
+    }
+
+    fn arg(&self, expr: &Expr) -> Arg {
+        match expr {
+            Expr::Ref(cell) => Arg::Cells(vec![from_cell_value(self.ctx.value(*cell))]),
+            Expr::Range(area) => Arg::Cells(
+                self.ctx
+                    .values(area)
+                    .into_iter()
+                    .map(from_cell_value)
+                    .collect(),
+            ),
+            Expr::Group(inner) => self.arg(inner),
+            _ => Arg::Scalar(self.scalar(expr)),
+        }
+    }
+
+    // Collects the numbers an aggregate function works on. Literal
+    // arguments are coerced; referenced text and booleans are skipped.
+    fn numbers(&self, args: &[Expr]) -> Result<Vec<f64>> {
+        let mut numbers = Vec::new();
+        for arg in args {
+            match self.arg(arg) {
+                Arg::Scalar(value) => numbers.push(to_number(&value)?),
+                Arg::Cells(values) => {
+                    for value in values {
+                        match value {
+                            Value::Number(n) => numbers.push(n),
+                            Value::Error(kind) => return Err(kind),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
+        Ok(numbers)
+    }
+
+    fn values(&self, args: &[Expr]) -> Vec<Value> {
+        args.iter()
+            .flat_map(|arg| match self.arg(arg) {
+                Arg::Scalar(value) => vec![value],
+                Arg::Cells(values) => values,
+            })
+            .collect()
+    }
+
+    fn number_arg(&self, args: &[Expr], index: usize) -> Result<f64> {
+        to_number(&self.scalar(&args[index]))
+    }
+
+    fn text_arg(&self, args: &[Expr], index: usize) -> Result<String> {
+        to_text(&self.scalar(&args[index]))
+    }
+
+    fn call(&self, name: &str, args: &[Expr]) -> Result<Value> {
+        let arity = |min: usize, max: usize| {
+            if (min..=max).contains(&args.len()) {
+                Ok(())
+            } else {
+                Err(ErrorKind::Value)
+            }
+        };
+
+        match name {
+            "SUM" => Ok(number_result(self.numbers(args)?.iter().sum())),
+            "PRODUCT" => Ok(number_result(self.numbers(args)?.iter().product())),
+            "AVERAGE" => {
+                let numbers = self.numbers(args)?;
+                if numbers.is_empty() {
+                    return Err(ErrorKind::Div0);
+                }
+                Ok(Value::Number(
+                    numbers.iter().sum::<f64>() / numbers.len() as f64,
+                ))
+            }
+            "MIN" => Ok(Value::Number(
+                self.numbers(args)?
+                    .into_iter()
+                    .reduce(f64::min)
+                    .unwrap_or(0.0),
+            )),
+            "MAX" => Ok(Value::Number(
+                self.numbers(args)?
+                    .into_iter()
+                    .reduce(f64::max)
+                    .unwrap_or(0.0),
+            )),
+            "COUNT" => {
+                let count = args
+                    .iter()
+                    .map(|arg| match self.arg(arg) {
+                        Arg::Scalar(value) => to_number(&value).is_ok() as usize,
+                        Arg::Cells(values) => values
+                            .iter()
+                            .filter(|v| matches!(v, Value::Number(_)))
+                            .count(),
+                    })
+                    .sum::<usize>();
+                Ok(Value::Number(count as f64))
+            }
+            "COUNTA" => {
+                let count = self
+                    .values(args)
+                    .iter()
+                    .filter(|v| **v != Value::Empty)
+                    .count();
+                Ok(Value::Number(count as f64))
+            }
+            "IF" => {
+                arity(2, 3)?;
+                if to_bool(&self.scalar(&args[0]))? {
+                    Ok(self.scalar(&args[1]))
+                } else if let Some(otherwise) = args.get(2) {
+                    Ok(self.scalar(otherwise))
+                } else {
+                    Ok(Value::Bool(false))
+                }
+            }
+            "IFERROR" => {
+                arity(2, 2)?;
+                match self.scalar(&args[0]) {
+                    Value::Error(_) => Ok(self.scalar(&args[1])),
+                    value => Ok(value),
+                }
+            }
+            "ISERROR" | "ISNA" | "ISBLANK" | "ISNUMBER" | "ISTEXT" => {
+                arity(1, 1)?;
+                let value = self.scalar(&args[0]);
+                Ok(Value::Bool(match name {
+                    "ISERROR" => matches!(value, Value::Error(_)),
+                    "ISNA" => value == Value::Error(ErrorKind::NA),
+                    "ISBLANK" => value == Value::Empty,
+                    "ISNUMBER" => matches!(value, Value::Number(_)),
+                    _ => matches!(value, Value::Text(_)),
+                }))
+            }
+            "AND" | "OR" => {
+                let mut bools = Vec::new();
+                for arg in args {
+                    match self.arg(arg) {
+                        Arg::Scalar(value) => bools.push(to_bool(&value)?),
+                        Arg::Cells(values) => {
+                            for value in values {
+                                match value {
+                                    Value::Bool(b) => bools.push(b),
+                                    Value::Number(n) => bools.push(n != 0.0),
+                                    Value::Error(kind) => return Err(kind),
+                                    _ => {}
+                                }
+                            }
+                        }
+                    }
+                }
+                if bools.is_empty() {
+                    return Err(ErrorKind::Value);
+                }
+                Ok(Value::Bool(if name == "AND" {
+                    bools.iter().all(|b| *b)
+                } else {
+                    bools.iter().any(|b| *b)
+                }))
+            }
+            "NOT" => {
+                arity(1, 1)?;
+                Ok(Value::Bool(!to_bool(&self.scalar(&args[0]))?))
+            }
+            "TRUE" | "FALSE" => {
+                arity(0, 0)?;
+                Ok(Value::Bool(name == "TRUE"))
+            }
+            "NA" => {
+                arity(0, 0)?;
+                Err(ErrorKind::NA)
+            }
+            "ABS" | "INT" | "SQRT" => {
+                arity(1, 1)?;
+                let n = self.number_arg(args, 0)?;
+                match name {
+                    "ABS" => Ok(Value::Number(n.abs())),
+                    "INT" => Ok(Value::Number(n.floor())),
+                    _ if n < 0.0 => Err(ErrorKind::Num),
+                    _ => Ok(Value::Number(n.sqrt())),
+                }
+            }
+            "ROUND" => {
+                arity(1, 2)?;
+                let n = self.number_arg(args, 0)?;
+                let digits = if args.len() == 2 {
+                    self.number_arg(args, 1)?.trunc() as i32
+                } else {
+                    0
+                };
+                let factor = 10f64.powi(digits);
+                Ok(number_result((n * factor).round() / factor))
+            }
+            "MOD" => {
+                arity(2, 2)?;
+                let (n, d) = (self.number_arg(args, 0)?, self.number_arg(args, 1)?);
+                if d == 0.0 {
+                    return Err(ErrorKind::Div0);
+                }
+                Ok(Value::Number(n - d * (n / d).floor()))
+            }
+            "POWER" => {
+                arity(2, 2)?;
+                let (lhs, rhs) = (self.scalar(&args[0]), self.scalar(&args[1]));
+                binary(BinaryOp::Pow, &lhs, &rhs)
+            }
+            "CONCATENATE" | "CONCAT" => {
+                let mut text = String::new();
+                for value in self.values(args) {
+                    text.push_str(&to_text(&value)?);
+                }
+                Ok(Value::Text(text))
+            }
+            "LEN" => {
+                arity(1, 1)?;
+                Ok(Value::Number(self.text_arg(args, 0)?.chars().count() as f64))
+            }
+            "UPPER" | "LOWER" | "TRIM" => {
+                arity(1, 1)?;
+                let text = self.text_arg(args, 0)?;
+                Ok(Value::Text(match name {
+                    "UPPER" => text.to_uppercase(),
+                    "LOWER" => text.to_lowercase(),
+                    _ => text.split_whitespace().collect::<Vec<_>>().join(" "),
+                }))
+            }
+            "LEFT" | "RIGHT" => {
+                arity(1, 2)?;
+                let text: Vec<char> = self.text_arg(args, 0)?.chars().collect();
+                let count = if args.len() == 2 {
+                    self.number_arg(args, 1)?
+                } else {
+                    1.0
+                };
+                if count < 0.0 {
+                    return Err(ErrorKind::Value);
+                }
+                let count = (count as usize).min(text.len());
+                let slice = if name == "LEFT" {
+                    &text[..count]
+                } else {
+                    &text[text.len() - count..]
+                };
+                Ok(Value::Text(slice.iter().collect()))
+            }
+            "MID" => {
+                arity(3, 3)?;
+                let text: Vec<char> = self.text_arg(args, 0)?.chars().collect();
+                let (start, count) = (self.number_arg(args, 1)?, self.number_arg(args, 2)?);
+                if start < 1.0 || count < 0.0 {
+                    return Err(ErrorKind::Value);
+                }
+                let start = (start as usize - 1).min(text.len());
+                let end = start + (count as usize).min(text.len() - start);
+                Ok(Value::Text(text[start..end].iter().collect()))
+            }
+            _ => Err(ErrorKind::Name),
+        }
+    }
+}
+
+fn binary(op: BinaryOp, lhs: &Value, rhs: &Value) -> Result<Value> {
+    match op {
+        BinaryOp::Concat => Ok(Value::Text(to_text(lhs)? + &to_text(rhs)?)),
+        BinaryOp::Eq | BinaryOp::Ne | BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => {
+            for value in [lhs, rhs] {
+                if let Value::Error(kind) = value {
+                    return Err(*kind);
+                }
+            }
+            let ordering = compare(lhs, rhs);
+            Ok(Value::Bool(match op {
+                BinaryOp::Eq => ordering == Ordering::Equal,
+                BinaryOp::Ne => ordering != Ordering::Equal,
+                BinaryOp::Lt => ordering == Ordering::Less,
+                BinaryOp::Le => ordering != Ordering::Greater,
+                BinaryOp::Gt => ordering == Ordering::Greater,
+                _ => ordering != Ordering::Less,
+            }))
+        }
+        _ => {
+            let (a, b) = (to_number(lhs)?, to_number(rhs)?);
+            match op {
+                BinaryOp::Add => Ok(number_result(a + b)),
+                BinaryOp::Sub => Ok(number_result(a - b)),
+                BinaryOp::Mul => Ok(number_result(a * b)),
+                BinaryOp::Div if b == 0.0 => Err(ErrorKind::Div0),
+                BinaryOp::Div => Ok(number_result(a / b)),
+                _ if a == 0.0 && b < 0.0 => Err(ErrorKind::Div0),
+                _ if a == 0.0 && b == 0.0 => Err(ErrorKind::Num),
+                _ => Ok(number_result(a.powf(b))),
+            }
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use super::parser::ParseError;
+use crate::cell::ErrorKind;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Token {
+    Number(f64),
+    Text(String),
+    Error(ErrorKind),
+    // A run of letters, digits, `$`, `_` and `.`: a function name, a boolean,
+    // a cell reference or a column name.
+    Word(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+    Colon,
+    Percent,
+    Eof,
+}
+
+// A token and the character position it starts at.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spanned {
+    pub token: Token,
+    pub position: usize,
+}
+
+const OPERATORS: [&str; 12] = [
+    "<>", "<=", ">=", "=", "<", ">", "&", "+", "-", "*", "/", "^",
+];
+
+pub fn tokenize(chars: &[char], offset: usize) -> Result<Vec<Spanned>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut i = offset;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let position = i;
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let token = match c {
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            ',' => Token::Comma,
+            ':' => Token::Colon,
+            '%' => Token::Percent,
+            '"' => {
+                let (text, end) = lex_text(chars, i)?;
+                i = end;
+                tokens.push(Spanned {
+                    token: Token::Text(text),
+                    position,
+                });
+                continue;
+            }
+            '#' => {
+                let kind = ErrorKind::ALL
+                    .into_iter()
+                    .find(|kind| {
+                        kind.as_str().chars().enumerate().all(|(k, ec)| {
+                            chars
+                                .get(i + k)
+                                .is_some_and(|c| c.eq_ignore_ascii_case(&ec))
+                        })
+                    })
+                    .ok_or_else(|| ParseError::new("unknown error value", position))?;
+                i += kind.as_str().len();
+                tokens.push(Spanned {
+                    token: Token::Error(kind),
+                    position,
+                });
+                continue;
+            }
+            c if c.is_ascii_digit() || (c == '.' && next_is_digit(chars, i)) => {
+                let (number, end) = lex_number(chars, i)?;
+                i = end;
+                tokens.push(Spanned {
+                    token: Token::Number(number),
+                    position,
+                });
+                continue;
+            }
+            c if is_word_char(c) => {
+                let end = (i..chars.len())
+                    .find(|&j| !is_word_char(chars[j]))
+                    .unwrap_or(chars.len());
+                let word = chars[i..end].iter().collect();
+                i = end;
+                tokens.push(Spanned {
+                    token: Token::Word(word),
+                    position,
+                });
+                continue;
+            }
+            _ => {
+                let op = OPERATORS.iter().find(|op| {
+                    op.chars()
+                        .enumerate()
+                        .all(|(k, oc)| chars.get(i + k) == Some(&oc))
+                });
+                match op {
+                    Some(op) => {
+                        i += op.len() - 1;
+                        Token::Op(op)
+                    }
+                    None => {
+                        return Err(ParseError::new(
+                            format!("unexpected character `{}`", c),
+                            position,
+                        ))
+                    }
+                }
+            }
+        };
+        i += 1;
+        tokens.push(Spanned { token, position });
+    }
+
+    tokens.push(Spanned {
+        token: Token::Eof,
+        position: chars.len(),
+    });
+    Ok(tokens)
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '$' || c == '_' || c == '.'
+}
+
+fn next_is_digit(chars: &[char], i: usize) -> bool {
+    chars.get(i + 1).is_some_and(|c| c.is_ascii_digit())
+}
+
+// Strings are delimited by `"`; a doubled `""` inside is a literal quote.
+fn lex_text(chars: &[char], start: usize) -> Result<(String, usize), ParseError> {
+    let mut text = String::new();
+    let mut i = start + 1;
+
+    loop {
+        match chars.get(i) {
+            None => return Err(ParseError::new("unterminated string", start)),
+            Some('"') if chars.get(i + 1) == Some(&'"') => {
+                text.push('"');
+                i += 2;
+            }
+            Some('"') => return Ok((text, i + 1)),
+            Some(&c) => {
+                text.push(c);
+                i += 1;
+            }
+        }
+    }
+}
+
+fn lex_number(chars: &[char], start: usize) -> Result<(f64, usize), ParseError> {
+    let mut i = start;
+    let digits = |i: &mut usize| {
+        while chars.get(*i).is_some_and(|c| c.is_ascii_digit()) {
+            *i += 1;
+        }
+    };
+
+    digits(&mut i);
+    if chars.get(i) == Some(&'.') {
+        i += 1;
+        digits(&mut i);
+    }
+    if matches!(chars.get(i), Some('e') | Some('E')) {
+        let mut j = i + 1;
+        if matches!(chars.get(j), Some('+') | Some('-')) {
+            j += 1;
+        }
+        if chars.get(j).is_some_and(|c| c.is_ascii_digit()) {
+            i = j;
+            digits(&mut i);
+        }
+    }
+
+    let text: String = chars[start..i].iter().collect();
+    // `1e400` would become infinity, which cannot be printed back.
+    text.parse()
+        .ok()
+        .filter(|n: &f64| n.is_finite())
+        .map(|n| (n, i))
+        .ok_or_else(|| ParseError::new(format!("invalid number `{}`", text), start))
+}
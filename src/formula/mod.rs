@@ -0,0 +1,41 @@
+mod ast;
+mod eval;
+mod lexer;
+mod parser;
+
+use std::fmt;
+
+pub use ast::{BinaryOp, Expr, UnaryOp};
+pub use eval::{evaluate, EvalContext};
+pub use parser::ParseError;
+
+// A parsed cell formula. The leading `=` is not part of `expr`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Formula {
+    expr: Expr,
+}
+
+impl Formula {
+    pub fn new(expr: Expr) -> Self {
+        Self { expr }
+    }
+
+    // Parses `text`, which must start with `=`.
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        parser::parse(text).map(Self::new)
+    }
+
+    pub fn is_formula(text: &str) -> bool {
+        text.starts_with('=') && text.len() > 1
+    }
+
+    pub fn expr(&self) -> &Expr {
+        &self.expr
+    }
+}
+
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "={}", self.expr)
+    }
+}
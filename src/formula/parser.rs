@@ -0,0 +1,247 @@
+use std::fmt;
+
+use super::ast::{BinaryOp, Expr, UnaryOp};
+use super::lexer::{tokenize, Spanned, Token};
+use crate::cell_ref::{column_index, CellRef, MAX_ROWS};
+use crate::range::Area;
+
+// A syntax error and the zero-based character position it was found at,
+// counted from the start of the formula including the leading `=`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    pub position: usize,
+}
+
+impl ParseError {
+    pub fn new(message: impl Into<String>, position: usize) -> Self {
+        Self {
+            message: message.into(),
+            position,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub fn parse(text: &str) -> Result<Expr, ParseError> {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.first() != Some(&'=') {
+        return Err(ParseError::new("formula must start with `=`", 0));
+    }
+
+    let tokens = tokenize(&chars, 1)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.comparison()?;
+    match parser.peek() {
+        Token::Eof => Ok(expr),
+        Token::RParen => Err(parser.error("unmatched `)`")),
+        _ => Err(parser.error("unexpected token")),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Spanned>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].token
+    }
+
+    fn position(&self) -> usize {
+        self.tokens[self.pos].position
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.pos].token.clone();
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        ParseError::new(message, self.position())
+    }
+
+    fn binary_op(&self, ops: &[BinaryOp]) -> Option<BinaryOp> {
+        match self.peek() {
+            Token::Op(symbol) => ops.iter().copied().find(|op| op.symbol() == *symbol),
+            _ => None,
+        }
+    }
+
+    fn binary_level(
+        &mut self,
+        ops: &[BinaryOp],
+        next: fn(&mut Self) -> Result<Expr, ParseError>,
+    ) -> Result<Expr, ParseError> {
+        let mut lhs = next(self)?;
+        while let Some(op) = self.binary_op(ops) {
+            self.advance();
+            let rhs = next(self)?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, ParseError> {
+        use BinaryOp::*;
+        self.binary_level(&[Eq, Ne, Lt, Le, Gt, Ge], Self::concat)
+    }
+
+    fn concat(&mut self) -> Result<Expr, ParseError> {
+        self.binary_level(&[BinaryOp::Concat], Self::additive)
+    }
+
+    fn additive(&mut self) -> Result<Expr, ParseError> {
+        self.binary_level(&[BinaryOp::Add, BinaryOp::Sub], Self::multiplicative)
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, ParseError> {
+        self.binary_level(&[BinaryOp::Mul, BinaryOp::Div], Self::power)
+    }
+
+    fn power(&mut self) -> Result<Expr, ParseError> {
+        self.binary_level(&[BinaryOp::Pow], Self::percent)
+    }
+
+    fn percent(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.unary()?;
+        while *self.peek() == Token::Percent {
+            self.advance();
+            expr = Expr::Percent(Box::new(expr));
+        }
+        Ok(expr)
+    }
+
+    // Negation binds tighter than `^` in Excel, so `=-2^2` is 4.
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        let op = match self.peek() {
+            Token::Op("-") => UnaryOp::Minus,
+            Token::Op("+") => UnaryOp::Plus,
+            _ => return self.primary(),
+        };
+        self.advance();
+        Ok(Expr::Unary(op, Box::new(self.unary()?)))
+    }
+
+    fn primary(&mut self) -> Result<Expr, ParseError> {
+        let position = self.position();
+        match self.advance() {
+            Token::Number(n) => {
+                if *self.peek() == Token::Colon {
+                    return self.range(&n.to_string(), position);
+                }
+                Ok(Expr::Number(n))
+            }
+            Token::Text(s) => Ok(Expr::Text(s)),
+            Token::Error(kind) => Ok(Expr::Error(kind)),
+            Token::LParen => {
+                let inner = self.comparison()?;
+                self.expect(Token::RParen, "expected `)`")?;
+                Ok(Expr::Group(Box::new(inner)))
+            }
+            Token::Word(word) => self.word(word, position),
+            Token::Eof => Err(ParseError::new("unexpected end of formula", position)),
+            _ => Err(ParseError::new("unexpected token", position)),
+        }
+    }
+
+    fn word(&mut self, word: String, position: usize) -> Result<Expr, ParseError> {
+        if *self.peek() == Token::LParen {
+            self.advance();
+            return self.call(word.to_ascii_uppercase());
+        }
+        if *self.peek() == Token::Colon {
+            return self.range(&word, position);
+        }
+        if word.eq_ignore_ascii_case("TRUE") {
+            return Ok(Expr::Bool(true));
+        }
+        if word.eq_ignore_ascii_case("FALSE") {
+            return Ok(Expr::Bool(false));
+        }
+        match word.parse::<CellRef>() {
+            Ok(cell) => Ok(Expr::Ref(cell)),
+            Err(_) if looks_like_cell(&word) => Err(ParseError::new(
+                format!("invalid reference `{}`", word),
+                position,
+            )),
+            Err(_) => Ok(Expr::Name(word.to_ascii_uppercase())),
+        }
+    }
+
+    fn call(&mut self, name: String) -> Result<Expr, ParseError> {
+        let mut args = Vec::new();
+        if *self.peek() == Token::RParen {
+            self.advance();
+            return Ok(Expr::Call(name, args));
+        }
+        loop {
+            args.push(self.comparison()?);
+            match self.peek() {
+                Token::Comma => {
+                    self.advance();
+                }
+                Token::RParen => {
+                    self.advance();
+                    return Ok(Expr::Call(name, args));
+                }
+                _ => return Err(self.error("expected `,` or `)`")),
+            }
+        }
+    }
+
+    // Parses the right-hand side of `first:` into an area.
+    fn range(&mut self, first: &str, position: usize) -> Result<Expr, ParseError> {
+        self.advance();
+        let second = match self.advance() {
+            Token::Word(word) => word,
+            Token::Number(n) => n.to_string(),
+            _ => return Err(ParseError::new("incomplete range", position)),
+        };
+        let text = format!("{}:{}", first, second);
+        if !is_range_side(first) || !is_range_side(&second) {
+            return Err(ParseError::new(
+                format!("invalid range `{}`", text),
+                position,
+            ));
+        }
+        text.parse::<Area>()
+            .map(Expr::Range)
+            .map_err(|err| ParseError::new(err.to_string(), position))
+    }
+
+    fn expect(&mut self, token: Token, message: &str) -> Result<(), ParseError> {
+        if *self.peek() == token {
+            self.advance();
+            Ok(())
+        } else {
+            Err(self.error(message))
+        }
+    }
+}
+
+// Letters followed by digits, optionally `$`-marked: clearly meant as a
+// cell reference, so a failure to parse it is an error rather than a name.
+fn looks_like_cell(word: &str) -> bool {
+    let word = word.replace('$', "");
+    let letters = word.bytes().take_while(u8::is_ascii_alphabetic).count();
+    letters > 0 && letters < word.len() && word[letters..].bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_range_side(side: &str) -> bool {
+    let bare = side.replace('$', "");
+    looks_like_cell(side)
+        || column_index(&bare).is_some()
+        || bare.parse::<u32>().is_ok_and(|row| row <= MAX_ROWS)
+}
@@ -0,0 +1,895 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::axis::Axis;
+use crate::cell::{CellObject, CellValue, ErrorKind};
+use crate::cell_ref::{CellRef, MAX_COLUMNS, MAX_ROWS};
+use crate::column::{CoercionError, Column, ColumnType, ConversionReport, EditorKind};
+use crate::deps::DependencyGraph;
+use crate::format::{FormattedValue, NumberFormat};
+use crate::formula::{evaluate, EvalContext, Formula, ParseError};
+use crate::range::Area;
+use crate::store::CellStore;
+use crate::style::CellStyle;
+
+pub const DEFAULT_COLUMN_WIDTH: f64 = 80.0;
+pub const DEFAULT_ROW_HEIGHT: f64 = 30.0;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GridError {
+    OutOfBounds(CellRef),
+    Parse(ParseError),
+    Coercion(CoercionError),
+    // Growing past the size limit, or a limit smaller than the grid.
+    SizeLimit,
+    // Deleting every row or every column.
+    DeleteAll,
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridError::OutOfBounds(cell) => write!(f, "cell {} is outside the grid", cell),
+            GridError::Parse(err) => write!(f, "{}", err),
+            GridError::Coercion(err) => write!(f, "{}", err),
+            GridError::SizeLimit => write!(f, "the grid cannot grow past its size limit"),
+            GridError::DeleteAll => write!(f, "cannot delete every row or column"),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+// Opt-in resolution of circular references, as in desktop spreadsheets.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IterativeCalc {
+    pub max_iterations: u32,
+    pub max_change: f64,
+}
+
+impl Default for IterativeCalc {
+    fn default() -> Self {
+        Self {
+            max_iterations: 100,
+            max_change: 0.001,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid {
+    num_rows: u32,
+    num_cols: u32,
+    // The most rows and columns the grid may grow to.
+    max_rows: u32,
+    max_cols: u32,
+    columns: Vec<Column>,
+    column_widths: Axis,
+    row_heights: Axis,
+    cells: CellStore,
+    deps: DependencyGraph,
+    // Cells edited since the last `recalculate`.
+    dirty: BTreeSet<CellRef>,
+    iterative: Option<IterativeCalc>,
+}
+
+impl Grid {
+    pub fn new(num_rows: u32, num_cols: u32) -> Self {
+        let columns = (0..num_cols).map(Column::new).collect();
+
+        Self {
+            columns,
+            num_rows,
+            num_cols,
+            max_rows: MAX_ROWS,
+            max_cols: MAX_COLUMNS,
+            column_widths: Axis::new(num_cols, DEFAULT_COLUMN_WIDTH),
+            row_heights: Axis::new(num_rows, DEFAULT_ROW_HEIGHT),
+            cells: CellStore::new(),
+            deps: DependencyGraph::new(),
+            dirty: BTreeSet::new(),
+            iterative: None,
+        }
+    }
+
+    pub fn num_rows(&self) -> u32 {
+        self.num_rows
+    }
+
+    pub fn num_cols(&self) -> u32 {
+        self.num_cols
+    }
+
+    pub fn size_limit(&self) -> (u32, u32) {
+        (self.max_rows, self.max_cols)
+    }
+
+    // Caps how far the grid can grow. The limit can be no smaller than the
+    // grid already is and no larger than `MAX_ROWS` by `MAX_COLUMNS`.
+    pub fn set_size_limit(&mut self, rows: u32, columns: u32) -> Result<(), GridError> {
+        if rows < self.num_rows
+            || columns < self.num_cols
+            || rows > MAX_ROWS
+            || columns > MAX_COLUMNS
+        {
+            return Err(GridError::SizeLimit);
+        }
+        self.max_rows = rows;
+        self.max_cols = columns;
+        Ok(())
+    }
+
+    // Adds empty rows and columns at the end until the grid is at least
+    // `rows` by `columns`, stopping at the size limit. Nothing moves, so
+    // unlike `insert_rows` no formula needs rewriting. Returns whether the
+    // grid grew.
+    pub fn grow_to(&mut self, rows: u32, columns: u32) -> bool {
+        let rows = rows.min(self.max_rows);
+        let columns = columns.min(self.max_cols);
+        let mut grew = false;
+        if rows > self.num_rows {
+            self.row_heights.insert(self.num_rows, rows - self.num_rows);
+            self.num_rows = rows;
+            grew = true;
+        }
+        if columns > self.num_cols {
+            self.column_widths
+                .insert(self.num_cols, columns - self.num_cols);
+            self.columns
+                .extend((self.num_cols..columns).map(Column::new));
+            self.num_cols = columns;
+            grew = true;
+        }
+        grew
+    }
+
+    // The smallest area holding every value and formula, or `None` when the
+    // grid is blank. Formatting alone does not count.
+    pub fn used_range(&self) -> Option<Area> {
+        let mut used = self
+            .cells
+            .iter()
+            .filter(|c| c.get_formula().is_some() || !c.get_value().is_empty())
+            .map(|c| CellRef::new(c.row_id, c.column_id));
+        let first = used.next()?;
+        let (top, left, bottom, right) = used.fold(
+            (first.row, first.column, first.row, first.column),
+            |(top, left, bottom, right), c| {
+                (
+                    top.min(c.row),
+                    left.min(c.column),
+                    bottom.max(c.row),
+                    right.max(c.column),
+                )
+            },
+        );
+        Some(Area::from_bounds(top, left, bottom, right))
+    }
+
+    pub fn get_column(&self, col_num: u32) -> Option<&Column> {
+        self.columns.get(col_num as usize)
+    }
+
+    pub fn get_column_mut(&mut self, col_num: u32) -> Option<&mut Column> {
+        self.columns.get_mut(col_num as usize)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = &Column> {
+        self.columns.iter()
+    }
+
+    pub fn cells(&self) -> &CellStore {
+        &self.cells
+    }
+
+    pub fn get_cell(&self, row: u32, column: u32) -> Option<&CellObject> {
+        self.cells.get(row, column)
+    }
+
+    // Cells outside the grid bounds are dropped rather than stored.
+    pub fn insert_cell(&mut self, cell: CellObject) -> Option<CellObject> {
+        if cell.row_id >= self.num_rows || cell.column_id >= self.num_cols {
+            return None;
+        }
+        let at = CellRef::new(cell.row_id, cell.column_id);
+        match cell.get_formula() {
+            Some(formula) => self.deps.set_precedents(at, formula.expr().references()),
+            None => self.deps.remove(at),
+        }
+        self.dirty.insert(at);
+        self.cells.insert(cell)
+    }
+
+    pub fn contains(&self, cell: CellRef) -> bool {
+        cell.row < self.num_rows && cell.column < self.num_cols
+    }
+
+    // Returns the value stored at `cell`, or an empty value when nothing is
+    // stored there.
+    pub fn get_value(&self, cell: CellRef) -> CellValue {
+        self.cells
+            .get(cell.row, cell.column)
+            .map(|c| c.get_value())
+            .unwrap_or(CellValue::Empty)
+    }
+
+    // Writes `value` into `cell`, converted to the column's type. Writing an
+    // empty value removes the cell from the store.
+    pub fn set_value(&mut self, cell: CellRef, value: CellValue) -> Result<(), GridError> {
+        if !self.contains(cell) {
+            return Err(GridError::OutOfBounds(cell));
+        }
+        let value = self
+            .column_type(cell.column)
+            .convert(&value)
+            .map_err(GridError::Coercion)?;
+        let cell = cell.to_relative();
+        self.deps.remove(cell);
+        self.dirty.insert(cell);
+        match self.cells.get_mut(cell.row, cell.column) {
+            Some(existing) => {
+                existing.set_value(value);
+                existing.set_formula(None);
+                if existing.is_empty() {
+                    self.cells.remove(cell.row, cell.column);
+                }
+            }
+            None => {
+                self.cells
+                    .insert(CellObject::with_value(cell.column, cell.row, value));
+            }
+        }
+        Ok(())
+    }
+
+    // Stores text typed by the user: formulas are parsed, anything else is
+    // parsed according to the column's type and rejected if it does not fit.
+    pub fn set_input(&mut self, cell: CellRef, text: &str) -> Result<(), GridError> {
+        if Formula::is_formula(text) {
+            return self.set_formula(cell, text);
+        }
+        if !self.contains(cell) {
+            return Err(GridError::OutOfBounds(cell));
+        }
+        let value = self
+            .column_type(cell.column)
+            .parse(text)
+            .map_err(GridError::Coercion)?;
+        self.set_value(cell, value)
+    }
+
+    // The text an editor opens with: the formula, or the value written the
+    // way its column would parse it back.
+    pub fn edit_text(&self, cell: CellRef) -> String {
+        match self.get_formula(cell) {
+            Some(formula) => formula.to_string(),
+            None => self.column_type(cell.column).display(&self.get_value(cell)),
+        }
+    }
+
+    pub fn column_type(&self, column: u32) -> ColumnType {
+        self.get_column(column)
+            .map(|c| c.column_type.clone())
+            .unwrap_or(ColumnType::General)
+    }
+
+    // Whether `cell` is drawn as a ticked or unticked checkbox rather than
+    // as text: it is in a column with a checkbox editor and holds a
+    // boolean or nothing.
+    pub fn checkbox(&self, cell: CellRef) -> Option<bool> {
+        let is_checkbox = self
+            .get_column(cell.column)
+            .is_some_and(|c| c.column_type.editor() == EditorKind::Checkbox);
+        match self.get_value(cell) {
+            CellValue::Bool(checked) if is_checkbox => Some(checked),
+            CellValue::Empty if is_checkbox => Some(false),
+            _ => None,
+        }
+    }
+
+    // Changes the type of `column` and converts the plain values already in
+    // it. Formula cells are left alone.
+    pub fn set_column_type(
+        &mut self,
+        column: u32,
+        column_type: ColumnType,
+    ) -> Result<ConversionReport, GridError> {
+        let Some(target) = self.columns.get_mut(column as usize) else {
+            return Err(GridError::OutOfBounds(CellRef::new(0, column)));
+        };
+        target.column_type = column_type.clone();
+
+        let mut report = ConversionReport::default();
+        let cells: Vec<(CellRef, CellValue)> = self
+            .cells
+            .column(column)
+            .filter(|c| c.get_formula().is_none())
+            .map(|c| (CellRef::new(c.row_id, c.column_id), c.get_value()))
+            .collect();
+        for (cell, value) in cells {
+            match column_type.convert(&value) {
+                Ok(converted) => {
+                    if converted != value {
+                        self.store_value(cell, converted);
+                        self.dirty.insert(cell);
+                    }
+                    report.converted += 1;
+                }
+                Err(_) => report.failed.push(cell),
+            }
+        }
+        Ok(report)
+    }
+
+    // The format that applies to `cell`: its own, or else its column's.
+    pub fn number_format(&self, cell: CellRef) -> Option<&NumberFormat> {
+        self.cells
+            .get(cell.row, cell.column)
+            .and_then(|c| c.get_format())
+            .or_else(|| self.get_column(cell.column)?.format.as_ref())
+    }
+
+    pub fn set_cell_format(
+        &mut self,
+        cell: CellRef,
+        format: Option<NumberFormat>,
+    ) -> Result<(), GridError> {
+        self.update_cell(cell, |c| c.set_format(format))
+    }
+
+    pub fn cell_style(&self, cell: CellRef) -> CellStyle {
+        self.cells
+            .get(cell.row, cell.column)
+            .map(|c| c.get_style())
+            .unwrap_or_default()
+    }
+
+    pub fn set_cell_style(&mut self, cell: CellRef, style: CellStyle) -> Result<(), GridError> {
+        self.update_cell(cell, |c| c.set_style(style))
+    }
+
+    // Applies `update` to the cell stored at `cell`, creating it if needed
+    // and dropping it again if it ends up empty.
+    fn update_cell(
+        &mut self,
+        cell: CellRef,
+        update: impl FnOnce(&mut CellObject),
+    ) -> Result<(), GridError> {
+        if !self.contains(cell) {
+            return Err(GridError::OutOfBounds(cell));
+        }
+        match self.cells.get_mut(cell.row, cell.column) {
+            Some(existing) => {
+                update(existing);
+                if existing.is_empty() {
+                    self.cells.remove(cell.row, cell.column);
+                }
+            }
+            None => {
+                let mut new_cell = CellObject::new(cell.column, cell.row);
+                update(&mut new_cell);
+                self.cells.insert(new_cell);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn set_column_format(
+        &mut self,
+        column: u32,
+        format: Option<NumberFormat>,
+    ) -> Result<(), GridError> {
+        match self.columns.get_mut(column as usize) {
+            Some(target) => {
+                target.format = format;
+                Ok(())
+            }
+            None => Err(GridError::OutOfBounds(CellRef::new(0, column))),
+        }
+    }
+
+    // The text shown in `cell`. A number format, if one applies, wins over
+    // the column type's own display.
+    pub fn display_value(&self, cell: CellRef) -> FormattedValue {
+        let value = self.get_value(cell);
+        match self.number_format(cell) {
+            Some(format) => format.format(&value),
+            None => FormattedValue {
+                text: self.column_type(cell.column).display(&value),
+                color: None,
+            },
+        }
+    }
+
+    // Removes everything stored in `area`: values, formulas and formatting.
+    pub fn clear_area(&mut self, area: &Area) {
+        let cells: Vec<CellRef> = self
+            .cells
+            .area(area)
+            .map(|c| CellRef::new(c.row_id, c.column_id))
+            .collect();
+        for cell in cells {
+            self.cells.remove(cell.row, cell.column);
+            self.deps.remove(cell);
+            self.dirty.insert(cell);
+        }
+    }
+
+    // Copies the cells of `source` so that its top-left corner lands on
+    // `dest`, replacing what was there. Relative references in copied
+    // formulas move with them; ones pushed off the sheet become `#REF!`.
+    pub fn copy_area(&mut self, source: &Area, dest: CellRef) {
+        let (rows, columns) = offset_between(source.start, dest);
+        let copies = self.take_snapshot(source);
+        if let Some(target) = self.landing_area(source, dest) {
+            self.clear_area(&target);
+        }
+        for mut cell in copies {
+            if let Some(formula) = cell.get_formula() {
+                let expr = formula
+                    .expr()
+                    .map_references(&mut |area| area.shift_relative(rows, columns));
+                cell.set_formula(Some(Formula::new(expr)));
+            }
+            self.place(cell, rows, columns);
+        }
+    }
+
+    // Moves the cells of `source` so that its top-left corner lands on
+    // `dest`. Formulas anywhere in the grid that pointed into `source`
+    // follow the cells to their new place; the moved formulas themselves
+    // keep pointing where they did. References to the cells the move
+    // overwrites become `#REF!`.
+    pub fn move_area(&mut self, source: &Area, dest: CellRef) {
+        let (rows, columns) = offset_between(source.start, dest);
+        let moved = self.take_snapshot(source);
+        self.clear_area(source);
+        let target = self.landing_area(source, dest);
+        if let Some(target) = &target {
+            self.clear_area(target);
+        }
+        let mut follow = |area: Area| {
+            let overwritten = target
+                .and_then(|target| target.intersect(&area))
+                .is_some_and(|hit| !source.contains_area(&hit));
+            if source.contains_area(&area) {
+                area.offset(rows, columns)
+            } else if overwritten {
+                None
+            } else {
+                Some(area)
+            }
+        };
+        self.rewrite_references(&mut follow);
+        for mut cell in moved {
+            if let Some(formula) = cell.get_formula() {
+                let expr = formula.expr().map_references(&mut follow);
+                cell.set_formula(Some(Formula::new(expr)));
+            }
+            self.place(cell, rows, columns);
+        }
+    }
+
+    // Where `source` lands when copied or moved to `dest`, clipped to the
+    // grid.
+    pub fn landing_area(&self, source: &Area, dest: CellRef) -> Option<Area> {
+        if !self.contains(dest) {
+            return None;
+        }
+        let bottom = dest.row.saturating_add(source.num_rows() - 1);
+        let right = dest.column.saturating_add(source.num_cols() - 1);
+        Some(Area::from_bounds(
+            dest.row,
+            dest.column,
+            bottom.min(self.num_rows - 1),
+            right.min(self.num_cols - 1),
+        ))
+    }
+
+    // Replaces everything stored in `area` with `cells`, as it was before
+    // an edit.
+    pub fn restore_area(&mut self, area: &Area, cells: Vec<CellObject>) {
+        self.clear_area(area);
+        for cell in cells {
+            self.insert_cell(cell);
+        }
+    }
+
+    // Passes every reference in every formula through `f`, as when cells
+    // move. References `f` rejects become `#REF!`.
+    pub fn rewrite_references(&mut self, f: &mut impl FnMut(Area) -> Option<Area>) {
+        let formulas: Vec<(CellRef, Formula)> = self
+            .cells
+            .iter()
+            .filter_map(|c| {
+                Some((
+                    CellRef::new(c.row_id, c.column_id),
+                    c.get_formula()?.clone(),
+                ))
+            })
+            .collect();
+        for (cell, formula) in formulas {
+            let expr = formula.expr().map_references(f);
+            if expr != *formula.expr() {
+                self.deps.set_precedents(cell, expr.references());
+                self.dirty.insert(cell);
+                if let Some(stored) = self.cells.get_mut(cell.row, cell.column) {
+                    stored.set_formula(Some(Formula::new(expr)));
+                }
+            }
+        }
+    }
+
+    fn take_snapshot(&self, area: &Area) -> Vec<CellObject> {
+        self.cells.area(area).cloned().collect()
+    }
+
+    // Stores `cell` shifted by (`rows`, `columns`), if that is on the grid.
+    fn place(&mut self, mut cell: CellObject, rows: i64, columns: i64) {
+        let at = CellRef::new(cell.row_id, cell.column_id).offset(rows, columns);
+        if let Some(at) = at.filter(|at| self.contains(*at)) {
+            cell.row_id = at.row;
+            cell.column_id = at.column;
+            self.insert_cell(cell);
+        }
+    }
+
+    // Inserts `count` empty rows before row `at` (`at` may be `num_rows` to
+    // append), within the size limit. Cells below move down and every
+    // reference follows them.
+    pub fn insert_rows(&mut self, at: u32, count: u32) -> Result<(), GridError> {
+        if at > self.num_rows {
+            return Err(GridError::OutOfBounds(CellRef::new(at, 0)));
+        }
+        if self.num_rows as u64 + count as u64 > self.max_rows as u64 {
+            return Err(GridError::SizeLimit);
+        }
+        self.num_rows += count;
+        self.row_heights.insert(at, count);
+        self.restructure(
+            |cell| cell.offset(if cell.row >= at { count as i64 } else { 0 }, 0),
+            |area| area.insert_rows(at, count),
+        );
+        Ok(())
+    }
+
+    // Deletes rows `at..at + count`. Cells below move up; references to the
+    // deleted cells become `#REF!`, and ranges across them shrink.
+    pub fn delete_rows(&mut self, at: u32, count: u32) -> Result<(), GridError> {
+        if at as u64 + count as u64 > self.num_rows as u64 {
+            return Err(GridError::OutOfBounds(CellRef::new(at, 0)));
+        }
+        if count == self.num_rows {
+            return Err(GridError::DeleteAll);
+        }
+        self.num_rows -= count;
+        self.row_heights.remove(at, count);
+        self.restructure(
+            |cell| match cell.row {
+                row if row < at => Some(cell),
+                row if row >= at + count => cell.offset(-(count as i64), 0),
+                _ => None,
+            },
+            |area| area.delete_rows(at, count),
+        );
+        Ok(())
+    }
+
+    pub fn insert_columns(&mut self, at: u32, count: u32) -> Result<(), GridError> {
+        if at > self.num_cols {
+            return Err(GridError::OutOfBounds(CellRef::new(0, at)));
+        }
+        if self.num_cols as u64 + count as u64 > self.max_cols as u64 {
+            return Err(GridError::SizeLimit);
+        }
+        self.num_cols += count;
+        self.column_widths.insert(at, count);
+        let index = at as usize;
+        self.columns
+            .splice(index..index, (at..at + count).map(Column::new));
+        self.renumber_columns();
+        self.restructure(
+            |cell| cell.offset(0, if cell.column >= at { count as i64 } else { 0 }),
+            |area| area.insert_columns(at, count),
+        );
+        Ok(())
+    }
+
+    pub fn delete_columns(&mut self, at: u32, count: u32) -> Result<(), GridError> {
+        if at as u64 + count as u64 > self.num_cols as u64 {
+            return Err(GridError::OutOfBounds(CellRef::new(0, at)));
+        }
+        if count == self.num_cols {
+            return Err(GridError::DeleteAll);
+        }
+        self.num_cols -= count;
+        self.column_widths.remove(at, count);
+        self.columns.drain(at as usize..(at + count) as usize);
+        self.renumber_columns();
+        self.restructure(
+            |cell| match cell.column {
+                column if column < at => Some(cell),
+                column if column >= at + count => cell.offset(0, -(count as i64)),
+                _ => None,
+            },
+            |area| area.delete_columns(at, count),
+        );
+        Ok(())
+    }
+
+    fn renumber_columns(&mut self) {
+        for (i, column) in self.columns.iter_mut().enumerate() {
+            column.column_id = i as u32;
+        }
+    }
+
+    // Moves every stored cell to `move_cell(cell)`, dropping it on `None`,
+    // and passes every reference through `adjust`. Dependencies are rebuilt
+    // and every formula is recalculated, since any of them may have
+    // changed.
+    fn restructure(
+        &mut self,
+        move_cell: impl Fn(CellRef) -> Option<CellRef>,
+        mut adjust: impl FnMut(Area) -> Option<Area>,
+    ) {
+        let cells = std::mem::take(&mut self.cells);
+        self.deps = DependencyGraph::new();
+        self.dirty.clear();
+        for mut cell in cells.iter().cloned() {
+            let Some(at) = move_cell(CellRef::new(cell.row_id, cell.column_id)) else {
+                continue;
+            };
+            cell.row_id = at.row;
+            cell.column_id = at.column;
+            if let Some(formula) = cell.get_formula() {
+                let expr = formula.expr().map_references(&mut adjust);
+                self.deps.set_precedents(at, expr.references());
+                self.dirty.insert(at);
+                cell.set_formula(Some(Formula::new(expr)));
+            }
+            self.cells.insert(cell);
+        }
+    }
+
+    pub fn get_formula(&self, cell: CellRef) -> Option<&Formula> {
+        self.cells
+            .get(cell.row, cell.column)
+            .and_then(|c| c.get_formula())
+    }
+
+    // Parses `text` (which must start with `=`) and stores it in `cell`. The
+    // cell's value is computed on the next `recalculate`.
+    pub fn set_formula(&mut self, cell: CellRef, text: &str) -> Result<(), GridError> {
+        if !self.contains(cell) {
+            return Err(GridError::OutOfBounds(cell));
+        }
+        let formula = Formula::parse(text).map_err(GridError::Parse)?;
+        let cell = cell.to_relative();
+        self.deps.set_precedents(cell, formula.expr().references());
+        self.dirty.insert(cell);
+        match self.cells.get_mut(cell.row, cell.column) {
+            Some(existing) => existing.set_formula(Some(formula)),
+            None => {
+                let mut new_cell = CellObject::new(cell.column, cell.row);
+                new_cell.set_formula(Some(formula));
+                self.cells.insert(new_cell);
+            }
+        }
+        Ok(())
+    }
+
+    // Recomputes the formulas affected by edits since the last call, in
+    // dependency order, and returns every cell whose content changed: the
+    // edited cells themselves plus any formula whose result differs.
+    pub fn recalculate(&mut self) -> BTreeSet<CellRef> {
+        let edited = std::mem::take(&mut self.dirty);
+        let mut pending = self.deps.transitive_dependents(edited.iter().copied());
+        pending.extend(
+            edited
+                .iter()
+                .filter(|cell| self.get_formula(**cell).is_some()),
+        );
+
+        let mut changed = edited;
+        for group in self.deps.evaluation_order(&pending) {
+            if !self.deps.is_cycle(&group) {
+                if self.evaluate_cell(group[0]).is_some() {
+                    changed.insert(group[0]);
+                }
+                continue;
+            }
+            match self.iterative {
+                Some(settings) => self.iterate_cycle(&group, settings, &mut changed),
+                None => {
+                    for cell in group {
+                        if self.store_value(cell, CellValue::Error(ErrorKind::Circular)) {
+                            changed.insert(cell);
+                        }
+                    }
+                }
+            }
+        }
+        changed
+    }
+
+    // Evaluates a cycle repeatedly, each pass reading the previous pass's
+    // results, until no value moves by more than `max_change` or the
+    // iteration limit is reached.
+    fn iterate_cycle(
+        &mut self,
+        group: &[CellRef],
+        settings: IterativeCalc,
+        changed: &mut BTreeSet<CellRef>,
+    ) {
+        for cell in group {
+            if self.get_value(*cell) == CellValue::Error(ErrorKind::Circular) {
+                self.store_value(*cell, CellValue::Empty);
+            }
+        }
+        for _ in 0..settings.max_iterations {
+            let mut max_change: f64 = 0.0;
+            for cell in group {
+                if let Some((old, new)) = self.evaluate_cell(*cell) {
+                    changed.insert(*cell);
+                    max_change = max_change.max(match (old.as_number(), new.as_number()) {
+                        (Some(a), Some(b)) => (a - b).abs(),
+                        _ => f64::INFINITY,
+                    });
+                }
+            }
+            if max_change <= settings.max_change {
+                break;
+            }
+        }
+    }
+
+    // Evaluates the formula in `cell` and stores the result. Returns the old
+    // and new value if they differ.
+    fn evaluate_cell(&mut self, cell: CellRef) -> Option<(CellValue, CellValue)> {
+        let value = evaluate(self.get_formula(cell)?.expr(), self);
+        let old = self.get_value(cell);
+        if self.store_value(cell, value.clone()) {
+            Some((old, value))
+        } else {
+            None
+        }
+    }
+
+    // Replaces the cached value of a stored cell without touching its
+    // formula. Returns whether the value changed.
+    fn store_value(&mut self, cell: CellRef, value: CellValue) -> bool {
+        match self.cells.get_mut(cell.row, cell.column) {
+            Some(existing) if existing.get_value() != value => {
+                existing.set_value(value);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // The chain of formulas through which `cell` reads itself, if any.
+    pub fn cycle_path(&self, cell: CellRef) -> Option<Vec<CellRef>> {
+        self.deps.find_cycle(cell)
+    }
+
+    pub fn circular_references(&self) -> Vec<Vec<CellRef>> {
+        self.deps.cycles()
+    }
+
+    pub fn iterative_calculation(&self) -> Option<IterativeCalc> {
+        self.iterative
+    }
+
+    // With `Some(settings)`, cycles are resolved by iteration instead of
+    // being marked `#CIRC!`. Every cycle is recomputed on the next
+    // `recalculate`.
+    pub fn set_iterative_calculation(&mut self, settings: Option<IterativeCalc>) {
+        self.iterative = settings;
+        for cycle in self.deps.cycles() {
+            self.dirty.extend(cycle);
+        }
+    }
+
+    pub fn dependencies(&self) -> &DependencyGraph {
+        &self.deps
+    }
+
+    pub fn column_widths(&self) -> &Axis {
+        &self.column_widths
+    }
+
+    pub fn row_heights(&self) -> &Axis {
+        &self.row_heights
+    }
+
+    // Width on screen; zero for a hidden column.
+    pub fn column_width(&self, column: u32) -> f64 {
+        self.column_widths.size(column)
+    }
+
+    pub fn row_height(&self, row: u32) -> f64 {
+        self.row_heights.size(row)
+    }
+
+    // A width of zero hides the column; any other width shows it again.
+    pub fn set_column_width(&mut self, column: u32, width: f64) -> Result<(), GridError> {
+        if column >= self.num_cols {
+            return Err(GridError::OutOfBounds(CellRef::new(0, column)));
+        }
+        self.column_widths.set_size(column, width);
+        Ok(())
+    }
+
+    pub fn set_row_height(&mut self, row: u32, height: f64) -> Result<(), GridError> {
+        if row >= self.num_rows {
+            return Err(GridError::OutOfBounds(CellRef::new(row, 0)));
+        }
+        self.row_heights.set_size(row, height);
+        Ok(())
+    }
+
+    pub fn set_column_hidden(&mut self, column: u32, hidden: bool) -> Result<(), GridError> {
+        if column >= self.num_cols {
+            return Err(GridError::OutOfBounds(CellRef::new(0, column)));
+        }
+        self.column_widths.set_hidden(column, hidden);
+        Ok(())
+    }
+
+    pub fn set_row_hidden(&mut self, row: u32, hidden: bool) -> Result<(), GridError> {
+        if row >= self.num_rows {
+            return Err(GridError::OutOfBounds(CellRef::new(row, 0)));
+        }
+        self.row_heights.set_hidden(row, hidden);
+        Ok(())
+    }
+
+    // Distance from the left edge of the grid to the left edge of `column`.
+    pub fn column_left(&self, column: u32) -> f64 {
+        self.column_widths.offset(column)
+    }
+
+    pub fn row_top(&self, row: u32) -> f64 {
+        self.row_heights.offset(row)
+    }
+
+    // The visible column under horizontal position `x`, measured from the
+    // left edge of the grid.
+    pub fn column_at(&self, x: f64) -> Option<u32> {
+        self.column_widths.index_at(x)
+    }
+
+    pub fn row_at(&self, y: f64) -> Option<u32> {
+        self.row_heights.index_at(y)
+    }
+
+    pub fn get_width(&self) -> f64 {
+        self.column_widths.total()
+    }
+
+    pub fn get_height(&self) -> f64 {
+        self.row_heights.total()
+    }
+}
+
+// Rows and columns from `from` to `to`.
+fn offset_between(from: CellRef, to: CellRef) -> (i64, i64) {
+    (
+        to.row as i64 - from.row as i64,
+        to.column as i64 - from.column as i64,
+    )
+}
+
+impl EvalContext for Grid {
+    fn value(&self, cell: CellRef) -> CellValue {
+        self.get_value(cell)
+    }
+
+    fn values(&self, area: &Area) -> Vec<CellValue> {
+        self.cells
+            .area(area)
+            .map(|c| c.get_value())
+            .filter(|v| !v.is_empty())
+            .collect()
+    }
+}
@@ -0,0 +1,413 @@
+use crate::cell::{CellObject, CellValue};
+use crate::cell_ref::CellRef;
+use crate::column::{Column, ColumnType};
+use crate::fill::fill;
+use crate::format::NumberFormat;
+use crate::grid::{Grid, GridError};
+use crate::range::Area;
+use crate::style::CellStyle;
+
+// Steps kept for undo. The oldest are forgotten past this.
+pub const MAX_UNDO_STEPS: usize = 100;
+
+// A change to the grid. Changes made through `History::execute` can be
+// undone; applying a command directly cannot.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    SetInput {
+        cell: CellRef,
+        text: String,
+    },
+    SetValue {
+        cell: CellRef,
+        value: CellValue,
+    },
+    SetCellFormat {
+        cell: CellRef,
+        format: Option<NumberFormat>,
+    },
+    SetCellStyle {
+        cell: CellRef,
+        style: CellStyle,
+    },
+    SetColumnType {
+        column: u32,
+        column_type: ColumnType,
+    },
+    SetColumnFormat {
+        column: u32,
+        format: Option<NumberFormat>,
+    },
+    SetColumnWidth {
+        column: u32,
+        width: f64,
+    },
+    SetRowHeight {
+        row: u32,
+        height: f64,
+    },
+    SetColumnHidden {
+        column: u32,
+        hidden: bool,
+    },
+    SetRowHidden {
+        row: u32,
+        hidden: bool,
+    },
+    ClearArea {
+        area: Area,
+    },
+    CopyArea {
+        source: Area,
+        dest: CellRef,
+    },
+    MoveArea {
+        source: Area,
+        dest: CellRef,
+    },
+    InsertRows {
+        at: u32,
+        count: u32,
+    },
+    DeleteRows {
+        at: u32,
+        count: u32,
+    },
+    InsertColumns {
+        at: u32,
+        count: u32,
+    },
+    DeleteColumns {
+        at: u32,
+        count: u32,
+    },
+    // Extends `source` over `dest`, as the fill handle does.
+    Fill {
+        source: Area,
+        dest: Area,
+    },
+}
+
+impl Command {
+    // Makes the change. Returns the cells it could not be applied to, such
+    // as values that do not fit a column's new type.
+    pub fn apply(&self, grid: &mut Grid) -> Result<Vec<CellRef>, GridError> {
+        match self {
+            Command::SetInput { cell, text } => grid.set_input(*cell, text)?,
+            Command::SetValue { cell, value } => grid.set_value(*cell, value.clone())?,
+            Command::SetCellFormat { cell, format } => {
+                grid.set_cell_format(*cell, format.clone())?
+            }
+            Command::SetCellStyle { cell, style } => grid.set_cell_style(*cell, *style)?,
+            Command::SetColumnType {
+                column,
+                column_type,
+            } => return Ok(grid.set_column_type(*column, column_type.clone())?.failed),
+            Command::SetColumnFormat { column, format } => {
+                grid.set_column_format(*column, format.clone())?
+            }
+            Command::SetColumnWidth { column, width } => grid.set_column_width(*column, *width)?,
+            Command::SetRowHeight { row, height } => grid.set_row_height(*row, *height)?,
+            Command::SetColumnHidden { column, hidden } => {
+                grid.set_column_hidden(*column, *hidden)?
+            }
+            Command::SetRowHidden { row, hidden } => grid.set_row_hidden(*row, *hidden)?,
+            Command::ClearArea { area } => grid.clear_area(area),
+            Command::CopyArea { source, dest } => grid.copy_area(source, *dest),
+            Command::MoveArea { source, dest } => grid.move_area(source, *dest),
+            Command::InsertRows { at, count } => grid.insert_rows(*at, *count)?,
+            Command::DeleteRows { at, count } => grid.delete_rows(*at, *count)?,
+            Command::InsertColumns { at, count } => grid.insert_columns(*at, *count)?,
+            Command::DeleteColumns { at, count } => grid.delete_columns(*at, *count)?,
+            Command::Fill { source, dest } => fill(grid, source, dest),
+        }
+        Ok(Vec::new())
+    }
+
+    // What the grid looks like now in every place the command may touch.
+    fn snapshot(&self, grid: &Grid) -> Vec<Change> {
+        let cells = |area: Area| Change::Cells(area, grid.cells().area(&area).cloned().collect());
+        match self {
+            Command::SetInput { cell, .. }
+            | Command::SetValue { cell, .. }
+            | Command::SetCellFormat { cell, .. }
+            | Command::SetCellStyle { cell, .. } => vec![cells(Area::cell(*cell))],
+            Command::SetColumnType { column, .. } => {
+                let mut changes = vec![cells(Area::whole_columns(*column, *column))];
+                changes.extend(grid.get_column(*column).cloned().map(Change::Column));
+                changes
+            }
+            Command::SetColumnFormat { column, .. } => grid
+                .get_column(*column)
+                .cloned()
+                .map(Change::Column)
+                .into_iter()
+                .collect(),
+            Command::SetColumnWidth { column, .. } | Command::SetColumnHidden { column, .. } => {
+                let widths = grid.column_widths();
+                vec![Change::ColumnWidth {
+                    column: *column,
+                    width: widths.stored_size(*column),
+                    hidden: widths.is_hidden(*column),
+                }]
+            }
+            Command::SetRowHeight { row, .. } | Command::SetRowHidden { row, .. } => {
+                let heights = grid.row_heights();
+                vec![Change::RowHeight {
+                    row: *row,
+                    height: heights.stored_size(*row),
+                    hidden: heights.is_hidden(*row),
+                }]
+            }
+            Command::ClearArea { area } | Command::Fill { dest: area, .. } => vec![cells(*area)],
+            Command::CopyArea { source, dest } => grid
+                .landing_area(source, *dest)
+                .map(cells)
+                .into_iter()
+                .collect(),
+            Command::MoveArea { source, dest } => {
+                // Formulas anywhere may be rewritten to follow the cells.
+                let mut changes = formulas(grid);
+                changes.push(cells(*source));
+                changes.extend(grid.landing_area(source, *dest).map(cells));
+                changes
+            }
+            // Inserted rows and columns are empty, so deleting them again
+            // undoes everything, references included.
+            Command::InsertRows { at, count } => vec![Change::Structure(Command::DeleteRows {
+                at: *at,
+                count: *count,
+            })],
+            Command::InsertColumns { at, count } => {
+                vec![Change::Structure(Command::DeleteColumns {
+                    at: *at,
+                    count: *count,
+                })]
+            }
+            // Undoing a delete inserts blank rows or columns, then puts back
+            // what was in them and every formula as it was. The insert comes
+            // last here since changes are restored in reverse.
+            Command::DeleteRows { at, count } => {
+                let mut changes = formulas(grid);
+                let end = at.saturating_add(*count);
+                changes.push(cells(Area::whole_rows(*at, end.saturating_sub(1))));
+                let heights = grid.row_heights();
+                changes.extend(heights.customized(*at..end).into_iter().map(|row| {
+                    Change::RowHeight {
+                        row,
+                        height: heights.stored_size(row),
+                        hidden: heights.is_hidden(row),
+                    }
+                }));
+                changes.push(Change::Structure(Command::InsertRows {
+                    at: *at,
+                    count: *count,
+                }));
+                changes
+            }
+            Command::DeleteColumns { at, count } => {
+                let mut changes = formulas(grid);
+                let end = at.saturating_add(*count);
+                changes.push(cells(Area::whole_columns(*at, end.saturating_sub(1))));
+                let widths = grid.column_widths();
+                changes.extend(widths.customized(*at..end).into_iter().map(|column| {
+                    Change::ColumnWidth {
+                        column,
+                        width: widths.stored_size(column),
+                        hidden: widths.is_hidden(column),
+                    }
+                }));
+                changes.extend(
+                    (*at..end).filter_map(|c| grid.get_column(c).cloned().map(Change::Column)),
+                );
+                changes.push(Change::Structure(Command::InsertColumns {
+                    at: *at,
+                    count: *count,
+                }));
+                changes
+            }
+        }
+    }
+}
+
+// Every formula cell as it is now, for commands that may rewrite any of
+// them.
+fn formulas(grid: &Grid) -> Vec<Change> {
+    grid.cells()
+        .iter()
+        .filter(|c| c.get_formula().is_some())
+        .map(|c| {
+            let at = CellRef::new(c.row_id, c.column_id);
+            Change::Cells(Area::cell(at), vec![c.clone()])
+        })
+        .collect()
+}
+
+// The state of part of the grid before a command ran.
+#[derive(Clone, Debug, PartialEq)]
+enum Change {
+    // Everything stored in an area.
+    Cells(Area, Vec<CellObject>),
+    Column(Column),
+    ColumnWidth {
+        column: u32,
+        width: f64,
+        hidden: bool,
+    },
+    RowHeight {
+        row: u32,
+        height: f64,
+        hidden: bool,
+    },
+    // A command that reverses an insert or delete.
+    Structure(Command),
+}
+
+impl Change {
+    fn restore(self, grid: &mut Grid) {
+        match self {
+            Change::Cells(area, cells) => grid.restore_area(&area, cells),
+            Change::Column(column) => {
+                if let Some(target) = grid.get_column_mut(column.column_id) {
+                    *target = column;
+                }
+            }
+            Change::ColumnWidth {
+                column,
+                width,
+                hidden,
+            } => {
+                let _ = grid.set_column_width(column, width);
+                let _ = grid.set_column_hidden(column, hidden);
+            }
+            Change::RowHeight {
+                row,
+                height,
+                hidden,
+            } => {
+                let _ = grid.set_row_height(row, height);
+                let _ = grid.set_row_hidden(row, hidden);
+            }
+            Change::Structure(command) => {
+                let _ = command.apply(grid);
+            }
+        }
+    }
+}
+
+// Commands undone or redone together, with what they replaced.
+#[derive(Clone, Debug, Default)]
+struct Step {
+    commands: Vec<Command>,
+    changes: Vec<Change>,
+}
+
+// Undo and redo stacks for one grid. Commands executed between
+// `begin_group` and `end_group`, such as every cell of a paste or every
+// move of a resize drag, are undone as one step.
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    done: Vec<Step>,
+    undone: Vec<Step>,
+    group: Option<Step>,
+    depth: u32,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Applies `command` to `grid` and records it for undo. A command that
+    // fails leaves the grid and the history as they were.
+    pub fn execute(
+        &mut self,
+        grid: &mut Grid,
+        command: Command,
+    ) -> Result<Vec<CellRef>, GridError> {
+        let changes = command.snapshot(grid);
+        let skipped = command.apply(grid)?;
+        let step = Step {
+            commands: vec![command],
+            changes,
+        };
+        match &mut self.group {
+            Some(group) => {
+                group.commands.extend(step.commands);
+                group.changes.extend(step.changes);
+            }
+            None => self.push(step),
+        }
+        self.undone.clear();
+        Ok(skipped)
+    }
+
+    // Groups can nest; only the outermost one makes a step.
+    pub fn begin_group(&mut self) {
+        if self.depth == 0 {
+            self.group = Some(Step::default());
+        }
+        self.depth += 1;
+    }
+
+    pub fn end_group(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+        if self.depth == 0 {
+            if let Some(group) = self.group.take().filter(|g| !g.commands.is_empty()) {
+                self.push(group);
+            }
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    // Puts back what the last step replaced. Returns false if there was
+    // nothing to undo.
+    pub fn undo(&mut self, grid: &mut Grid) -> bool {
+        let Some(step) = self.done.pop() else {
+            return false;
+        };
+        for change in step.changes.into_iter().rev() {
+            change.restore(grid);
+        }
+        self.undone.push(Step {
+            commands: step.commands,
+            changes: Vec::new(),
+        });
+        true
+    }
+
+    // Executes the last undone step again.
+    pub fn redo(&mut self, grid: &mut Grid) -> bool {
+        let Some(step) = self.undone.pop() else {
+            return false;
+        };
+        let mut redone = Step::default();
+        for command in step.commands {
+            redone.changes.extend(command.snapshot(grid));
+            // It worked the first time, on the same grid.
+            let _ = command.apply(grid);
+            redone.commands.push(command);
+        }
+        self.done.push(redone);
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+
+    fn push(&mut self, step: Step) {
+        self.done.push(step);
+        if self.done.len() > MAX_UNDO_STEPS {
+            self.done.remove(0);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use crate::cell::CellValue;
+use crate::cell_ref::CellRef;
+use crate::grid::{Grid, DEFAULT_COLUMN_WIDTH};
+use crate::style::{HAlign, Overflow, VAlign};
+use crate::viewport::Viewport;
+
+pub const CELL_PADDING: f64 = 4.0;
+pub const FONT_SIZE: f64 = 14.0;
+const ELLIPSIS: &str = "…";
+
+// Text width in pixels. The canvas implements this with `measureText`;
+// tests can use a fixed-width font.
+pub trait TextMeasure {
+    fn text_width(&self, text: &str) -> f64;
+}
+
+// Where and how to paint one cell's text, in canvas coordinates.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextLayout {
+    pub text: String,
+    // Left end of the text and the vertical middle of its line.
+    pub x: f64,
+    pub y: f64,
+    // x, y, width and height of the region the text is clipped to. Wider
+    // than the cell when text spills into empty neighbours.
+    pub clip: (f64, f64, f64, f64),
+    pub color: Option<String>,
+}
+
+// Lays out the formatted text of `cell`, or `None` if it shows nothing.
+pub fn layout_cell(
+    grid: &Grid,
+    viewport: &Viewport,
+    cell: CellRef,
+    measure: &dyn TextMeasure,
+) -> Option<TextLayout> {
+    let value = grid.get_value(cell);
+    let formatted = grid.display_value(cell);
+    if formatted.text.is_empty() {
+        return None;
+    }
+    let style = grid.cell_style(cell);
+    let (x, y, width, height) = viewport.cell_rect(grid, cell);
+    let available = (width - 2.0 * CELL_PADDING).max(0.0);
+
+    let align = match style.h_align {
+        HAlign::General => match value {
+            _ if value.is_number() => HAlign::Right,
+            CellValue::Bool(_) | CellValue::Error(_) => HAlign::Center,
+            _ => HAlign::Left,
+        },
+        align => align,
+    };
+
+    let mut text = formatted.text;
+    let mut text_width = measure.text_width(&text);
+    let (mut left, mut right) = (x, x + width);
+
+    if text_width > available {
+        if value.is_number() {
+            // A number is never shown cut off, since that would show a
+            // different number.
+            let hash = measure.text_width("#").max(1.0);
+            text = "#".repeat(((available / hash) as usize).max(1));
+            text_width = measure.text_width(&text);
+        } else {
+            match style.overflow {
+                Overflow::Overflow => {
+                    let start = text_x(align, x, width, text_width);
+                    let end = start + text_width + CELL_PADDING;
+                    let mut column = cell.column + 1;
+                    while right < end && is_blank(grid, cell.row, column) {
+                        right += grid.column_width(column);
+                        column += 1;
+                    }
+                    let mut column = cell.column;
+                    while left > start - CELL_PADDING
+                        && column > 0
+                        && is_blank(grid, cell.row, column - 1)
+                    {
+                        column -= 1;
+                        left -= grid.column_width(column);
+                    }
+                }
+                Overflow::Clip => {}
+                Overflow::Ellipsis => {
+                    while !text.is_empty()
+                        && measure.text_width(&format!("{}{}", text, ELLIPSIS)) > available
+                    {
+                        text.pop();
+                    }
+                    text.push_str(ELLIPSIS);
+                    text_width = measure.text_width(&text);
+                }
+            }
+        }
+    }
+
+    let text_y = match style.v_align {
+        VAlign::Top => y + CELL_PADDING + FONT_SIZE / 2.0,
+        VAlign::Middle => y + height / 2.0,
+        VAlign::Bottom => y + height - CELL_PADDING - FONT_SIZE / 2.0,
+    };
+    Some(TextLayout {
+        text,
+        x: text_x(align, x, width, text_width),
+        y: text_y,
+        clip: (left, y, right - left, height),
+        color: formatted.color,
+    })
+}
+
+// Width that shows the widest value in `column` without clipping, or the
+// default width for an empty column.
+pub fn fit_column_width(grid: &Grid, column: u32, measure: &dyn TextMeasure) -> f64 {
+    grid.cells()
+        .column(column)
+        .map(|cell| grid.display_value(CellRef::new(cell.row_id, cell.column_id)))
+        .filter(|formatted| !formatted.text.is_empty())
+        .map(|formatted| (measure.text_width(&formatted.text) + 2.0 * CELL_PADDING).ceil())
+        .reduce(f64::max)
+        .unwrap_or(DEFAULT_COLUMN_WIDTH)
+}
+
+fn text_x(align: HAlign, x: f64, width: f64, text_width: f64) -> f64 {
+    match align {
+        HAlign::Right => x + width - CELL_PADDING - text_width,
+        HAlign::Center => x + (width - text_width) / 2.0,
+        HAlign::General | HAlign::Left => x + CELL_PADDING,
+    }
+}
+
+fn is_blank(grid: &Grid, row: u32, column: u32) -> bool {
+    column < grid.num_cols() && grid.get_value(CellRef::new(row, column)).is_empty()
+}
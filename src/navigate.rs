@@ -0,0 +1,115 @@
+use crate::cell_ref::CellRef;
+use crate::grid::Grid;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+// A keyboard movement of the active cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Motion {
+    // Arrow keys.
+    Step(Direction),
+    // Ctrl+arrow: to the edge of the current block of data, or to the next
+    // cell with data, or to the edge of the sheet.
+    Jump(Direction),
+    // PageUp/PageDown, by the given number of rows or columns.
+    Page(Direction, u32),
+    // Home and End: the start of the row, and the last cell with data in it.
+    RowStart,
+    RowEnd,
+    // Ctrl+Home and Ctrl+End: A1, and the bottom-right corner of the data.
+    SheetStart,
+    SheetEnd,
+}
+
+// Where `motion` takes the active cell from `from`. Hidden rows and
+// columns are skipped over.
+pub fn navigate(grid: &Grid, from: CellRef, motion: Motion) -> CellRef {
+    match motion {
+        Motion::Step(direction) => step(grid, from, direction).unwrap_or(from),
+        Motion::Jump(direction) => jump(grid, from, direction),
+        Motion::Page(direction, count) => {
+            let mut at = from;
+            for _ in 0..count.max(1) {
+                match step(grid, at, direction) {
+                    Some(next) => at = next,
+                    None => break,
+                }
+            }
+            at
+        }
+        Motion::RowStart => visible(grid, CellRef::new(from.row, 0)),
+        Motion::RowEnd => {
+            let last = grid
+                .cells()
+                .row(from.row)
+                .filter(|c| !c.get_value().is_empty())
+                .map(|c| c.column_id)
+                .last();
+            last.map_or(from, |column| CellRef::new(from.row, column))
+        }
+        Motion::SheetStart => visible(grid, CellRef::new(0, 0)),
+        Motion::SheetEnd => grid.used_range().map_or(CellRef::new(0, 0), |used| {
+            CellRef::new(used.bottom(), used.right())
+        }),
+    }
+}
+
+// The neighbour of `from` in `direction`, skipping hidden rows and columns.
+fn step(grid: &Grid, from: CellRef, direction: Direction) -> Option<CellRef> {
+    let mut at = from;
+    loop {
+        at = match direction {
+            Direction::Up => CellRef::new(at.row.checked_sub(1)?, at.column),
+            Direction::Down if at.row + 1 < grid.num_rows() => CellRef::new(at.row + 1, at.column),
+            Direction::Left => CellRef::new(at.row, at.column.checked_sub(1)?),
+            Direction::Right if at.column + 1 < grid.num_cols() => {
+                CellRef::new(at.row, at.column + 1)
+            }
+            _ => return None,
+        };
+        if grid.row_height(at.row) > 0.0 && grid.column_width(at.column) > 0.0 {
+            return Some(at);
+        }
+    }
+}
+
+fn jump(grid: &Grid, from: CellRef, direction: Direction) -> CellRef {
+    let filled = |cell: CellRef| !grid.get_value(cell).is_empty();
+    let Some(mut next) = step(grid, from, direction) else {
+        return from;
+    };
+    if filled(from) && filled(next) {
+        // Inside a block: stop on its last filled cell.
+        while let Some(after) = step(grid, next, direction).filter(|c| filled(*c)) {
+            next = after;
+        }
+        return next;
+    }
+    // Otherwise: the next filled cell, or the edge of the sheet.
+    while !filled(next) {
+        match step(grid, next, direction) {
+            Some(after) => next = after,
+            None => break,
+        }
+    }
+    next
+}
+
+// `cell`, or the nearest visible cell to it when its row or column is
+// hidden.
+fn visible(grid: &Grid, cell: CellRef) -> CellRef {
+    CellRef::new(
+        grid.row_heights()
+            .visible_near(cell.row)
+            .unwrap_or(cell.row),
+        grid.column_widths()
+            .visible_near(cell.column)
+            .unwrap_or(cell.column),
+    )
+}
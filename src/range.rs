@@ -0,0 +1,529 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::cell_ref::{column_index, column_name, CellRef, CellRefError, MAX_COLUMNS, MAX_ROWS};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IterOrder {
+    RowMajor,
+    ColumnMajor,
+}
+
+// A rectangular block of cells. `start` is always the top-left corner and
+// `end` the bottom-right one. Whole columns (`B:B`) and whole rows (`3:3`)
+// are areas that span the full height or width of the sheet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Area {
+    pub start: CellRef,
+    pub end: CellRef,
+}
+
+impl Area {
+    // Builds the area spanned by two corners given in any order.
+    pub fn new(a: CellRef, b: CellRef) -> Self {
+        let (top, bottom) = if a.row <= b.row { (a, b) } else { (b, a) };
+        let (left, right) = if a.column <= b.column { (a, b) } else { (b, a) };
+
+        Self {
+            start: CellRef {
+                row: top.row,
+                row_absolute: top.row_absolute,
+                column: left.column,
+                column_absolute: left.column_absolute,
+            },
+            end: CellRef {
+                row: bottom.row,
+                row_absolute: bottom.row_absolute,
+                column: right.column,
+                column_absolute: right.column_absolute,
+            },
+        }
+    }
+
+    pub fn cell(cell: CellRef) -> Self {
+        Self::new(cell, cell)
+    }
+
+    pub fn from_bounds(top: u32, left: u32, bottom: u32, right: u32) -> Self {
+        Self::new(CellRef::new(top, left), CellRef::new(bottom, right))
+    }
+
+    pub fn whole_columns(first: u32, last: u32) -> Self {
+        Self::from_bounds(0, first, MAX_ROWS - 1, last)
+    }
+
+    pub fn whole_rows(first: u32, last: u32) -> Self {
+        Self::from_bounds(first, 0, last, MAX_COLUMNS - 1)
+    }
+
+    // The area moved `rows` down and `columns` across, or `None` if any of
+    // it would leave the sheet. Whole columns still span every row and
+    // whole rows every column.
+    pub fn offset(&self, rows: i64, columns: i64) -> Option<Area> {
+        self.map_corners(
+            |cell, rows, columns| cell.offset(rows, columns),
+            rows,
+            columns,
+        )
+    }
+
+    // The area as seen from a formula copied `rows` down and `columns`
+    // across: only the relative corners move.
+    pub fn shift_relative(&self, rows: i64, columns: i64) -> Option<Area> {
+        self.map_corners(
+            |cell, rows, columns| cell.shift_relative(rows, columns),
+            rows,
+            columns,
+        )
+    }
+
+    fn map_corners(
+        &self,
+        f: impl Fn(CellRef, i64, i64) -> Option<CellRef>,
+        rows: i64,
+        columns: i64,
+    ) -> Option<Area> {
+        let rows = if self.is_whole_columns() { 0 } else { rows };
+        let columns = if self.is_whole_rows() { 0 } else { columns };
+        Some(Area::new(
+            f(self.start, rows, columns)?,
+            f(self.end, rows, columns)?,
+        ))
+    }
+
+    // The area after `count` rows are inserted before row `at`. Parts at or
+    // below `at` move down, so an area spanning `at` grows; anything pushed
+    // off the sheet is cut off, and `None` if all of it is.
+    pub fn insert_rows(&self, at: u32, count: u32) -> Option<Area> {
+        if self.is_whole_columns() {
+            return Some(*self);
+        }
+        let (top, bottom) = insert_span(self.top(), self.bottom(), at, count, MAX_ROWS)?;
+        Some(self.with_rows(top, bottom))
+    }
+
+    // The area after rows `at..at + count` are deleted: the rest closes up,
+    // and `None` if every row of it was deleted.
+    pub fn delete_rows(&self, at: u32, count: u32) -> Option<Area> {
+        if self.is_whole_columns() {
+            return Some(*self);
+        }
+        let (top, bottom) = delete_span(self.top(), self.bottom(), at, count)?;
+        Some(self.with_rows(top, bottom))
+    }
+
+    pub fn insert_columns(&self, at: u32, count: u32) -> Option<Area> {
+        if self.is_whole_rows() {
+            return Some(*self);
+        }
+        let (left, right) = insert_span(self.left(), self.right(), at, count, MAX_COLUMNS)?;
+        Some(self.with_columns(left, right))
+    }
+
+    pub fn delete_columns(&self, at: u32, count: u32) -> Option<Area> {
+        if self.is_whole_rows() {
+            return Some(*self);
+        }
+        let (left, right) = delete_span(self.left(), self.right(), at, count)?;
+        Some(self.with_columns(left, right))
+    }
+
+    // The same corners, `$` markers included, on other rows.
+    fn with_rows(&self, top: u32, bottom: u32) -> Area {
+        let mut area = *self;
+        area.start.row = top;
+        area.end.row = bottom;
+        area
+    }
+
+    fn with_columns(&self, left: u32, right: u32) -> Area {
+        let mut area = *self;
+        area.start.column = left;
+        area.end.column = right;
+        area
+    }
+
+    pub fn top(&self) -> u32 {
+        self.start.row
+    }
+
+    pub fn left(&self) -> u32 {
+        self.start.column
+    }
+
+    pub fn bottom(&self) -> u32 {
+        self.end.row
+    }
+
+    pub fn right(&self) -> u32 {
+        self.end.column
+    }
+
+    pub fn num_rows(&self) -> u32 {
+        self.bottom() - self.top() + 1
+    }
+
+    pub fn num_cols(&self) -> u32 {
+        self.right() - self.left() + 1
+    }
+
+    pub fn cell_count(&self) -> u64 {
+        self.num_rows() as u64 * self.num_cols() as u64
+    }
+
+    pub fn is_whole_columns(&self) -> bool {
+        self.top() == 0 && self.bottom() == MAX_ROWS - 1
+    }
+
+    pub fn is_whole_rows(&self) -> bool {
+        self.left() == 0 && self.right() == MAX_COLUMNS - 1
+    }
+
+    pub fn contains(&self, cell: CellRef) -> bool {
+        (self.top()..=self.bottom()).contains(&cell.row)
+            && (self.left()..=self.right()).contains(&cell.column)
+    }
+
+    pub fn contains_area(&self, other: &Area) -> bool {
+        self.contains(other.start) && self.contains(other.end)
+    }
+
+    pub fn intersect(&self, other: &Area) -> Option<Area> {
+        let top = self.top().max(other.top());
+        let left = self.left().max(other.left());
+        let bottom = self.bottom().min(other.bottom());
+        let right = self.right().min(other.right());
+
+        if top > bottom || left > right {
+            return None;
+        }
+        Some(Area::from_bounds(top, left, bottom, right))
+    }
+
+    pub fn bounding(&self, other: &Area) -> Area {
+        Area::from_bounds(
+            self.top().min(other.top()),
+            self.left().min(other.left()),
+            self.bottom().max(other.bottom()),
+            self.right().max(other.right()),
+        )
+    }
+
+    pub fn iter(&self, order: IterOrder) -> AreaIter {
+        AreaIter {
+            area: *self,
+            order,
+            next: Some(CellRef::new(self.top(), self.left())),
+        }
+    }
+}
+
+pub struct AreaIter {
+    area: Area,
+    order: IterOrder,
+    next: Option<CellRef>,
+}
+
+impl Iterator for AreaIter {
+    type Item = CellRef;
+
+    fn next(&mut self) -> Option<CellRef> {
+        let current = self.next?;
+        let Area { start, end } = self.area;
+
+        self.next = match self.order {
+            IterOrder::RowMajor if current.column < end.column => {
+                Some(CellRef::new(current.row, current.column + 1))
+            }
+            IterOrder::RowMajor if current.row < end.row => {
+                Some(CellRef::new(current.row + 1, start.column))
+            }
+            IterOrder::ColumnMajor if current.row < end.row => {
+                Some(CellRef::new(current.row + 1, current.column))
+            }
+            IterOrder::ColumnMajor if current.column < end.column => {
+                Some(CellRef::new(start.row, current.column + 1))
+            }
+            _ => None,
+        };
+        Some(current)
+    }
+}
+
+// `first..=last` along one side after inserting `count` before `at`, on a
+// sheet `limit` long.
+fn insert_span(first: u32, last: u32, at: u32, count: u32, limit: u32) -> Option<(u32, u32)> {
+    let shift = |i: u32| {
+        if i >= at {
+            i as u64 + count as u64
+        } else {
+            i as u64
+        }
+    };
+    let (first, last) = (shift(first), shift(last));
+    (first < limit as u64).then(|| (first as u32, last.min(limit as u64 - 1) as u32))
+}
+
+// `first..=last` along one side after deleting `at..at + count`. A deleted
+// first entry becomes the one after the gap and a deleted last entry the
+// one before it.
+fn delete_span(first: u32, last: u32, at: u32, count: u32) -> Option<(u32, u32)> {
+    let (first, last, at) = (first as i64, last as i64, at as i64);
+    let end = at + count as i64;
+    let first = if first < at {
+        first
+    } else if first >= end {
+        first - count as i64
+    } else {
+        at
+    };
+    let last = if last < at {
+        last
+    } else if last >= end {
+        last - count as i64
+    } else {
+        at - 1
+    };
+    (first <= last).then_some((first as u32, last as u32))
+}
+
+impl fmt::Display for Area {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dollar = |absolute: bool| if absolute { "$" } else { "" };
+
+        if self.is_whole_columns() && !self.is_whole_rows() {
+            write!(
+                f,
+                "{}{}:{}{}",
+                dollar(self.start.column_absolute),
+                column_name(self.left()),
+                dollar(self.end.column_absolute),
+                column_name(self.right())
+            )
+        } else if self.is_whole_rows() && !self.is_whole_columns() {
+            write!(
+                f,
+                "{}{}:{}{}",
+                dollar(self.start.row_absolute),
+                self.top() + 1,
+                dollar(self.end.row_absolute),
+                self.bottom() + 1
+            )
+        } else if self.start == self.end {
+            write!(f, "{}", self.start)
+        } else {
+            write!(f, "{}:{}", self.start, self.end)
+        }
+    }
+}
+
+impl FromStr for Area {
+    type Err = CellRefError;
+
+    // Accepts `A1`, `A1:C10`, `B:B` and `3:3`, with optional `$` markers.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(CellRefError::Empty);
+        }
+        let (first, second) = match s.split_once(':') {
+            Some((a, b)) => (a.trim(), b.trim()),
+            None => return Ok(Area::cell(s.parse()?)),
+        };
+
+        if let (Some(a), Some(b)) = (parse_line(first, false), parse_line(second, false)) {
+            let mut area = Area::whole_columns(a.0.min(b.0), a.0.max(b.0));
+            area.start.column_absolute = a.1;
+            area.end.column_absolute = b.1;
+            return Ok(area);
+        }
+        if let (Some(a), Some(b)) = (parse_line(first, true), parse_line(second, true)) {
+            if a.0 == 0 || b.0 == 0 {
+                return Err(CellRefError::RowOutOfRange(0));
+            }
+            if a.0.max(b.0) > MAX_ROWS {
+                return Err(CellRefError::RowOutOfRange(a.0.max(b.0) as i64));
+            }
+            let mut area = Area::whole_rows(a.0.min(b.0) - 1, a.0.max(b.0) - 1);
+            area.start.row_absolute = a.1;
+            area.end.row_absolute = b.1;
+            return Ok(area);
+        }
+
+        Ok(Area::new(first.parse()?, second.parse()?))
+    }
+}
+
+// Parses one side of `B:B` (letters) or `3:3` (digits), returning the
+// column index or one-based row number and whether it carried a `$`.
+fn parse_line(s: &str, digits: bool) -> Option<(u32, bool)> {
+    let (absolute, s) = match s.strip_prefix('$') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    if digits {
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        s.parse().ok().map(|row| (row, absolute))
+    } else {
+        column_index(s).map(|column| (column, absolute))
+    }
+}
+
+// The parts of `rect` outside `hole`: up to four bands above, below, left
+// and right of it.
+fn subtract(rect: &Area, hole: &Area) -> Vec<Area> {
+    let Some(overlap) = rect.intersect(hole) else {
+        return vec![*rect];
+    };
+    let mut parts = Vec::new();
+    if rect.top() < overlap.top() {
+        parts.push(Area::from_bounds(
+            rect.top(),
+            rect.left(),
+            overlap.top() - 1,
+            rect.right(),
+        ));
+    }
+    if overlap.bottom() < rect.bottom() {
+        parts.push(Area::from_bounds(
+            overlap.bottom() + 1,
+            rect.left(),
+            rect.bottom(),
+            rect.right(),
+        ));
+    }
+    if rect.left() < overlap.left() {
+        parts.push(Area::from_bounds(
+            overlap.top(),
+            rect.left(),
+            overlap.bottom(),
+            overlap.left() - 1,
+        ));
+    }
+    if overlap.right() < rect.right() {
+        parts.push(Area::from_bounds(
+            overlap.top(),
+            overlap.right() + 1,
+            overlap.bottom(),
+            rect.right(),
+        ));
+    }
+    parts
+}
+
+// One or more areas treated as a single selection, e.g. `A1:B2,D4`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CellRange {
+    areas: Vec<Area>,
+}
+
+impl CellRange {
+    pub fn new(areas: Vec<Area>) -> Self {
+        Self { areas }
+    }
+
+    pub fn areas(&self) -> &[Area] {
+        &self.areas
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.areas.is_empty()
+    }
+
+    pub fn push(&mut self, area: Area) {
+        self.areas.push(area);
+    }
+
+    pub fn contains(&self, cell: CellRef) -> bool {
+        self.areas.iter().any(|area| area.contains(cell))
+    }
+
+    // Cuts each area out of `other` in turn; it is covered if nothing is
+    // left. This works on bounds, so whole rows and columns cost no more
+    // than single cells.
+    pub fn contains_area(&self, other: &Area) -> bool {
+        let mut uncovered = vec![*other];
+        for area in &self.areas {
+            uncovered = uncovered
+                .iter()
+                .flat_map(|rect| subtract(rect, area))
+                .collect();
+            if uncovered.is_empty() {
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn bounding_box(&self) -> Option<Area> {
+        self.areas
+            .iter()
+            .copied()
+            .reduce(|acc, area| acc.bounding(&area))
+    }
+
+    pub fn intersect(&self, other: &CellRange) -> CellRange {
+        let areas = self
+            .areas
+            .iter()
+            .flat_map(|a| other.areas.iter().filter_map(move |b| a.intersect(b)))
+            .collect();
+        CellRange::new(areas)
+    }
+
+    // Combines both selections. Areas already covered by another area are
+    // dropped; partially overlapping areas are kept and iteration skips the
+    // cells they share.
+    pub fn union(&self, other: &CellRange) -> CellRange {
+        let mut result = CellRange::default();
+        for area in self.areas.iter().chain(other.areas.iter()) {
+            if result.areas.iter().any(|a| a.contains_area(area)) {
+                continue;
+            }
+            result.areas.retain(|a| !area.contains_area(a));
+            result.areas.push(*area);
+        }
+        result
+    }
+
+    // Visits every cell once, area by area.
+    pub fn iter(&self, order: IterOrder) -> impl Iterator<Item = CellRef> + '_ {
+        self.areas.iter().enumerate().flat_map(move |(i, area)| {
+            let earlier = &self.areas[..i];
+            area.iter(order)
+                .filter(move |cell| !earlier.iter().any(|a| a.contains(*cell)))
+        })
+    }
+}
+
+impl From<Area> for CellRange {
+    fn from(area: Area) -> Self {
+        Self::new(vec![area])
+    }
+}
+
+impl fmt::Display for CellRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, area) in self.areas.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", area)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for CellRange {
+    type Err = CellRefError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let areas = s
+            .split(',')
+            .map(str::parse)
+            .collect::<Result<Vec<Area>, _>>()?;
+        Ok(Self::new(areas))
+    }
+}
@@ -0,0 +1,258 @@
+use web_sys::CanvasRenderingContext2d;
+
+use crate::cell_ref::{column_name, CellRef};
+use crate::grid::Grid;
+use crate::layout::{layout_cell, TextLayout, TextMeasure};
+use crate::range::Area;
+use crate::selection::Selection;
+use crate::viewport::Viewport;
+
+const FONT: &str = "14px sans-serif";
+const TEXT_COLOR: &str = "black";
+const BACKGROUND: &str = "white";
+const HEADER_BACKGROUND: &str = "#f3f3f3";
+const HEADER_SELECTED: &str = "#d3e3fd";
+const HEADER_FONT: &str = "12px sans-serif";
+const GRID_LINE: &str = "#c0c0c0";
+const SELECTION: &str = "#1a73e8";
+const SELECTION_FILL: &str = "rgba(26, 115, 232, 0.1)";
+const FILL_PREVIEW: &str = "#5f6368";
+const CHECKBOX_SIZE: f64 = 14.0;
+const CHECKBOX_BORDER: &str = "#5f6368";
+
+// Paints a `Grid` onto a canvas. The grid itself knows nothing about the
+// canvas, so the same model can be rendered (or tested) without a browser.
+pub struct Renderer<'a> {
+    ctx: &'a CanvasRenderingContext2d,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(ctx: &'a CanvasRenderingContext2d) -> Self {
+        Self { ctx }
+    }
+
+    // Clears the canvas and paints only the cells inside `viewport`, then
+    // the header strips on top. `fill` is the area a fill handle drag
+    // would cover.
+    pub fn draw(
+        &self,
+        grid: &Grid,
+        viewport: &Viewport,
+        selection: &Selection,
+        fill: Option<&Area>,
+    ) {
+        self.ctx
+            .clear_rect(0.0, 0.0, viewport.width(), viewport.height());
+
+        let rows = viewport.visible_rows(grid);
+        for row in rows.clone() {
+            for column in viewport.visible_columns(grid) {
+                let at = CellRef::new(row, column);
+                let (x, y, width, height) = viewport.cell_rect(grid, at);
+                self.draw_border(x, y, width, height);
+                if let Some(checked) = grid.checkbox(at) {
+                    self.draw_checkbox(x, y, width, height, checked);
+                }
+            }
+        }
+
+        self.ctx.set_font(FONT);
+        self.ctx.set_text_baseline("middle");
+        // Every stored cell in a visible row, since text from a cell scrolled
+        // out of view can still spill into the viewport.
+        for row in rows {
+            for cell in grid.cells().row(row) {
+                let at = CellRef::new(cell.row_id, cell.column_id);
+                if grid.checkbox(at).is_some() {
+                    continue;
+                }
+                if let Some(layout) = layout_cell(grid, viewport, at, self) {
+                    let (x, _, width, _) = layout.clip;
+                    if x < viewport.width() && x + width > 0.0 {
+                        self.draw_text(&layout, viewport.cell_rect(grid, at).2);
+                    }
+                }
+            }
+        }
+
+        self.draw_selection(grid, viewport, selection, fill);
+        self.draw_headers(grid, viewport, selection);
+    }
+
+    // Translucent areas with an outline, and a heavier box around the
+    // active cell. A single area gets a fill handle at its corner.
+    fn draw_selection(
+        &self,
+        grid: &Grid,
+        viewport: &Viewport,
+        selection: &Selection,
+        fill: Option<&Area>,
+    ) {
+        self.ctx.save();
+        self.ctx.set_stroke_style_str(SELECTION);
+        self.ctx.set_fill_style_str(SELECTION_FILL);
+        self.ctx.set_line_width(1.0);
+        for area in selection.areas() {
+            let (x, y, _, _) = viewport.cell_rect(grid, area.start);
+            let (right, bottom, width, height) = viewport.cell_rect(grid, area.end);
+            let (width, height) = (right + width - x, bottom + height - y);
+            if area.cell_count() > 1 {
+                self.ctx.fill_rect(x, y, width, height);
+            }
+            self.ctx.stroke_rect(x, y, width, height);
+        }
+        let (x, y, width, height) = viewport.cell_rect(grid, selection.active());
+        self.ctx.set_line_width(2.0);
+        self.ctx
+            .stroke_rect(x + 1.0, y + 1.0, width - 2.0, height - 2.0);
+
+        if let [area] = selection.areas() {
+            let (x, y, width, height) = viewport.fill_handle_rect(grid, area);
+            self.ctx.set_fill_style_str(SELECTION);
+            self.ctx.set_stroke_style_str(BACKGROUND);
+            self.ctx.set_line_width(1.0);
+            self.ctx.fill_rect(x, y, width, height);
+            self.ctx
+                .stroke_rect(x - 0.5, y - 0.5, width + 1.0, height + 1.0);
+        }
+        if let Some(area) = fill {
+            let (x, y, _, _) = viewport.cell_rect(grid, area.start);
+            let (right, bottom, width, height) = viewport.cell_rect(grid, area.end);
+            self.ctx.set_stroke_style_str(FILL_PREVIEW);
+            self.ctx.set_line_width(1.0);
+            let dash = js_sys::Array::of2(&4.0.into(), &3.0.into());
+            let _ = self.ctx.set_line_dash(&dash);
+            self.ctx
+                .stroke_rect(x, y, right + width - x, bottom + height - y);
+        }
+        self.ctx.restore();
+    }
+
+    // The column letters along the top and row numbers down the left. They
+    // are painted last so the scrolled body never shows through them.
+    fn draw_headers(&self, grid: &Grid, viewport: &Viewport, selection: &Selection) {
+        let (header_w, header_h) = (viewport.header_width(), viewport.header_height());
+        if header_w <= 0.0 && header_h <= 0.0 {
+            return;
+        }
+        let areas = selection.areas();
+
+        self.ctx.save();
+        self.ctx.set_font(HEADER_FONT);
+        self.ctx.set_text_align("center");
+        self.ctx.set_text_baseline("middle");
+        self.ctx.set_stroke_style_str(GRID_LINE);
+
+        self.ctx.set_fill_style_str(HEADER_BACKGROUND);
+        self.ctx.fill_rect(0.0, 0.0, viewport.width(), header_h);
+        for column in viewport.visible_columns(grid) {
+            let x = viewport.column_x(grid, column);
+            let width = grid.column_width(column);
+            if width <= 0.0 {
+                continue;
+            }
+            let selected = areas
+                .iter()
+                .any(|a| (a.left()..=a.right()).contains(&column));
+            self.draw_header_cell(x, 0.0, width, header_h, &column_name(column), selected);
+        }
+
+        self.ctx.set_fill_style_str(HEADER_BACKGROUND);
+        self.ctx.fill_rect(0.0, 0.0, header_w, viewport.height());
+        for row in viewport.visible_rows(grid) {
+            let y = viewport.row_y(grid, row);
+            let height = grid.row_height(row);
+            if height <= 0.0 {
+                continue;
+            }
+            let selected = areas.iter().any(|a| (a.top()..=a.bottom()).contains(&row));
+            let label = (row + 1).to_string();
+            self.draw_header_cell(0.0, y, header_w, height, &label, selected);
+        }
+
+        // The corner square hides headers scrolled under it.
+        self.ctx.set_fill_style_str(HEADER_BACKGROUND);
+        self.ctx.fill_rect(0.0, 0.0, header_w, header_h);
+        self.ctx.stroke_rect(0.0, 0.0, header_w, header_h);
+        self.ctx.restore();
+    }
+
+    fn draw_header_cell(
+        &self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        label: &str,
+        selected: bool,
+    ) {
+        self.ctx.set_fill_style_str(if selected {
+            HEADER_SELECTED
+        } else {
+            HEADER_BACKGROUND
+        });
+        self.ctx.fill_rect(x, y, width, height);
+        self.ctx.stroke_rect(x, y, width, height);
+        self.ctx.set_fill_style_str(TEXT_COLOR);
+        let _ = self.ctx.fill_text(label, x + width / 2.0, y + height / 2.0);
+    }
+
+    fn draw_border(&self, x: f64, y: f64, width: f64, height: f64) {
+        self.ctx.begin_path();
+        self.ctx.rect(x, y, width, height);
+        self.ctx.stroke();
+    }
+
+    // A box centred in the cell, filled with a tick when `checked`.
+    fn draw_checkbox(&self, x: f64, y: f64, width: f64, height: f64, checked: bool) {
+        let size = CHECKBOX_SIZE.min(width - 4.0).min(height - 4.0);
+        if size <= 0.0 {
+            return;
+        }
+        let (left, top) = (x + (width - size) / 2.0, y + (height - size) / 2.0);
+        self.ctx.save();
+        self.ctx.set_line_width(1.5);
+        if checked {
+            self.ctx.set_fill_style_str(SELECTION);
+            self.ctx.fill_rect(left, top, size, size);
+            self.ctx.set_stroke_style_str(BACKGROUND);
+            self.ctx.begin_path();
+            self.ctx.move_to(left + size * 0.2, top + size * 0.5);
+            self.ctx.line_to(left + size * 0.42, top + size * 0.72);
+            self.ctx.line_to(left + size * 0.8, top + size * 0.28);
+            self.ctx.stroke();
+        } else {
+            self.ctx.set_stroke_style_str(CHECKBOX_BORDER);
+            self.ctx.stroke_rect(left, top, size, size);
+        }
+        self.ctx.restore();
+    }
+
+    fn draw_text(&self, layout: &TextLayout, cell_width: f64) {
+        let (x, y, width, height) = layout.clip;
+        self.ctx.save();
+        // Text spilling into empty neighbours hides the borders between them.
+        if width > cell_width {
+            self.ctx.set_fill_style_str(BACKGROUND);
+            self.ctx
+                .fill_rect(x + 1.0, y + 1.0, width - 2.0, height - 2.0);
+        }
+        self.ctx.begin_path();
+        self.ctx.rect(x, y, width, height);
+        self.ctx.clip();
+        let color = layout.color.as_deref().unwrap_or(TEXT_COLOR);
+        self.ctx.set_fill_style_str(&color.to_ascii_lowercase());
+        let _ = self.ctx.fill_text(&layout.text, layout.x, layout.y);
+        self.ctx.restore();
+    }
+}
+
+impl TextMeasure for Renderer<'_> {
+    fn text_width(&self, text: &str) -> f64 {
+        self.ctx.set_font(FONT);
+        self.ctx
+            .measure_text(text)
+            .map(|metrics| metrics.width())
+            .unwrap_or(0.0)
+    }
+}
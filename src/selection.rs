@@ -0,0 +1,176 @@
+use crate::cell_ref::CellRef;
+use crate::navigate::Direction;
+use crate::range::{Area, CellRange};
+
+// What the area being extended is made of: a block of cells, or whole
+// columns or rows picked from the headers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Span {
+    Cells,
+    Columns,
+    Rows,
+}
+
+// The selected areas and the active cell. The last area is the one that
+// shift-click, dragging and shift+arrows extend; it spans from `anchor` to
+// `focus`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Selection {
+    areas: Vec<Area>,
+    active: CellRef,
+    anchor: CellRef,
+    focus: CellRef,
+    span: Span,
+}
+
+impl Selection {
+    pub fn new(cell: CellRef) -> Self {
+        Self {
+            areas: vec![Area::cell(cell)],
+            active: cell,
+            anchor: cell,
+            focus: cell,
+            span: Span::Cells,
+        }
+    }
+
+    pub fn areas(&self) -> &[Area] {
+        &self.areas
+    }
+
+    // The cell that receives input.
+    pub fn active(&self) -> CellRef {
+        self.active
+    }
+
+    // The fixed corner of the area being extended.
+    pub fn anchor(&self) -> CellRef {
+        self.anchor
+    }
+
+    // The moving corner of the area being extended.
+    pub fn focus(&self) -> CellRef {
+        self.focus
+    }
+
+    pub fn current_area(&self) -> Area {
+        *self.areas.last().unwrap()
+    }
+
+    pub fn contains(&self, cell: CellRef) -> bool {
+        self.areas.iter().any(|a| a.contains(cell))
+    }
+
+    pub fn to_range(&self) -> CellRange {
+        CellRange::new(self.areas.clone())
+    }
+
+    // A plain click: just this cell.
+    pub fn select_cell(&mut self, cell: CellRef) {
+        *self = Self::new(cell);
+    }
+
+    // Shift-click or drag: the current area now runs from the anchor to
+    // `cell`. The active cell stays where it is.
+    pub fn extend_to(&mut self, cell: CellRef) {
+        let area = match self.span {
+            Span::Cells => Area::new(self.anchor, cell),
+            Span::Columns => Area::whole_columns(
+                self.anchor.column.min(cell.column),
+                self.anchor.column.max(cell.column),
+            ),
+            Span::Rows => {
+                Area::whole_rows(self.anchor.row.min(cell.row), self.anchor.row.max(cell.row))
+            }
+        };
+        *self.areas.last_mut().unwrap() = area;
+        self.focus = cell;
+    }
+
+    // Keyboard movement: either a fresh single cell or an extension of the
+    // current area.
+    pub fn move_to(&mut self, cell: CellRef, extend: bool) {
+        if extend {
+            self.extend_to(cell);
+        } else {
+            self.select_cell(cell);
+        }
+    }
+
+    // Tab and Enter inside a multi-cell area move the active cell through
+    // it, wrapping at the edges, and leave the selection alone. Returns
+    // false when the area is a single cell.
+    pub fn advance(&mut self, direction: Direction) -> bool {
+        let area = self.current_area();
+        if area.cell_count() <= 1 {
+            return false;
+        }
+        let CellRef { row, column, .. } = self.active;
+        let (row, column) = match direction {
+            Direction::Right if column < area.right() => (row, column + 1),
+            Direction::Right if row < area.bottom() => (row + 1, area.left()),
+            Direction::Right => (area.top(), area.left()),
+            Direction::Left if column > area.left() => (row, column - 1),
+            Direction::Left if row > area.top() => (row - 1, area.right()),
+            Direction::Left => (area.bottom(), area.right()),
+            Direction::Down if row < area.bottom() => (row + 1, column),
+            Direction::Down if column < area.right() => (area.top(), column + 1),
+            Direction::Down => (area.top(), area.left()),
+            Direction::Up if row > area.top() => (row - 1, column),
+            Direction::Up if column > area.left() => (area.bottom(), column - 1),
+            Direction::Up => (area.bottom(), area.right()),
+        };
+        self.active = CellRef::new(row, column);
+        true
+    }
+
+    // Ctrl/cmd-click: a new single-cell area alongside the existing ones.
+    pub fn add_cell(&mut self, cell: CellRef) {
+        self.push(Area::cell(cell), cell, Span::Cells);
+    }
+
+    // A column header click. With `add`, the columns join the existing
+    // selection instead of replacing it.
+    pub fn select_columns(&mut self, first: u32, last: u32, add: bool) {
+        let cell = CellRef::new(0, first);
+        if !add {
+            self.areas.clear();
+        }
+        self.push(Area::whole_columns(first, last), cell, Span::Columns);
+    }
+
+    pub fn select_rows(&mut self, first: u32, last: u32, add: bool) {
+        let cell = CellRef::new(first, 0);
+        if !add {
+            self.areas.clear();
+        }
+        self.push(Area::whole_rows(first, last), cell, Span::Rows);
+    }
+
+    // Replaces the selection with `range`, the first cell of its first area
+    // becoming active.
+    pub fn select_range(&mut self, range: &CellRange) {
+        let Some(first) = range.areas().first() else {
+            return;
+        };
+        self.areas = range.areas().to_vec();
+        self.active = first.start;
+        self.anchor = self.current_area().start;
+        self.focus = self.current_area().end;
+        self.span = Span::Cells;
+    }
+
+    fn push(&mut self, area: Area, cell: CellRef, span: Span) {
+        self.areas.push(area);
+        self.active = cell;
+        self.anchor = cell;
+        self.focus = cell;
+        self.span = span;
+    }
+}
+
+impl Default for Selection {
+    fn default() -> Self {
+        Self::new(CellRef::new(0, 0))
+    }
+}
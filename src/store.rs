@@ -0,0 +1,75 @@
+use std::collections::BTreeMap;
+
+use crate::cell::CellObject;
+use crate::range::Area;
+
+// Sparse cell storage keyed by (row, column). Only cells that hold content
+// are stored, so memory follows the amount of data rather than the logical
+// size of the sheet. The map is ordered, which makes row-major scans and
+// row slices cheap.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CellStore {
+    cells: BTreeMap<(u32, u32), CellObject>,
+}
+
+impl CellStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn get(&self, row: u32, column: u32) -> Option<&CellObject> {
+        self.cells.get(&(row, column))
+    }
+
+    pub fn get_mut(&mut self, row: u32, column: u32) -> Option<&mut CellObject> {
+        self.cells.get_mut(&(row, column))
+    }
+
+    // Stores `cell` at its own (row_id, column_id), replacing whatever was
+    // there. Empty cells are not kept; inserting one clears the slot.
+    pub fn insert(&mut self, cell: CellObject) -> Option<CellObject> {
+        let key = (cell.row_id, cell.column_id);
+        if cell.is_empty() {
+            self.cells.remove(&key)
+        } else {
+            self.cells.insert(key, cell)
+        }
+    }
+
+    pub fn remove(&mut self, row: u32, column: u32) -> Option<CellObject> {
+        self.cells.remove(&(row, column))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CellObject> {
+        self.cells.values()
+    }
+
+    pub fn row(&self, row: u32) -> impl Iterator<Item = &CellObject> {
+        self.cells
+            .range((row, 0)..=(row, u32::MAX))
+            .map(|(_, cell)| cell)
+    }
+
+    pub fn area(&self, area: &Area) -> impl Iterator<Item = &CellObject> {
+        let (left, right) = (area.left(), area.right());
+        self.cells
+            .range((area.top(), left)..=(area.bottom(), right))
+            .filter(move |((_, c), _)| (left..=right).contains(c))
+            .map(|(_, cell)| cell)
+    }
+
+    pub fn column(&self, column: u32) -> impl Iterator<Item = &CellObject> {
+        self.cells
+            .iter()
+            .filter(move |((_, c), _)| *c == column)
+            .map(|(_, cell)| cell)
+    }
+}
@@ -0,0 +1,122 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HAlign {
+    // Numbers and dates to the right, booleans and errors centred, text to
+    // the left.
+    #[default]
+    General,
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VAlign {
+    Top,
+    Middle,
+    #[default]
+    Bottom,
+}
+
+// What happens to text wider than its cell.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Overflow {
+    // Spills into empty neighbouring cells, and is clipped at the first one
+    // that has content.
+    #[default]
+    Overflow,
+    Clip,
+    // Truncated with a trailing "…".
+    Ellipsis,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CellStyle {
+    pub h_align: HAlign,
+    pub v_align: VAlign,
+    pub overflow: Overflow,
+}
+
+impl HAlign {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HAlign::General => "general",
+            HAlign::Left => "left",
+            HAlign::Center => "center",
+            HAlign::Right => "right",
+        }
+    }
+}
+
+impl VAlign {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VAlign::Top => "top",
+            VAlign::Middle => "middle",
+            VAlign::Bottom => "bottom",
+        }
+    }
+}
+
+impl Overflow {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Overflow::Overflow => "overflow",
+            Overflow::Clip => "clip",
+            Overflow::Ellipsis => "ellipsis",
+        }
+    }
+}
+
+impl fmt::Display for HAlign {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for VAlign {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for HAlign {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [HAlign::General, HAlign::Left, HAlign::Center, HAlign::Right]
+            .into_iter()
+            .find(|a| a.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| format!("unknown horizontal alignment `{}`", s))
+    }
+}
+
+impl FromStr for VAlign {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [VAlign::Top, VAlign::Middle, VAlign::Bottom]
+            .into_iter()
+            .find(|a| a.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| format!("unknown vertical alignment `{}`", s))
+    }
+}
+
+impl FromStr for Overflow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Overflow::Overflow, Overflow::Clip, Overflow::Ellipsis]
+            .into_iter()
+            .find(|o| o.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| format!("unknown overflow `{}`", s))
+    }
+}
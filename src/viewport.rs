@@ -0,0 +1,307 @@
+use std::ops::Range;
+
+use crate::cell_ref::CellRef;
+use crate::grid::Grid;
+use crate::range::Area;
+
+// Browsers refuse to lay out elements past a few million pixels, so the
+// native scrollbar's spacer is capped at this size and scroll positions are
+// scaled to cover the whole grid.
+pub const MAX_SCROLL_SIZE: f64 = 10_000_000.0;
+
+pub const ROW_HEADER_WIDTH: f64 = 60.0;
+pub const COLUMN_HEADER_HEIGHT: f64 = 24.0;
+// How close to a header border the pointer must be to grab it.
+pub const RESIZE_MARGIN: f64 = 4.0;
+// Side of the square at the corner of the selection that is dragged to
+// fill.
+pub const FILL_HANDLE_SIZE: f64 = 6.0;
+
+// A header border that can be dragged; the index is the column or row
+// that the drag resizes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResizeHandle {
+    Column(u32),
+    Row(u32),
+}
+
+// The window of the grid shown on the canvas: the grid position shown at
+// the top-left of the body, and the canvas size. The body starts below and
+// to the right of the header strips, which do not scroll.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Viewport {
+    scroll_x: f64,
+    scroll_y: f64,
+    width: f64,
+    height: f64,
+    header_width: f64,
+    header_height: f64,
+}
+
+impl Viewport {
+    pub fn new(width: f64, height: f64) -> Self {
+        Self {
+            scroll_x: 0.0,
+            scroll_y: 0.0,
+            width,
+            height,
+            header_width: 0.0,
+            header_height: 0.0,
+        }
+    }
+
+    // A viewport with room for the row and column header strips.
+    pub fn with_headers(width: f64, height: f64) -> Self {
+        Self {
+            header_width: ROW_HEADER_WIDTH,
+            header_height: COLUMN_HEADER_HEIGHT,
+            ..Self::new(width, height)
+        }
+    }
+
+    pub fn scroll_x(&self) -> f64 {
+        self.scroll_x
+    }
+
+    pub fn scroll_y(&self) -> f64 {
+        self.scroll_y
+    }
+
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+
+    pub fn header_width(&self) -> f64 {
+        self.header_width
+    }
+
+    pub fn header_height(&self) -> f64 {
+        self.header_height
+    }
+
+    // Size of the scrolling part of the canvas.
+    pub fn body_width(&self) -> f64 {
+        (self.width - self.header_width).max(0.0)
+    }
+
+    pub fn body_height(&self) -> f64 {
+        (self.height - self.header_height).max(0.0)
+    }
+
+    pub fn resize(&mut self, grid: &Grid, width: f64, height: f64) {
+        self.width = width;
+        self.height = height;
+        self.scroll_to(grid, self.scroll_x, self.scroll_y);
+    }
+
+    // Furthest the viewport can scroll while still showing grid content.
+    pub fn max_scroll(&self, grid: &Grid) -> (f64, f64) {
+        (
+            (grid.get_width() - self.body_width()).max(0.0),
+            (grid.get_height() - self.body_height()).max(0.0),
+        )
+    }
+
+    // Scrolls to grid position (`x`, `y`), clamped to the grid. Returns
+    // whether the viewport moved.
+    pub fn scroll_to(&mut self, grid: &Grid, x: f64, y: f64) -> bool {
+        let (max_x, max_y) = self.max_scroll(grid);
+        let (x, y) = (x.clamp(0.0, max_x), y.clamp(0.0, max_y));
+        let moved = x != self.scroll_x || y != self.scroll_y;
+        self.scroll_x = x;
+        self.scroll_y = y;
+        moved
+    }
+
+    pub fn scroll_by(&mut self, grid: &Grid, dx: f64, dy: f64) -> bool {
+        self.scroll_to(grid, self.scroll_x + dx, self.scroll_y + dy)
+    }
+
+    // Scrolls just enough to show all of `cell`, or its top-left corner if
+    // it is larger than the body. Returns whether the viewport moved.
+    pub fn scroll_into_view(&mut self, grid: &Grid, cell: CellRef) -> bool {
+        let (left, top) = (grid.column_left(cell.column), grid.row_top(cell.row));
+        let right = left + grid.column_width(cell.column);
+        let bottom = top + grid.row_height(cell.row);
+        let mut x = self.scroll_x;
+        let mut y = self.scroll_y;
+        if right > x + self.body_width() {
+            x = right - self.body_width();
+        }
+        if left < x {
+            x = left;
+        }
+        if bottom > y + self.body_height() {
+            y = bottom - self.body_height();
+        }
+        if top < y {
+            y = top;
+        }
+        self.scroll_to(grid, x, y)
+    }
+
+    pub fn visible_columns(&self, grid: &Grid) -> Range<u32> {
+        let first = grid.column_at(self.scroll_x).unwrap_or(grid.num_cols());
+        let last = grid
+            .column_at(self.scroll_x + self.body_width() - 1.0)
+            .map_or(grid.num_cols(), |c| c + 1);
+        first..last.max(first)
+    }
+
+    pub fn visible_rows(&self, grid: &Grid) -> Range<u32> {
+        let first = grid.row_at(self.scroll_y).unwrap_or(grid.num_rows());
+        let last = grid
+            .row_at(self.scroll_y + self.body_height() - 1.0)
+            .map_or(grid.num_rows(), |r| r + 1);
+        first..last.max(first)
+    }
+
+    // The (possibly partly hidden) cell in the top-left corner.
+    pub fn top_left(&self, grid: &Grid) -> CellRef {
+        CellRef::new(
+            self.visible_rows(grid).start,
+            self.visible_columns(grid).start,
+        )
+    }
+
+    // Left edge of `column` in canvas coordinates.
+    pub fn column_x(&self, grid: &Grid, column: u32) -> f64 {
+        self.header_width + grid.column_left(column) - self.scroll_x
+    }
+
+    pub fn row_y(&self, grid: &Grid, row: u32) -> f64 {
+        self.header_height + grid.row_top(row) - self.scroll_y
+    }
+
+    // Position and size of `cell` in canvas coordinates.
+    pub fn cell_rect(&self, grid: &Grid, cell: CellRef) -> (f64, f64, f64, f64) {
+        (
+            self.column_x(grid, cell.column),
+            self.row_y(grid, cell.row),
+            grid.column_width(cell.column),
+            grid.row_height(cell.row),
+        )
+    }
+
+    // The cell under canvas position (`x`, `y`); `None` over the headers.
+    pub fn cell_at(&self, grid: &Grid, x: f64, y: f64) -> Option<CellRef> {
+        let column = self.column_header_at(grid, x)?;
+        let row = self.row_header_at(grid, y)?;
+        Some(CellRef::new(row, column))
+    }
+
+    // The column under canvas position `x`, ignoring the vertical position.
+    pub fn column_header_at(&self, grid: &Grid, x: f64) -> Option<u32> {
+        if x < self.header_width || x >= self.width {
+            return None;
+        }
+        grid.column_at(x - self.header_width + self.scroll_x)
+    }
+
+    pub fn row_header_at(&self, grid: &Grid, y: f64) -> Option<u32> {
+        if y < self.header_height || y >= self.height {
+            return None;
+        }
+        grid.row_at(y - self.header_height + self.scroll_y)
+    }
+
+    // The header border under canvas position (`x`, `y`), if any. A border
+    // belongs to the column or row before it, so a hidden column can be
+    // dragged open again from the border where it was.
+    pub fn resize_handle_at(&self, grid: &Grid, x: f64, y: f64) -> Option<ResizeHandle> {
+        if y >= 0.0 && y < self.header_height && x >= self.header_width {
+            let column = self
+                .column_header_at(grid, x + RESIZE_MARGIN)
+                .or_else(|| self.column_header_at(grid, x))?;
+            let left = self.column_x(grid, column);
+            if (x - left).abs() <= RESIZE_MARGIN && column > 0 {
+                return Some(ResizeHandle::Column(column - 1));
+            }
+            let right = left + grid.column_width(column);
+            if (x - right).abs() <= RESIZE_MARGIN {
+                return Some(ResizeHandle::Column(column));
+            }
+        }
+        if x >= 0.0 && x < self.header_width && y >= self.header_height {
+            let row = self
+                .row_header_at(grid, y + RESIZE_MARGIN)
+                .or_else(|| self.row_header_at(grid, y))?;
+            let top = self.row_y(grid, row);
+            if (y - top).abs() <= RESIZE_MARGIN && row > 0 {
+                return Some(ResizeHandle::Row(row - 1));
+            }
+            let bottom = top + grid.row_height(row);
+            if (y - bottom).abs() <= RESIZE_MARGIN {
+                return Some(ResizeHandle::Row(row));
+            }
+        }
+        None
+    }
+
+    // The fill handle of `area`: a square centred on its bottom-right
+    // corner.
+    pub fn fill_handle_rect(&self, grid: &Grid, area: &Area) -> (f64, f64, f64, f64) {
+        let (x, y, width, height) = self.cell_rect(grid, area.end);
+        let half = FILL_HANDLE_SIZE / 2.0;
+        (
+            x + width - half,
+            y + height - half,
+            FILL_HANDLE_SIZE,
+            FILL_HANDLE_SIZE,
+        )
+    }
+
+    // Whether canvas position (`x`, `y`) grabs the fill handle of `area`.
+    // The handle is small, so a margin around it counts too.
+    pub fn is_over_fill_handle(&self, grid: &Grid, area: &Area, x: f64, y: f64) -> bool {
+        if x < self.header_width || y < self.header_height {
+            return false;
+        }
+        let (left, top, width, height) = self.fill_handle_rect(grid, area);
+        let margin = RESIZE_MARGIN / 2.0;
+        x >= left - margin
+            && x <= left + width + margin
+            && y >= top - margin
+            && y <= top + height + margin
+    }
+
+    // Size of the element that gives the native scrollbars their range.
+    pub fn spacer_size(&self, grid: &Grid) -> (f64, f64) {
+        (
+            (grid.get_width() + self.header_width).min(MAX_SCROLL_SIZE),
+            (grid.get_height() + self.header_height).min(MAX_SCROLL_SIZE),
+        )
+    }
+
+    // Scrolls to match the native scrollbar position (`left`, `top`).
+    pub fn scroll_to_native(&mut self, grid: &Grid, left: f64, top: f64) -> bool {
+        let (spacer_w, spacer_h) = self.spacer_size(grid);
+        let (max_x, max_y) = self.max_scroll(grid);
+        let x = scale(left, spacer_w - self.width, max_x);
+        let y = scale(top, spacer_h - self.height, max_y);
+        self.scroll_to(grid, x, y)
+    }
+
+    // The native scrollbar position for the current scroll offset.
+    pub fn native_scroll(&self, grid: &Grid) -> (f64, f64) {
+        let (spacer_w, spacer_h) = self.spacer_size(grid);
+        let (max_x, max_y) = self.max_scroll(grid);
+        (
+            scale(self.scroll_x, max_x, spacer_w - self.width),
+            scale(self.scroll_y, max_y, spacer_h - self.height),
+        )
+    }
+}
+
+// Maps `value` from [0, from] onto [0, to].
+fn scale(value: f64, from: f64, to: f64) -> f64 {
+    if from <= 0.0 || from == to {
+        value
+    } else {
+        value / from * to.max(0.0)
+    }
+}
@@ -0,0 +1,129 @@
+use wasm_spreadsheet::{
+    fit_column_width, Axis, CellRef, Grid, ResizeHandle, TextMeasure, Viewport,
+};
+
+struct Monospace;
+
+impl TextMeasure for Monospace {
+    fn text_width(&self, text: &str) -> f64 {
+        text.chars().count() as f64 * 10.0
+    }
+}
+
+#[test]
+fn uniform_axis_needs_no_storage() {
+    let axis = Axis::new(1_048_576, 30.0);
+
+    assert_eq!(axis.offset(10), 300.0);
+    assert_eq!(axis.total(), 1_048_576.0 * 30.0);
+    assert_eq!(axis.index_at(0.0), Some(0));
+    assert_eq!(axis.index_at(299.9), Some(9));
+    assert_eq!(axis.index_at(300.0), Some(10));
+    assert_eq!(axis.index_at(axis.total()), None);
+    assert_eq!(axis.index_at(-1.0), None);
+}
+
+#[test]
+fn resized_entries_shift_later_offsets() {
+    let mut axis = Axis::new(100, 10.0);
+    axis.set_size(2, 50.0);
+    axis.set_size(5, 5.0);
+
+    assert_eq!(axis.size(2), 50.0);
+    assert_eq!(axis.offset(2), 20.0);
+    assert_eq!(axis.offset(3), 70.0);
+    assert_eq!(axis.offset(6), 70.0 + 20.0 + 5.0);
+    assert_eq!(axis.total(), 1000.0 + 40.0 - 5.0);
+
+    assert_eq!(axis.index_at(25.0), Some(2));
+    assert_eq!(axis.index_at(69.9), Some(2));
+    assert_eq!(axis.index_at(70.0), Some(3));
+    assert_eq!(axis.index_at(92.0), Some(5));
+    assert_eq!(axis.index_at(95.0), Some(6));
+
+    axis.reset(2);
+    assert_eq!(axis.offset(3), 30.0);
+}
+
+#[test]
+fn hidden_entries_take_no_space_and_keep_their_size() {
+    let mut axis = Axis::new(10, 10.0);
+    axis.set_size(3, 40.0);
+    axis.set_hidden(3, true);
+    axis.set_size(4, 0.0);
+
+    assert!(axis.is_hidden(3) && axis.is_hidden(4));
+    assert_eq!(axis.size(3), 0.0);
+    assert_eq!(axis.offset(5), 30.0);
+    assert_eq!(axis.index_at(30.0), Some(5));
+    assert_eq!(axis.visible_near(3), Some(5));
+    assert_eq!(axis.visible_near(9), Some(9));
+
+    axis.set_hidden(3, false);
+    assert_eq!(axis.size(3), 40.0);
+    axis.set_size(4, 25.0);
+    assert!(!axis.is_hidden(4));
+    assert_eq!(axis.total(), 80.0 + 40.0 + 25.0);
+}
+
+#[test]
+fn grid_geometry_follows_sizes() {
+    let mut grid = Grid::new(10, 10);
+    grid.set_column_width(0, 120.0).unwrap();
+    grid.set_row_height(1, 60.0).unwrap();
+    grid.set_column_hidden(1, true).unwrap();
+    assert!(grid.set_column_width(10, 1.0).is_err());
+
+    assert_eq!(grid.get_width(), 120.0 + 8.0 * 80.0);
+    assert_eq!(grid.get_height(), 9.0 * 30.0 + 60.0);
+
+    let viewport = Viewport::new(800.0, 600.0);
+    assert_eq!(
+        viewport.cell_rect(&grid, CellRef::new(2, 2)),
+        (120.0, 90.0, 80.0, 30.0)
+    );
+    assert_eq!(
+        viewport.cell_at(&grid, 125.0, 50.0),
+        Some(CellRef::new(1, 2))
+    );
+    assert_eq!(viewport.visible_columns(&grid), 0..10);
+}
+
+#[test]
+fn header_borders_are_resize_handles() {
+    let grid = Grid::new(10, 10);
+    let viewport = Viewport::with_headers(800.0, 600.0);
+    // Column A spans 60..140 and row 1 spans 24..54 on the canvas.
+    assert_eq!(
+        viewport.resize_handle_at(&grid, 138.0, 10.0),
+        Some(ResizeHandle::Column(0))
+    );
+    assert_eq!(
+        viewport.resize_handle_at(&grid, 142.0, 10.0),
+        Some(ResizeHandle::Column(0))
+    );
+    assert_eq!(viewport.resize_handle_at(&grid, 100.0, 10.0), None);
+    assert_eq!(viewport.resize_handle_at(&grid, 62.0, 10.0), None);
+    assert_eq!(
+        viewport.resize_handle_at(&grid, 30.0, 55.0),
+        Some(ResizeHandle::Row(0))
+    );
+    // Not in the body.
+    assert_eq!(viewport.resize_handle_at(&grid, 140.0, 100.0), None);
+}
+
+#[test]
+fn autofit_uses_the_widest_formatted_value() {
+    let mut grid = Grid::new(10, 10);
+    assert_eq!(fit_column_width(&grid, 0, &Monospace), 80.0);
+
+    grid.set_input(CellRef::new(0, 0), "short").unwrap();
+    grid.set_input(CellRef::new(4, 0), "a much longer label")
+        .unwrap();
+    grid.set_input(CellRef::new(0, 1), "1234.5").unwrap();
+    grid.set_cell_format(CellRef::new(0, 1), Some("#,##0.00".parse().unwrap()))
+        .unwrap();
+
+    assert_eq!(fit_column_width(&grid, 0, &Monospace), 190.0 + 8.0);
+    assert_eq!(fit_column_width(&grid, 1, &Monospace), 80.0 + 8.0);
+}
@@ -0,0 +1,92 @@
+use wasm_spreadsheet::{column_index, column_name, CellRef, CellRefError, MAX_COLUMNS, MAX_ROWS};
+
+#[test]
+fn column_names_round_trip() {
+    for column in [0, 25, 26, 51, 701, 702, MAX_COLUMNS - 1] {
+        assert_eq!(column_index(&column_name(column)), Some(column));
+    }
+    assert_eq!(column_name(MAX_COLUMNS - 1), "XFD");
+    assert_eq!(column_index("xfd"), Some(MAX_COLUMNS - 1));
+    assert_eq!(column_index("XFE"), None);
+    assert_eq!(column_index("A1"), None);
+}
+
+#[test]
+fn parses_a1_notation() {
+    let b12: CellRef = "B12".parse().unwrap();
+    assert_eq!(b12, CellRef::new(11, 1));
+
+    let abs: CellRef = "$B$12".parse().unwrap();
+    assert_eq!(abs, CellRef::absolute(11, 1));
+
+    let mixed: CellRef = "aa$1".parse().unwrap();
+    assert_eq!((mixed.row, mixed.column), (0, 26));
+    assert!(mixed.row_absolute && !mixed.column_absolute);
+}
+
+#[test]
+fn a1_round_trips() {
+    for s in ["A1", "B12", "$B$12", "AA1", "$XFD1048576", "Z$9"] {
+        let cell: CellRef = s.parse().unwrap();
+        assert_eq!(cell.to_string(), s);
+    }
+}
+
+#[test]
+fn rejects_bad_a1_input() {
+    assert_eq!("".parse::<CellRef>(), Err(CellRefError::Empty));
+    assert!(matches!(
+        "12".parse::<CellRef>(),
+        Err(CellRefError::Malformed(_))
+    ));
+    assert!(matches!(
+        "B".parse::<CellRef>(),
+        Err(CellRefError::Malformed(_))
+    ));
+    assert!(matches!(
+        "B1C".parse::<CellRef>(),
+        Err(CellRefError::Malformed(_))
+    ));
+    assert_eq!("A0".parse::<CellRef>(), Err(CellRefError::RowOutOfRange(0)));
+    assert_eq!(
+        "A1048577".parse::<CellRef>(),
+        Err(CellRefError::RowOutOfRange(MAX_ROWS as i64 + 1))
+    );
+    assert_eq!(
+        "XFE1".parse::<CellRef>(),
+        Err(CellRefError::ColumnOutOfRange(MAX_COLUMNS as i64 + 1))
+    );
+    assert_eq!(
+        "xfe1".parse::<CellRef>(),
+        Err(CellRefError::ColumnOutOfRange(MAX_COLUMNS as i64 + 1))
+    );
+}
+
+#[test]
+fn r1c1_notation() {
+    let origin = CellRef::new(4, 4);
+
+    assert_eq!(
+        CellRef::parse_r1c1("R12C2", origin),
+        Ok(CellRef::absolute(11, 1))
+    );
+    assert_eq!(
+        CellRef::parse_r1c1("R[-1]C[2]", origin),
+        Ok(CellRef::new(3, 6))
+    );
+    assert_eq!(CellRef::parse_r1c1("RC", origin), Ok(origin));
+    assert_eq!(
+        CellRef::parse_r1c1("R[-5]C", origin),
+        Err(CellRefError::RowOutOfRange(0))
+    );
+    assert!(CellRef::parse_r1c1("R1X1", origin).is_err());
+    assert_eq!(
+        CellRef::parse_r1c1("R[9223372036854775807]C1", origin),
+        Err(CellRefError::Malformed("R[9223372036854775807]C1".into()))
+    );
+
+    for s in ["R12C2", "R[-1]C[2]", "RC", "R5C[-3]"] {
+        let cell = CellRef::parse_r1c1(s, origin).unwrap();
+        assert_eq!(cell.to_r1c1(origin), s);
+    }
+}
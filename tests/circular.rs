@@ -0,0 +1,118 @@
+use wasm_spreadsheet::{CellRef, CellValue, ErrorKind, Grid, IterativeCalc};
+
+fn cell(s: &str) -> CellRef {
+    s.parse().unwrap()
+}
+
+fn circ() -> CellValue {
+    CellValue::Error(ErrorKind::Circular)
+}
+
+#[test]
+fn marks_cycle_members() {
+    let mut grid = Grid::new(10, 10);
+    grid.set_formula(cell("A1"), "=B1+1").unwrap();
+    grid.set_formula(cell("B1"), "=C1+1").unwrap();
+    grid.set_formula(cell("C1"), "=A1+1").unwrap();
+    grid.set_formula(cell("D1"), "=A1*2").unwrap();
+    grid.set_formula(cell("E1"), "=1").unwrap();
+    grid.recalculate();
+
+    for at in ["A1", "B1", "C1", "D1"] {
+        assert_eq!(grid.get_value(cell(at)), circ(), "{}", at);
+    }
+    assert_eq!(grid.get_value(cell("E1")), CellValue::Int(1));
+    assert_eq!(
+        grid.circular_references(),
+        vec![vec![cell("A1"), cell("B1"), cell("C1")]]
+    );
+}
+
+#[test]
+fn reports_cycle_path() {
+    let mut grid = Grid::new(10, 10);
+    grid.set_formula(cell("A1"), "=SUM(B1:B5)").unwrap();
+    grid.set_formula(cell("B3"), "=A1").unwrap();
+    grid.set_formula(cell("C1"), "=C1").unwrap();
+
+    assert_eq!(
+        grid.cycle_path(cell("A1")),
+        Some(vec![cell("A1"), cell("B3")])
+    );
+    assert_eq!(
+        grid.cycle_path(cell("B3")),
+        Some(vec![cell("B3"), cell("A1")])
+    );
+    assert_eq!(grid.cycle_path(cell("C1")), Some(vec![cell("C1")]));
+    assert_eq!(grid.cycle_path(cell("B1")), None);
+}
+
+#[test]
+fn breaking_a_cycle_recovers() {
+    let mut grid = Grid::new(10, 10);
+    grid.set_formula(cell("A1"), "=B1").unwrap();
+    grid.set_formula(cell("B1"), "=A1").unwrap();
+    grid.recalculate();
+    assert_eq!(grid.get_value(cell("B1")), circ());
+
+    grid.set_value(cell("A1"), CellValue::Int(5)).unwrap();
+    grid.recalculate();
+
+    assert_eq!(grid.get_value(cell("B1")), CellValue::Int(5));
+    assert!(grid.circular_references().is_empty());
+}
+
+#[test]
+fn iterative_mode_converges() {
+    let mut grid = Grid::new(10, 10);
+    // x = 10 + x / 2 converges to 20.
+    grid.set_formula(cell("A1"), "=10+B1/2").unwrap();
+    grid.set_formula(cell("B1"), "=A1").unwrap();
+    grid.recalculate();
+    assert_eq!(grid.get_value(cell("A1")), circ());
+
+    grid.set_iterative_calculation(Some(IterativeCalc::default()));
+    grid.recalculate();
+
+    let CellValue::Float(x) = grid.get_value(cell("A1")) else {
+        panic!("expected a number");
+    };
+    assert!((x - 20.0).abs() < 0.01, "{}", x);
+}
+
+#[test]
+fn iterative_mode_stops_at_the_limit() {
+    let mut grid = Grid::new(10, 10);
+    grid.set_iterative_calculation(Some(IterativeCalc {
+        max_iterations: 5,
+        max_change: 0.0,
+    }));
+    grid.set_formula(cell("A1"), "=A1+1").unwrap();
+    grid.recalculate();
+
+    assert_eq!(grid.get_value(cell("A1")), CellValue::Int(5));
+}
+
+#[test]
+fn cycles_are_found_as_formulas_are_entered() {
+    let mut grid = Grid::new(10, 10);
+    grid.set_formula(cell("A1"), "=B1").unwrap();
+    grid.set_formula(cell("B1"), "=SUM(C1:C3)").unwrap();
+    assert!(grid.circular_references().is_empty());
+
+    // No recalculation needed to see the cycle, or to see it go.
+    grid.set_formula(cell("C2"), "=A1").unwrap();
+    assert_eq!(
+        grid.circular_references(),
+        vec![vec![cell("A1"), cell("B1"), cell("C2")]]
+    );
+    assert!(grid.dependencies().is_circular(cell("B1")));
+    assert!(!grid.dependencies().is_circular(cell("C1")));
+
+    grid.set_formula(cell("D1"), "=D1").unwrap();
+    assert_eq!(grid.circular_references().len(), 2);
+
+    grid.set_value(cell("C2"), CellValue::Int(1)).unwrap();
+    assert_eq!(grid.circular_references(), vec![vec![cell("D1")]]);
+    assert!(!grid.dependencies().is_circular(cell("A1")));
+}
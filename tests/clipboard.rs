@@ -0,0 +1,157 @@
+use wasm_spreadsheet::{
+    copy, parse_html, parse_tsv, paste_text, Area, CellRef, CellValue, ColumnType, ErrorKind, Grid,
+};
+
+fn cell(s: &str) -> CellRef {
+    s.parse().unwrap()
+}
+
+fn area(s: &str) -> Area {
+    s.parse().unwrap()
+}
+
+fn rows(rows: &[&[&str]]) -> Vec<Vec<String>> {
+    rows.iter()
+        .map(|row| row.iter().map(|f| f.to_string()).collect())
+        .collect()
+}
+
+#[test]
+fn copy_emits_tsv_and_an_html_table() {
+    let mut grid = Grid::new(5, 5);
+    grid.set_input(cell("A1"), "1").unwrap();
+    grid.set_input(cell("B1"), "a\tb").unwrap();
+    grid.set_input(cell("A2"), "=A1*2").unwrap();
+    grid.set_input(cell("B2"), "<\"x\" & y>").unwrap();
+    grid.recalculate();
+
+    let data = copy(&grid, &area("A1:B2"));
+    assert_eq!(data.text, "1\t\"a\tb\"\n2\t\"<\"\"x\"\" & y>\"\n");
+    assert_eq!(
+        data.html,
+        "<table><tr><td>1</td><td>a&#9;b</td></tr>\
+         <tr><td>2</td><td>&lt;&quot;x&quot; &amp; y&gt;</td></tr></table>"
+    );
+
+    // Both formats read back to the same fields.
+    let expected = rows(&[&["1", "a\tb"], &["2", "<\"x\" & y>"]]);
+    assert_eq!(parse_tsv(&data.text), expected);
+    assert_eq!(parse_html(&data.html), Some(expected));
+}
+
+#[test]
+fn whole_columns_copy_only_the_used_rows() {
+    let mut grid = Grid::new(100, 5);
+    grid.set_input(cell("B3"), "x").unwrap();
+    let data = copy(&grid, &area("A:B"));
+    assert_eq!(data.text, "\t\n\t\n\tx\n");
+}
+
+#[test]
+fn parses_tsv_and_html_from_other_apps() {
+    assert_eq!(
+        parse_tsv("a\tb\r\n\"multi\nline\"\t\r\nlast"),
+        rows(&[&["a", "b"], &["multi\nline", ""], &["last"]])
+    );
+    assert_eq!(parse_tsv(""), Vec::<Vec<String>>::new());
+
+    let html = "<html><body><table border=1>\n\
+                <tr><th>Name</th><th>Qty</th></tr>\n\
+                <tr><td style=\"x\"><b>Bolt</b>&nbsp;M4</td><td> 1,200 </td></tr>\n\
+                </table></body></html>";
+    assert_eq!(
+        parse_html(html),
+        Some(rows(&[&["Name", "Qty"], &["Bolt M4", "1,200"]]))
+    );
+    assert_eq!(parse_html("just text"), None);
+}
+
+#[test]
+fn paste_respects_column_types() {
+    let mut grid = Grid::new(5, 5);
+    grid.set_column_type(1, ColumnType::Int).unwrap();
+    grid.set_column_type(2, ColumnType::Percent).unwrap();
+
+    let failed = paste_text(
+        &mut grid,
+        cell("A1"),
+        &rows(&[&["label", "1,200", "5%"], &["x", "lots", "0.5"]]),
+    );
+    assert_eq!(failed, vec![cell("B2")]);
+    assert_eq!(
+        grid.get_value(cell("A1")),
+        CellValue::String("label".into())
+    );
+    assert_eq!(grid.get_value(cell("B1")), CellValue::Int(1200));
+    assert_eq!(grid.get_value(cell("C1")), CellValue::Float(0.05));
+    assert_eq!(grid.get_value(cell("B2")), CellValue::Empty);
+
+    // Fields past the edge of the sheet are dropped.
+    let failed = paste_text(&mut grid, cell("E5"), &rows(&[&["1", "2"], &["3"]]));
+    assert!(failed.is_empty());
+    assert_eq!(grid.get_value(cell("E5")), CellValue::Int(1));
+}
+
+#[test]
+fn copied_formulas_shift_their_relative_references() {
+    let mut grid = Grid::new(10, 5);
+    grid.set_input(cell("A1"), "2").unwrap();
+    grid.set_input(cell("A2"), "3").unwrap();
+    grid.set_input(cell("B1"), "=A1*$A$1").unwrap();
+    grid.copy_area(&area("B1"), cell("B2"));
+    grid.copy_area(&area("B1"), cell("A3"));
+    grid.recalculate();
+
+    assert_eq!(grid.edit_text(cell("B2")), "=A2*$A$1");
+    assert_eq!(grid.get_value(cell("B2")), CellValue::Int(6));
+    // Shifting A1 left falls off the sheet.
+    assert_eq!(grid.edit_text(cell("A3")), "=#REF!*$A$1");
+    assert_eq!(grid.get_value(cell("A3")), CellValue::Error(ErrorKind::Ref));
+}
+
+#[test]
+fn cut_and_paste_moves_cells_and_follows_references() {
+    let mut grid = Grid::new(10, 5);
+    grid.set_input(cell("A1"), "4").unwrap();
+    grid.set_input(cell("A2"), "=A1+1").unwrap();
+    grid.set_input(cell("B1"), "=SUM(A1:A2)").unwrap();
+    grid.set_input(cell("C1"), "=A1:A3").unwrap();
+    grid.move_area(&area("A1:A2"), cell("D5"));
+    grid.recalculate();
+
+    assert_eq!(grid.get_value(cell("A1")), CellValue::Empty);
+    assert_eq!(grid.get_value(cell("D5")), CellValue::Int(4));
+    // The moved formula and the one outside both follow the cells.
+    assert_eq!(grid.edit_text(cell("D6")), "=D5+1");
+    assert_eq!(grid.edit_text(cell("B1")), "=SUM(D5:D6)");
+    assert_eq!(grid.get_value(cell("B1")), CellValue::Int(9));
+    // A range only partly moved stays put.
+    assert_eq!(grid.edit_text(cell("C1")), "=A1:A3");
+}
+
+#[test]
+fn references_to_cells_a_move_overwrites_become_ref_errors() {
+    let mut grid = Grid::new(10, 5);
+    grid.set_input(cell("A1"), "1").unwrap();
+    grid.set_input(cell("A2"), "2").unwrap();
+    grid.set_input(cell("C3"), "old").unwrap();
+    grid.set_input(cell("E1"), "=C3").unwrap();
+    grid.set_input(cell("E2"), "=A2*2").unwrap();
+    grid.set_input(cell("E3"), "=COUNTA(C1:C9)").unwrap();
+    grid.set_input(cell("E4"), "=D9").unwrap();
+    grid.move_area(&area("A1:A2"), cell("C2"));
+    grid.recalculate();
+
+    assert_eq!(grid.edit_text(cell("E1")), "=#REF!");
+    assert_eq!(grid.get_value(cell("E1")), CellValue::Error(ErrorKind::Ref));
+    assert_eq!(grid.edit_text(cell("E2")), "=C3*2");
+    assert_eq!(grid.get_value(cell("E2")), CellValue::Int(4));
+    assert_eq!(grid.edit_text(cell("E3")), "=COUNTA(#REF!)");
+    assert_eq!(grid.edit_text(cell("E4")), "=D9");
+
+    grid.set_input(cell("B8"), "=B9").unwrap();
+    grid.set_input(cell("B9"), "x").unwrap();
+    grid.move_area(&area("B8:B9"), cell("B9"));
+    // The overwritten cell was part of the source, so it followed.
+    assert_eq!(grid.edit_text(cell("B9")), "=B10");
+}
@@ -0,0 +1,115 @@
+use wasm_spreadsheet::{CellRef, CellValue, ColumnType, DateTime, EditorKind, Grid};
+
+fn cell(s: &str) -> CellRef {
+    s.parse().unwrap()
+}
+
+fn parse(column_type: &ColumnType, input: &str) -> Option<CellValue> {
+    column_type.parse(input).ok()
+}
+
+#[test]
+fn bool_columns() {
+    let t = ColumnType::Bool;
+    assert_eq!(parse(&t, "Yes"), Some(CellValue::Bool(true)));
+    assert_eq!(parse(&t, "false"), Some(CellValue::Bool(false)));
+    assert_eq!(parse(&t, "maybe"), None);
+    assert_eq!(t.convert(&CellValue::Int(1)), Ok(CellValue::Bool(true)));
+    assert_eq!(t.editor(), EditorKind::Checkbox);
+}
+
+#[test]
+fn date_columns() {
+    let date = CellValue::DateTime(DateTime::from_ymd(2024, 3, 1).unwrap());
+    let t = ColumnType::Date;
+    assert_eq!(parse(&t, "2024-03-01"), Some(date.clone()));
+    assert_eq!(parse(&t, "2024-03-01 10:00"), None);
+    assert_eq!(parse(&t, "tomorrow"), None);
+    assert_eq!(t.display(&date), "2024-03-01");
+    assert_eq!(t.editor(), EditorKind::Date);
+
+    let t = ColumnType::DateTime;
+    let value = parse(&t, "2024-03-01 10:00").unwrap();
+    assert_eq!(t.display(&value), "2024-03-01 10:00:00");
+    assert_eq!(t.editor(), EditorKind::DateTime);
+    // Narrowing to a date drops the time of day.
+    assert_eq!(ColumnType::Date.convert(&value), Ok(date));
+}
+
+#[test]
+fn currency_columns() {
+    let usd = ColumnType::Currency("USD".into());
+    assert_eq!(parse(&usd, "$1,200.50"), Some(CellValue::Float(1200.5)));
+    assert_eq!(parse(&usd, "-$3"), Some(CellValue::Float(-3.0)));
+    assert_eq!(parse(&usd, "(12) USD"), Some(CellValue::Float(-12.0)));
+    assert_eq!(parse(&usd, "twelve"), None);
+    assert_eq!(
+        usd.display(&CellValue::Float(-1234567.891)),
+        "-$1,234,567.89"
+    );
+
+    let jpy = ColumnType::Currency("JPY".into());
+    assert_eq!(jpy.display(&CellValue::Int(5000)), "¥5,000");
+    let chf = ColumnType::Currency("CHF".into());
+    assert_eq!(chf.display(&CellValue::Float(9.5)), "CHF 9.50");
+}
+
+#[test]
+fn percent_columns() {
+    let t = ColumnType::Percent;
+    assert_eq!(parse(&t, "12.5%"), Some(CellValue::Float(0.125)));
+    assert_eq!(parse(&t, "50"), Some(CellValue::Float(0.5)));
+    assert_eq!(parse(&t, "1,000%"), Some(CellValue::Float(10.0)));
+    assert_eq!(parse(&t, "lots"), None);
+    assert_eq!(t.display(&CellValue::Float(0.125)), "12.5%");
+}
+
+#[test]
+fn enum_columns() {
+    let t: ColumnType = "enum:Low|Medium|High".parse().unwrap();
+    assert_eq!(
+        parse(&t, "medium"),
+        Some(CellValue::String("Medium".into()))
+    );
+    assert_eq!(parse(&t, "urgent"), None);
+    assert!(t.validate(&CellValue::String("High".into())));
+    assert!(!t.validate(&CellValue::String("high".into())));
+    assert_eq!(
+        t.editor(),
+        EditorKind::Dropdown(vec!["Low".into(), "Medium".into(), "High".into()])
+    );
+}
+
+#[test]
+fn type_names_round_trip() {
+    for name in [
+        "general",
+        "string",
+        "int",
+        "float",
+        "bool",
+        "date",
+        "datetime",
+        "currency:EUR",
+        "percent",
+        "enum:a|b",
+    ] {
+        assert_eq!(name.parse::<ColumnType>().unwrap().to_string(), name);
+    }
+    assert!("currency".parse::<ColumnType>().is_err());
+    assert!("colour".parse::<ColumnType>().is_err());
+}
+
+#[test]
+fn grid_applies_new_types() {
+    let mut grid = Grid::new(10, 10);
+    grid.set_column_type(0, "enum:open|closed".parse().unwrap())
+        .unwrap();
+    grid.set_input(cell("A1"), "OPEN").unwrap();
+
+    assert_eq!(grid.get_value(cell("A1")), CellValue::String("open".into()));
+    assert!(grid.set_input(cell("A2"), "pending").is_err());
+
+    let report = grid.set_column_type(0, ColumnType::Bool).unwrap();
+    assert_eq!(report.failed, vec![cell("A1")]);
+}
@@ -0,0 +1,102 @@
+use wasm_spreadsheet::{CellRef, CellValue, ColumnType, DateTime, ErrorKind, Grid, GridError};
+
+fn cell(s: &str) -> CellRef {
+    s.parse().unwrap()
+}
+
+#[test]
+fn general_columns_infer_values() {
+    let mut grid = Grid::new(10, 10);
+    let inputs = [
+        ("A1", "12", CellValue::Int(12)),
+        ("A2", "1,234.5", CellValue::Float(1234.5)),
+        ("A3", "true", CellValue::Bool(true)),
+        ("A4", "50%", CellValue::Float(0.5)),
+        (
+            "A5",
+            "2024-01-31",
+            CellValue::DateTime(DateTime::from_ymd(2024, 1, 31).unwrap()),
+        ),
+        ("A6", "#N/A", CellValue::Error(ErrorKind::NA)),
+        ("A7", "1,23", CellValue::String("1,23".into())),
+        ("A8", "inf", CellValue::String("inf".into())),
+    ];
+    for (at, input, expected) in inputs {
+        grid.set_input(cell(at), input).unwrap();
+        assert_eq!(grid.get_value(cell(at)), expected, "{}", input);
+    }
+}
+
+#[test]
+fn typed_columns_coerce_input() {
+    let mut grid = Grid::new(10, 10);
+    grid.set_column_type(0, ColumnType::Int).unwrap();
+    grid.set_column_type(1, ColumnType::Float).unwrap();
+    grid.set_column_type(2, ColumnType::String).unwrap();
+
+    grid.set_input(cell("A1"), " 12 ").unwrap();
+    grid.set_input(cell("B1"), "12").unwrap();
+    grid.set_input(cell("C1"), "12").unwrap();
+    grid.set_input(cell("A2"), "=1.5*2").unwrap();
+
+    assert_eq!(grid.get_value(cell("A1")), CellValue::Int(12));
+    assert_eq!(grid.get_value(cell("B1")), CellValue::Float(12.0));
+    assert_eq!(grid.get_value(cell("C1")), CellValue::String("12".into()));
+    assert!(grid.get_formula(cell("A2")).is_some());
+}
+
+#[test]
+fn typed_columns_reject_bad_input() {
+    let mut grid = Grid::new(10, 10);
+    grid.set_column_type(0, ColumnType::Int).unwrap();
+    grid.set_input(cell("A1"), "7").unwrap();
+
+    for input in ["abc", "1.5", "TRUE"] {
+        let err = grid.set_input(cell("A1"), input).unwrap_err();
+        let GridError::Coercion(err) = err else {
+            panic!("expected a coercion error");
+        };
+        assert_eq!(err.input, input);
+        assert_eq!(err.expected, ColumnType::Int);
+    }
+    assert!(grid.set_value(cell("A1"), CellValue::Float(0.5)).is_err());
+
+    // Non-finite numbers are not numbers, however they are spelled.
+    grid.set_column_type(1, ColumnType::Float).unwrap();
+    for input in ["inf", "NaN", "1e400", "-1e400"] {
+        assert!(grid.set_input(cell("B1"), input).is_err(), "{input}");
+    }
+    assert!(grid.set_input(cell("B1"), "=1e400*1").is_err());
+    grid.set_input(cell("C1"), "1e400").unwrap();
+    let report = grid.set_column_type(2, ColumnType::Float).unwrap();
+    assert_eq!(report.failed, vec![cell("C1")]);
+
+    // Rejected input leaves the cell untouched.
+    assert_eq!(grid.get_value(cell("A1")), CellValue::Int(7));
+    assert_eq!(
+        grid.set_input(cell("A1"), "abc").unwrap_err().to_string(),
+        "`abc` is not a valid int"
+    );
+}
+
+#[test]
+fn changing_type_converts_and_reports() {
+    let mut grid = Grid::new(10, 10);
+    grid.set_input(cell("A1"), "1").unwrap();
+    grid.set_input(cell("A2"), "2.5").unwrap();
+    grid.set_input(cell("A3"), "42").unwrap();
+    grid.set_column_type(0, ColumnType::String).unwrap();
+    grid.set_input(cell("A4"), "oops").unwrap();
+    grid.set_input(cell("A5"), "=A1").unwrap();
+    grid.set_input(cell("B1"), "=SUM(A1:A3)").unwrap();
+    grid.recalculate();
+
+    let report = grid.set_column_type(0, ColumnType::Int).unwrap();
+    grid.recalculate();
+
+    assert_eq!(report.converted, 2);
+    assert_eq!(report.failed, vec![cell("A2"), cell("A4")]);
+    assert_eq!(grid.get_value(cell("A3")), CellValue::Int(42));
+    assert_eq!(grid.get_value(cell("A2")), CellValue::String("2.5".into()));
+    assert_eq!(grid.get_value(cell("B1")), CellValue::Int(43));
+}
@@ -0,0 +1,80 @@
+use std::collections::BTreeSet;
+
+use wasm_spreadsheet::{CellRef, CellValue, Grid};
+
+fn cell(s: &str) -> CellRef {
+    s.parse().unwrap()
+}
+
+fn cells(list: &[&str]) -> BTreeSet<CellRef> {
+    list.iter().map(|s| cell(s)).collect()
+}
+
+fn int(n: i64) -> CellValue {
+    CellValue::Int(n)
+}
+
+#[test]
+fn tracks_precedents_and_dependents() {
+    let mut grid = Grid::new(100, 10);
+    grid.set_formula(cell("C1"), "=A1+$B$1").unwrap();
+    grid.set_formula(cell("D1"), "=SUM(A:A)").unwrap();
+
+    let deps = grid.dependencies();
+    assert_eq!(deps.precedents(cell("C1")).len(), 2);
+    assert_eq!(deps.dependents(cell("B1")), cells(&["C1"]));
+    assert_eq!(deps.dependents(cell("A1")), cells(&["C1", "D1"]));
+    assert_eq!(deps.dependents(cell("A99")), cells(&["D1"]));
+
+    grid.set_value(cell("C1"), int(0)).unwrap();
+    assert!(grid.dependencies().dependents(cell("B1")).is_empty());
+}
+
+#[test]
+fn recalculates_only_affected_cells() {
+    let mut grid = Grid::new(100, 10);
+    grid.set_value(cell("A1"), int(1)).unwrap();
+    grid.set_value(cell("E1"), int(5)).unwrap();
+    grid.set_formula(cell("B1"), "=A1*2").unwrap();
+    grid.set_formula(cell("C1"), "=B1+1").unwrap();
+    grid.set_formula(cell("F1"), "=E1").unwrap();
+    grid.recalculate();
+
+    grid.set_value(cell("A1"), int(10)).unwrap();
+    let changed = grid.recalculate();
+
+    assert_eq!(changed, cells(&["A1", "B1", "C1"]));
+    assert_eq!(grid.get_value(cell("C1")), int(21));
+    assert!(grid.recalculate().is_empty());
+}
+
+#[test]
+fn unchanged_results_are_not_reported() {
+    let mut grid = Grid::new(100, 10);
+    grid.set_value(cell("A1"), int(1)).unwrap();
+    grid.set_formula(cell("B1"), "=A1>0").unwrap();
+    grid.set_formula(cell("C1"), "=B1").unwrap();
+    grid.recalculate();
+
+    grid.set_value(cell("A1"), int(2)).unwrap();
+
+    assert_eq!(grid.recalculate(), cells(&["A1"]));
+}
+
+#[test]
+fn evaluates_in_topological_order() {
+    let mut grid = Grid::new(100, 10);
+    // Written so that row-major order would read stale values.
+    grid.set_formula(cell("A1"), "=A2+1").unwrap();
+    grid.set_formula(cell("A2"), "=A3+1").unwrap();
+    grid.set_formula(cell("A3"), "=SUM(B1:B50)").unwrap();
+    grid.set_value(cell("B40"), int(7)).unwrap();
+
+    grid.recalculate();
+    assert_eq!(grid.get_value(cell("A1")), int(9));
+
+    grid.set_value(cell("B2"), int(1)).unwrap();
+    let changed = grid.recalculate();
+    assert_eq!(changed, cells(&["A1", "A2", "A3", "B2"]));
+    assert_eq!(grid.get_value(cell("A1")), int(10));
+}
@@ -0,0 +1,107 @@
+use wasm_spreadsheet::{Area, CellRef, CellValue, ColumnType, Editor, EditorKind, Grid};
+
+fn cell(s: &str) -> CellRef {
+    s.parse().unwrap()
+}
+
+#[test]
+fn clicking_cells_inserts_references_where_an_operand_is_expected() {
+    let mut editor = Editor::new(cell("C1"), "=");
+    assert!(editor.can_point(1));
+    let caret = editor.point_at(1, Area::cell(cell("A1")));
+    assert_eq!((editor.text(), caret), ("=A1", 3));
+
+    // Clicking again replaces the reference just inserted.
+    assert!(editor.can_point(3));
+    let caret = editor.point_at(3, Area::cell(cell("B2")));
+    assert_eq!((editor.text(), caret), ("=B2", 3));
+
+    // Dragging turns it into a range.
+    let caret = editor.point_at(3, Area::new(cell("B2"), cell("B5")));
+    assert_eq!((editor.text(), caret), ("=B2:B5", 6));
+}
+
+#[test]
+fn typing_ends_pointing() {
+    let mut editor = Editor::new(cell("C1"), "=SUM(");
+    assert!(editor.can_point(5));
+    let caret = editor.point_at(5, Area::cell(cell("A1")));
+    editor.set_text("=SUM(A1,");
+    assert_eq!(caret, 7);
+    assert!(editor.can_point(8));
+    editor.point_at(8, Area::cell(cell("A2")));
+    assert_eq!(editor.text(), "=SUM(A1,A2");
+
+    editor.set_text("=SUM(A1,A2)");
+    assert!(!editor.can_point(11));
+}
+
+#[test]
+fn plain_text_never_takes_references() {
+    let editor = Editor::new(cell("A1"), "hello +");
+    assert!(!editor.can_point(7));
+    let editor = Editor::new(cell("A1"), "=A1");
+    assert!(!editor.can_point(3));
+    // Whitespace after an operator still expects an operand.
+    let editor = Editor::new(cell("A1"), "=A1 + ");
+    assert!(editor.can_point(6));
+}
+
+#[test]
+fn edit_text_round_trips_through_the_column_type() {
+    let mut grid = Grid::new(5, 5);
+    grid.set_column_type(1, ColumnType::Percent).unwrap();
+    grid.set_input(cell("B1"), "12.5%").unwrap();
+    grid.set_input(cell("A1"), "=1+2").unwrap();
+    grid.set_input(cell("A2"), "hello").unwrap();
+    grid.recalculate();
+
+    assert_eq!(grid.edit_text(cell("A1")), "=1+2");
+    assert_eq!(grid.edit_text(cell("A2")), "hello");
+    assert_eq!(grid.edit_text(cell("A3")), "");
+
+    let text = grid.edit_text(cell("B1"));
+    assert_eq!(text, "12.5%");
+    grid.set_input(cell("B1"), &text).unwrap();
+    assert_eq!(grid.get_value(cell("B1")), CellValue::Float(0.125));
+}
+
+#[test]
+fn columns_pick_their_editor() {
+    let mut grid = Grid::new(5, 5);
+    grid.set_column_type(0, ColumnType::Bool).unwrap();
+    grid.set_column_type(1, ColumnType::Date).unwrap();
+    grid.set_column_type(2, ColumnType::DateTime).unwrap();
+    let options = vec!["Low".to_string(), "High".to_string()];
+    grid.set_column_type(3, ColumnType::Enum(options.clone()))
+        .unwrap();
+    grid.set_input(cell("A1"), "true").unwrap();
+    grid.set_input(cell("B1"), "2024-03-01").unwrap();
+    grid.set_input(cell("C1"), "2024-03-01 10:30").unwrap();
+    grid.set_input(cell("B2"), "=B1+1").unwrap();
+
+    // Checkboxes toggle instead, unless the edit starts with typing.
+    assert_eq!(grid.checkbox(cell("A1")), Some(true));
+    assert_eq!(grid.checkbox(cell("A2")), Some(false));
+    assert_eq!(grid.checkbox(cell("E1")), None);
+    assert_eq!(Editor::open(&grid, cell("A1"), None), None);
+    let typed = Editor::open(&grid, cell("A1"), Some("f")).unwrap();
+    assert_eq!(typed.input_type(), "text");
+
+    let date = Editor::open(&grid, cell("B1"), None).unwrap();
+    assert_eq!((date.input_type(), date.text()), ("date", "2024-03-01"));
+    let date_time = Editor::open(&grid, cell("C1"), None).unwrap();
+    assert_eq!(
+        (date_time.input_type(), date_time.text()),
+        ("datetime-local", "2024-03-01T10:30:00")
+    );
+    let formula = Editor::open(&grid, cell("B2"), None).unwrap();
+    assert_eq!(
+        (formula.kind(), formula.text()),
+        (&EditorKind::Text, "=B1+1")
+    );
+
+    let dropdown = Editor::open(&grid, cell("D1"), Some("L")).unwrap();
+    assert_eq!(dropdown.options(), &options[..]);
+    assert_eq!(dropdown.input_type(), "text");
+}
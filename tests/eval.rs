@@ -0,0 +1,118 @@
+use wasm_spreadsheet::{CellRef, CellValue, ErrorKind, Grid};
+
+fn cell(s: &str) -> CellRef {
+    s.parse().unwrap()
+}
+
+fn grid_with(inputs: &[(&str, CellValue)], formulas: &[(&str, &str)]) -> Grid {
+    let mut grid = Grid::new(20, 10);
+    for (at, value) in inputs {
+        grid.set_value(cell(at), value.clone()).unwrap();
+    }
+    for (at, text) in formulas {
+        grid.set_formula(cell(at), text).unwrap();
+    }
+    grid.recalculate();
+    grid
+}
+
+fn eval(text: &str) -> CellValue {
+    let grid = grid_with(
+        &[
+            ("A1", CellValue::Int(10)),
+            ("A2", CellValue::Int(20)),
+            ("A3", CellValue::String("text".into())),
+            ("A4", CellValue::Float(0.5)),
+        ],
+        &[("J20", text)],
+    );
+    grid.get_value(cell("J20"))
+}
+
+fn text(s: &str) -> CellValue {
+    CellValue::String(s.into())
+}
+
+#[test]
+fn arithmetic_and_references() {
+    assert_eq!(eval("=1+2*3"), CellValue::Int(7));
+    assert_eq!(eval("=-2^2"), CellValue::Int(4));
+    assert_eq!(eval("=A1*A4"), CellValue::Int(5));
+    assert_eq!(eval("=50%"), CellValue::Float(0.5));
+    assert_eq!(eval("=B1+1"), CellValue::Int(1));
+    assert_eq!(eval("=B1"), CellValue::Int(0));
+    assert_eq!(eval("=\"3\"+1"), CellValue::Int(4));
+}
+
+#[test]
+fn text_and_comparison() {
+    assert_eq!(eval("=A3&\"-\"&A1"), text("text-10"));
+    assert_eq!(eval("=\"ABC\"=\"abc\""), CellValue::Bool(true));
+    assert_eq!(eval("=A1<A3"), CellValue::Bool(true));
+    assert_eq!(eval("=B1=0"), CellValue::Bool(true));
+    assert_eq!(eval("=IF(A1>5,\"big\",\"small\")"), text("big"));
+    assert_eq!(eval("=MID(\"abc\",2,1E300)"), text("bc"));
+    assert_eq!(eval("=MID(\"abc\",1E300,2)"), text(""));
+}
+
+#[test]
+fn functions_over_ranges() {
+    assert_eq!(eval("=SUM(A1:A4)"), CellValue::Float(30.5));
+    assert_eq!(eval("=SUM(A:A, 1)"), CellValue::Float(31.5));
+    assert_eq!(eval("=COUNT(A1:A10)"), CellValue::Int(3));
+    assert_eq!(eval("=COUNTA(A1:A10)"), CellValue::Int(4));
+    assert_eq!(eval("=MAX(A1:A4)"), CellValue::Int(20));
+    assert_eq!(eval("=AVERAGE(A1,A2)"), CellValue::Int(15));
+    assert_eq!(eval("=UPPER(LEFT(A3,2))"), text("TE"));
+}
+
+#[test]
+fn error_values() {
+    assert_eq!(eval("=1/0"), CellValue::Error(ErrorKind::Div0));
+    assert_eq!(eval("=A3+1"), CellValue::Error(ErrorKind::Value));
+    assert_eq!(eval("=\"inf\"+1"), CellValue::Error(ErrorKind::Value));
+    assert_eq!(eval("=\"1e400\"*1"), CellValue::Error(ErrorKind::Value));
+    assert_eq!(eval("=\" 12 \"+1"), CellValue::Int(13));
+    assert_eq!(eval("=NOPE(1)"), CellValue::Error(ErrorKind::Name));
+    assert_eq!(eval("=foo+1"), CellValue::Error(ErrorKind::Name));
+    assert_eq!(eval("=NA()"), CellValue::Error(ErrorKind::NA));
+    assert_eq!(eval("=SQRT(-1)"), CellValue::Error(ErrorKind::Num));
+    assert_eq!(eval("=AVERAGE(B1:B5)"), CellValue::Error(ErrorKind::Div0));
+    assert_eq!(eval("=A1:A2"), CellValue::Error(ErrorKind::Value));
+}
+
+#[test]
+fn errors_propagate() {
+    let grid = grid_with(
+        &[("A1", CellValue::Error(ErrorKind::Ref))],
+        &[
+            ("B1", "=A1*2"),
+            ("B2", "=SUM(A1:A5)"),
+            ("B3", "=IFERROR(B1,-1)"),
+            ("B4", "=ISERROR(B2)"),
+            ("B5", "=#N/A&\"x\""),
+        ],
+    );
+
+    assert_eq!(grid.get_value(cell("B1")), CellValue::Error(ErrorKind::Ref));
+    assert_eq!(grid.get_value(cell("B2")), CellValue::Error(ErrorKind::Ref));
+    assert_eq!(grid.get_value(cell("B3")), CellValue::Int(-1));
+    assert_eq!(grid.get_value(cell("B4")), CellValue::Bool(true));
+    assert_eq!(grid.get_value(cell("B5")), CellValue::Error(ErrorKind::NA));
+}
+
+#[test]
+fn formulas_see_computed_precedents() {
+    let grid = grid_with(
+        &[("A1", CellValue::Int(2))],
+        &[("C1", "=B1*10"), ("B1", "=A1+1"), ("D1", "=SUM(B1:C1)")],
+    );
+
+    assert_eq!(grid.get_value(cell("B1")), CellValue::Int(3));
+    assert_eq!(grid.get_value(cell("C1")), CellValue::Int(30));
+    assert_eq!(grid.get_value(cell("D1")), CellValue::Int(33));
+    assert_eq!(
+        grid.get_formula(cell("D1")).unwrap().to_string(),
+        "=SUM(B1:C1)"
+    );
+}
@@ -0,0 +1,188 @@
+use wasm_spreadsheet::{
+    extend_series, fill, fill_down_extent, fill_target, Area, CellRef, CellValue, DateTime, Grid,
+};
+
+fn cell(s: &str) -> CellRef {
+    s.parse().unwrap()
+}
+
+fn area(s: &str) -> Area {
+    s.parse().unwrap()
+}
+
+fn text(s: &str) -> CellValue {
+    CellValue::String(s.to_string())
+}
+
+fn date(year: i32, month: u32, day: u32) -> CellValue {
+    CellValue::DateTime(DateTime::from_ymd(year, month, day).unwrap())
+}
+
+#[test]
+fn numbers_continue_their_step_ratio_or_trend() {
+    let ints = |v: &[i64]| v.iter().map(|n| CellValue::Int(*n)).collect::<Vec<_>>();
+    assert_eq!(extend_series(&ints(&[1]), 2, false), None);
+    assert_eq!(
+        extend_series(&ints(&[1, 3]), 3, false),
+        Some(ints(&[5, 7, 9]))
+    );
+    assert_eq!(
+        extend_series(&ints(&[10, 8]), 2, false),
+        Some(ints(&[6, 4]))
+    );
+    assert_eq!(
+        extend_series(&ints(&[2, 6, 18]), 2, false),
+        Some(ints(&[54, 162]))
+    );
+    // No constant step or ratio: the least-squares trend.
+    assert_eq!(
+        extend_series(&ints(&[1, 2, 4, 5]), 1, false),
+        Some(vec![CellValue::Float(6.5)])
+    );
+    assert_eq!(
+        extend_series(&[CellValue::Float(0.5), CellValue::Int(1)], 1, false),
+        Some(vec![CellValue::Float(1.5)])
+    );
+    // Mixed kinds repeat.
+    assert_eq!(
+        extend_series(&[CellValue::Int(1), text("a")], 2, false),
+        None
+    );
+}
+
+#[test]
+fn dates_step_by_day_week_or_month() {
+    assert_eq!(
+        extend_series(&[date(2024, 2, 28)], 2, false),
+        Some(vec![date(2024, 2, 29), date(2024, 3, 1)])
+    );
+    assert_eq!(
+        extend_series(&[date(2024, 1, 1), date(2024, 1, 8)], 1, false),
+        Some(vec![date(2024, 1, 15)])
+    );
+    assert_eq!(
+        extend_series(&[date(2024, 1, 31), date(2024, 3, 31)], 2, false),
+        Some(vec![date(2024, 5, 31), date(2024, 7, 31)])
+    );
+    assert_eq!(
+        extend_series(&[date(2023, 11, 15), date(2023, 12, 15)], 2, false),
+        Some(vec![date(2024, 1, 15), date(2024, 2, 15)])
+    );
+}
+
+#[test]
+fn names_and_numbered_text_count_on() {
+    assert_eq!(
+        extend_series(&[text("Friday")], 2, false),
+        Some(vec![text("Saturday"), text("Sunday")])
+    );
+    assert_eq!(
+        extend_series(&[text("MON"), text("WED")], 2, false),
+        Some(vec![text("FRI"), text("SUN")])
+    );
+    assert_eq!(
+        extend_series(&[text("nov")], 2, false),
+        Some(vec![text("dec"), text("jan")])
+    );
+    assert_eq!(
+        extend_series(&[text("Item 1")], 2, false),
+        Some(vec![text("Item 2"), text("Item 3")])
+    );
+    assert_eq!(
+        extend_series(&[text("Q08"), text("Q10")], 1, false),
+        Some(vec![text("Q12")])
+    );
+    assert_eq!(extend_series(&[text("a1"), text("b2")], 1, false), None);
+    assert_eq!(extend_series(&[text("plain")], 1, false), None);
+    // Numbers past i64 repeat rather than overflow.
+    let max = text("Item 9223372036854775807");
+    assert_eq!(extend_series(std::slice::from_ref(&max), 1, false), None);
+    assert_eq!(
+        extend_series(&[text("Item 0"), max.clone()], 1, false),
+        None
+    );
+    let near = [
+        text("Item 9223372036854775805"),
+        text("Item 9223372036854775806"),
+    ];
+    assert_eq!(extend_series(&near, 1, false), Some(vec![max]));
+    assert_eq!(extend_series(&near, 2, false), None);
+    assert_eq!(
+        extend_series(&[text("Jan")], 1, true),
+        Some(vec![text("Dec")])
+    );
+}
+
+#[test]
+fn fill_repeats_or_extends_each_line() {
+    let mut grid = Grid::new(10, 5);
+    grid.set_input(cell("A1"), "1").unwrap();
+    grid.set_input(cell("A2"), "2").unwrap();
+    grid.set_input(cell("B1"), "x").unwrap();
+    grid.set_input(cell("B2"), "y").unwrap();
+    grid.set_input(cell("C1"), "=A1*10").unwrap();
+    grid.set_input(cell("D5"), "stale").unwrap();
+    fill(&mut grid, &area("A1:D2"), &area("A1:D5"));
+    grid.recalculate();
+
+    let column = |c: &str| -> Vec<CellValue> {
+        (1..=5)
+            .map(|r| grid.get_value(cell(&format!("{c}{r}"))))
+            .collect()
+    };
+    assert_eq!(column("A"), (1..=5).map(CellValue::Int).collect::<Vec<_>>());
+    assert_eq!(
+        column("B"),
+        vec![text("x"), text("y"), text("x"), text("y"), text("x")]
+    );
+    // The formula and the blank under it repeat.
+    assert_eq!(grid.edit_text(cell("C3")), "=A3*10");
+    assert_eq!(grid.get_value(cell("C4")), CellValue::Empty);
+    assert_eq!(grid.get_value(cell("C5")), CellValue::Int(50));
+    // What was in the way is cleared.
+    assert_eq!(grid.get_value(cell("D5")), CellValue::Empty);
+}
+
+#[test]
+fn filling_up_or_left_runs_the_series_backwards() {
+    let mut grid = Grid::new(10, 10);
+    grid.set_input(cell("A5"), "3").unwrap();
+    grid.set_input(cell("A6"), "4").unwrap();
+    fill(&mut grid, &area("A5:A6"), &area("A3:A6"));
+    assert_eq!(grid.get_value(cell("A4")), CellValue::Int(2));
+    assert_eq!(grid.get_value(cell("A3")), CellValue::Int(1));
+
+    grid.set_input(cell("B9"), "Item 9223372036854775807")
+        .unwrap();
+    fill(&mut grid, &area("B9"), &area("B9:B10"));
+    assert_eq!(
+        grid.get_value(cell("B10")),
+        text("Item 9223372036854775807")
+    );
+
+    grid.set_input(cell("E1"), "Item 5").unwrap();
+    fill(&mut grid, &area("E1"), &area("C1:E1"));
+    assert_eq!(grid.get_value(cell("D1")), text("Item 4"));
+    assert_eq!(grid.get_value(cell("C1")), text("Item 3"));
+}
+
+#[test]
+fn drag_target_and_double_click_extent() {
+    let source = area("B2:C3");
+    assert_eq!(fill_target(&source, cell("C3")), source);
+    assert_eq!(fill_target(&source, cell("D9")), area("B2:C9"));
+    assert_eq!(fill_target(&source, cell("H4")), area("B2:H3"));
+    assert_eq!(fill_target(&source, cell("A1")), area("B1:C3"));
+
+    let mut grid = Grid::new(20, 5);
+    for row in 1..=6 {
+        grid.set_input(cell(&format!("A{row}")), "x").unwrap();
+    }
+    grid.set_input(cell("A8"), "after a gap").unwrap();
+    assert_eq!(fill_down_extent(&grid, &area("B1")), Some(5));
+    assert_eq!(fill_down_extent(&grid, &area("B1:B6")), None);
+    // Nothing on the left: the column on the right decides.
+    grid.set_input(cell("E1"), "1").unwrap();
+    grid.set_input(cell("E2"), "1").unwrap();
+    assert_eq!(fill_down_extent(&grid, &area("D1")), Some(1));
+}
@@ -0,0 +1,117 @@
+use wasm_spreadsheet::{CellRef, CellValue, ColumnType, DateTime, Grid, NumberFormat};
+
+fn fmt(code: &str, value: CellValue) -> String {
+    NumberFormat::parse(code).unwrap().format(&value).text
+}
+
+fn num(n: f64) -> CellValue {
+    CellValue::from_number(n)
+}
+
+#[test]
+fn formats_numbers_with_placeholders_and_grouping() {
+    assert_eq!(fmt("#,##0.00", num(1234.567)), "1,234.57");
+    assert_eq!(fmt("#,##0.00", num(-1234567.891)), "-1,234,567.89");
+    assert_eq!(fmt("0.00", num(0.5)), "0.50");
+    assert_eq!(fmt("#.##", num(0.5)), ".5");
+    assert_eq!(fmt(".00", num(12.5)), "12.50");
+    assert_eq!(fmt(".00", num(0.5)), ".50");
+    assert_eq!(fmt("000", num(7.0)), "007");
+    assert_eq!(fmt("0.0??", num(1.5)), "1.5  ");
+    assert_eq!(fmt("#,##0,", num(1234567.0)), "1,235");
+    assert_eq!(fmt("General", num(0.1 + 0.2)), "0.3");
+}
+
+#[test]
+fn formats_percent_scientific_and_literals() {
+    assert_eq!(fmt("0.0%", num(0.125)), "12.5%");
+    assert_eq!(fmt("0%", num(1.0)), "100%");
+    assert_eq!(fmt("0.00E+00", num(12345.0)), "1.23E+04");
+    assert_eq!(fmt("0.00E+00", num(0.000123)), "1.23E-04");
+    assert_eq!(fmt("0.00E+00", num(9.999)), "1.00E+01");
+    assert_eq!(fmt("##0.0E+0", num(12345.0)), "12.3E+3");
+    assert_eq!(fmt("0 \"kg\"", num(12.0)), "12 kg");
+    assert_eq!(fmt("\\$0.00", num(3.0)), "$3.00");
+    assert_eq!(fmt("000-0000", num(5551234.0)), "555-1234");
+}
+
+#[test]
+fn picks_sections_and_colors() {
+    let format = NumberFormat::parse("$#,##0;[Red]($#,##0)").unwrap();
+    let positive = format.format(&num(1234.0));
+    assert_eq!(positive.text, "$1,234");
+    assert_eq!(positive.color, None);
+    let negative = format.format(&num(-1234.0));
+    assert_eq!(negative.text, "($1,234)");
+    assert_eq!(negative.color.as_deref(), Some("Red"));
+
+    let format = "0.00;-0.00;\"zero\";\"text: \"@";
+    assert_eq!(fmt(format, num(0.0)), "zero");
+    assert_eq!(fmt(format, CellValue::String("abc".into())), "text: abc");
+    assert_eq!(fmt("0.0", num(-0.01)), "0.0");
+    assert_eq!(fmt("[<100]\"small\";\"big\"", num(5.0)), "small");
+    assert_eq!(fmt("[<100]\"small\";\"big\"", num(500.0)), "big");
+}
+
+#[test]
+fn formats_dates_and_times() {
+    let date = CellValue::DateTime(DateTime::from_ymd_hms(2023, 3, 5, 14, 7, 9).unwrap());
+    assert_eq!(fmt("yyyy-mm-dd", date.clone()), "2023-03-05");
+    assert_eq!(fmt("d/m/yy", date.clone()), "5/3/23");
+    assert_eq!(fmt("dddd, mmmm d", date.clone()), "Sunday, March 5");
+    assert_eq!(fmt("ddd mmm", date.clone()), "Sun Mar");
+    assert_eq!(fmt("hh:mm:ss", date.clone()), "14:07:09");
+    assert_eq!(fmt("h:mm AM/PM", date.clone()), "2:07 PM");
+    assert_eq!(fmt("yyyy-mm-dd", num(45000.0)), "2023-03-15");
+    // Serials past year 9999 or before year 1 cannot be shown as dates.
+    assert_eq!(fmt("yyyy-mm-dd", num(1e300)), "#####");
+    assert_eq!(fmt("dddd", num(-1e300)), "#####");
+    assert_eq!(DateTime::from_serial(1e300).to_string(), "#####");
+}
+
+#[test]
+fn leaves_text_booleans_and_errors_alone() {
+    assert_eq!(fmt("@", CellValue::String("hi".into())), "hi");
+    assert_eq!(fmt("0.00", CellValue::String("hi".into())), "hi");
+    assert_eq!(fmt("0.00", CellValue::Bool(true)), "TRUE");
+    assert_eq!(fmt("0.00", CellValue::Empty), "");
+    assert!(NumberFormat::parse("0.00\"").is_err());
+    assert!(NumberFormat::parse("[Purple]0").is_err());
+    assert!(NumberFormat::parse("0;0;0;@;0").is_err());
+}
+
+#[test]
+fn grid_applies_cell_then_column_format() {
+    let mut grid = Grid::new(5, 3);
+    let a1 = CellRef::new(0, 0);
+    let a2 = CellRef::new(1, 0);
+    grid.set_value(a1, num(1234.5)).unwrap();
+    grid.set_value(a2, num(0.25)).unwrap();
+    assert_eq!(grid.display_value(a1).text, "1234.5");
+
+    grid.set_column_format(0, Some("#,##0.00".parse().unwrap()))
+        .unwrap();
+    assert_eq!(grid.display_value(a1).text, "1,234.50");
+    grid.set_cell_format(a2, Some("0%".parse().unwrap()))
+        .unwrap();
+    assert_eq!(grid.display_value(a2).text, "25%");
+
+    // A format survives clearing the value, so typing later picks it up.
+    grid.set_value(a2, CellValue::Empty).unwrap();
+    grid.set_input(a2, "0.5").unwrap();
+    assert_eq!(grid.display_value(a2).text, "50%");
+    grid.set_cell_format(a2, None).unwrap();
+    assert_eq!(grid.display_value(a2).text, "0.50");
+}
+
+#[test]
+fn number_format_wins_over_column_type_display() {
+    let mut grid = Grid::new(5, 3);
+    let b1 = CellRef::new(0, 1);
+    grid.set_column_type(1, ColumnType::Percent).unwrap();
+    grid.set_input(b1, "12.5%").unwrap();
+    assert_eq!(grid.display_value(b1).text, "12.5%");
+    grid.set_cell_format(b1, Some("0.000".parse().unwrap()))
+        .unwrap();
+    assert_eq!(grid.display_value(b1).text, "0.125");
+}
@@ -0,0 +1,112 @@
+use wasm_spreadsheet::{BinaryOp, CellRef, Expr, Formula, UnaryOp};
+
+fn parse(text: &str) -> Expr {
+    Formula::parse(text).unwrap().expr().clone()
+}
+
+fn num(n: f64) -> Box<Expr> {
+    Box::new(Expr::Number(n))
+}
+
+#[test]
+fn parses_literals() {
+    assert_eq!(parse("=1.5e3"), Expr::Number(1500.0));
+    assert_eq!(
+        parse("=\"say \"\"hi\"\"\""),
+        Expr::Text("say \"hi\"".into())
+    );
+    assert_eq!(parse("=true"), Expr::Bool(true));
+    assert_eq!(parse("=$B$12"), Expr::Ref(CellRef::absolute(11, 1)));
+}
+
+#[test]
+fn follows_excel_precedence() {
+    // Multiplication before addition.
+    assert_eq!(
+        parse("=1+2*3"),
+        Expr::Binary(
+            BinaryOp::Add,
+            num(1.0),
+            Box::new(Expr::Binary(BinaryOp::Mul, num(2.0), num(3.0)))
+        )
+    );
+    // Negation binds tighter than exponentiation.
+    assert_eq!(
+        parse("=-2^2"),
+        Expr::Binary(
+            BinaryOp::Pow,
+            Box::new(Expr::Unary(UnaryOp::Minus, num(2.0))),
+            num(2.0)
+        )
+    );
+    // Percent applies before `^`, `&` binds looser than `+`, and
+    // comparisons are loosest of all.
+    assert_eq!(parse("=2^50%").to_string(), "2^50%");
+    assert_eq!(
+        parse("=1+2&3=\"33\""),
+        Expr::Binary(
+            BinaryOp::Eq,
+            Box::new(Expr::Binary(
+                BinaryOp::Concat,
+                Box::new(Expr::Binary(BinaryOp::Add, num(1.0), num(2.0))),
+                num(3.0)
+            )),
+            Box::new(Expr::Text("33".into()))
+        )
+    );
+}
+
+#[test]
+fn parses_ranges_and_calls() {
+    let expr = parse("=sum(A1:B10, C:C, 3:3, 7)");
+    let Expr::Call(name, args) = &expr else {
+        panic!("expected a call, got {:?}", expr);
+    };
+    assert_eq!(name, "SUM");
+    assert_eq!(args.len(), 4);
+    assert_eq!(expr.to_string(), "SUM(A1:B10,C:C,3:3,7)");
+    assert_eq!(expr.references().len(), 3);
+
+    assert_eq!(parse("=NOW()"), Expr::Call("NOW".into(), vec![]));
+    assert_eq!(parse("=foo"), Expr::Name("FOO".into()));
+}
+
+#[test]
+fn prints_back_what_was_typed() {
+    for text in [
+        "=(A1+B1)*2",
+        "=-A1%",
+        "=IF(A1>=10,\"big\",\"small\")",
+        "=A1&\" \"&B1",
+        "=$A$1:B2<>0",
+        "=1-(2-3)",
+    ] {
+        assert_eq!(Formula::parse(text).unwrap().to_string(), text);
+    }
+}
+
+#[test]
+fn reports_error_positions() {
+    let err = |text: &str| Formula::parse(text).unwrap_err();
+
+    assert_eq!(err("1+1").position, 0);
+    assert_eq!(err("=1+").position, 3);
+    assert_eq!(err("=(1+2").position, 5);
+    assert_eq!(err("=1+2)").position, 4);
+    assert_eq!(err("=SUM(1;2)").position, 6);
+    assert_eq!(err("=\"abc").position, 1);
+    assert_eq!(err("=A1:").position, 1);
+    assert_eq!(err("=ZZZZ1").position, 1);
+    assert_eq!(
+        err("=1 + 2 @ 3").to_string(),
+        "unexpected character `@` at position 7"
+    );
+    assert_eq!(
+        err("=1 + 1e400").to_string(),
+        "invalid number `1e400` at position 5"
+    );
+    assert_eq!(
+        err("=1 + #BOGUS!").to_string(),
+        "unknown error value at position 5"
+    );
+}
@@ -0,0 +1,143 @@
+use wasm_spreadsheet::{CellObject, CellRef, CellValue, Grid, GridError};
+
+#[test]
+fn grid_builds_without_a_canvas() {
+    let grid = Grid::new(12, 350);
+
+    assert_eq!(grid.num_rows(), 12);
+    assert_eq!(grid.num_cols(), 350);
+    assert_eq!(grid.get_width(), 350.0 * 80.0);
+    assert_eq!(grid.get_height(), 12.0 * 30.0);
+}
+
+#[test]
+fn column_names() {
+    let grid = Grid::new(1, 703);
+    let name = |col| grid.get_column(col).unwrap().get_column_name();
+
+    assert_eq!(name(0), "A");
+    assert_eq!(name(25), "Z");
+    assert_eq!(name(26), "AA");
+    assert_eq!(name(349), "ML");
+    assert_eq!(name(702), "AAA");
+}
+
+#[test]
+fn new_grid_stores_no_cells() {
+    let grid = Grid::new(3, 3);
+
+    assert!(grid.cells().is_empty());
+    assert_eq!(grid.get_cell(1, 2), None);
+}
+
+#[test]
+fn sparse_store_only_keeps_content() {
+    let mut grid = Grid::new(1_048_576, 16_384);
+    let value = CellValue::Int(42);
+
+    grid.insert_cell(CellObject::with_value(16_383, 1_048_575, value.clone()));
+    grid.insert_cell(CellObject::new(3, 3));
+
+    assert_eq!(grid.cells().len(), 1);
+    let cell = grid.get_cell(1_048_575, 16_383).unwrap();
+    assert_eq!(cell.get_value(), value);
+
+    grid.insert_cell(CellObject::new(16_383, 1_048_575));
+    assert!(grid.cells().is_empty());
+}
+
+#[test]
+fn out_of_bounds_cells_are_dropped() {
+    let mut grid = Grid::new(2, 2);
+
+    grid.insert_cell(CellObject::with_value(2, 0, CellValue::Int(1)));
+
+    assert!(grid.cells().is_empty());
+}
+
+#[test]
+fn store_slices_by_row_and_column() {
+    let mut grid = Grid::new(10, 10);
+    for (row, col) in [(0, 0), (0, 4), (3, 4), (5, 1)] {
+        let value = CellValue::Int((row * 10 + col) as i64);
+        grid.insert_cell(CellObject::with_value(col, row, value));
+    }
+
+    let row: Vec<_> = grid.cells().row(0).map(|c| c.column_id).collect();
+    let column: Vec<_> = grid.cells().column(4).map(|c| c.row_id).collect();
+
+    assert_eq!(row, vec![0, 4]);
+    assert_eq!(column, vec![0, 3]);
+}
+
+#[test]
+fn set_and_get_values_by_reference() {
+    let mut grid = Grid::new(10, 10);
+    let b2: CellRef = "B2".parse().unwrap();
+
+    assert_eq!(grid.get_value(b2), CellValue::Empty);
+
+    grid.set_value(b2, CellValue::Float(1.5)).unwrap();
+    assert_eq!(grid.get_value(b2), CellValue::Float(1.5));
+    assert_eq!(
+        grid.get_cell(1, 1).unwrap().get_value(),
+        CellValue::Float(1.5)
+    );
+
+    grid.set_value(b2, CellValue::Empty).unwrap();
+    assert!(grid.cells().is_empty());
+}
+
+#[test]
+fn set_value_outside_grid_fails() {
+    let mut grid = Grid::new(10, 10);
+    let k1: CellRef = "K1".parse().unwrap();
+
+    assert_eq!(
+        grid.set_value(k1, CellValue::Int(1)),
+        Err(GridError::OutOfBounds(k1))
+    );
+}
+
+#[test]
+fn grid_grows_up_to_its_size_limit() {
+    let mut grid = Grid::new(10, 5);
+    grid.set_column_width(4, 120.0).unwrap();
+    grid.set_input("A1".parse().unwrap(), "=E10").unwrap();
+
+    assert!(grid.grow_to(20, 8));
+    assert!(!grid.grow_to(15, 6));
+    assert_eq!((grid.num_rows(), grid.num_cols()), (20, 8));
+    assert_eq!(grid.get_column(7).unwrap().column_id, 7);
+    assert_eq!(grid.column_width(4), 120.0);
+    assert_eq!(grid.edit_text("A1".parse().unwrap()), "=E10");
+    grid.set_value("H20".parse().unwrap(), CellValue::Int(1))
+        .unwrap();
+
+    assert_eq!(grid.set_size_limit(10, 100), Err(GridError::SizeLimit));
+    grid.set_size_limit(30, 10).unwrap();
+    assert!(grid.grow_to(1_000, 1_000));
+    assert_eq!((grid.num_rows(), grid.num_cols()), (30, 10));
+    assert!(!grid.grow_to(31, 11));
+    assert_eq!(grid.insert_rows(0, 1), Err(GridError::SizeLimit));
+}
+
+#[test]
+fn used_range_bounds_the_values() {
+    let mut grid = Grid::new(20, 10);
+    assert_eq!(grid.used_range(), None);
+
+    grid.set_input("C4".parse().unwrap(), "x").unwrap();
+    assert_eq!(grid.used_range().unwrap().to_string(), "C4");
+
+    grid.set_input("B9".parse().unwrap(), "1").unwrap();
+    grid.set_input("F2".parse().unwrap(), "=B9").unwrap();
+    assert_eq!(grid.used_range().unwrap().to_string(), "B2:F9");
+
+    // Clearing a cell gives its space back.
+    grid.set_value("B9".parse().unwrap(), CellValue::Empty)
+        .unwrap();
+    grid.set_value("F2".parse().unwrap(), CellValue::Empty)
+        .unwrap();
+    assert_eq!(grid.used_range().unwrap().to_string(), "C4");
+}
@@ -0,0 +1,199 @@
+use wasm_spreadsheet::{
+    paste_commands, Area, CellRef, CellValue, ColumnType, Command, Grid, History, MAX_UNDO_STEPS,
+};
+
+fn cell(s: &str) -> CellRef {
+    s.parse().unwrap()
+}
+
+fn area(s: &str) -> Area {
+    s.parse().unwrap()
+}
+
+fn input(cell_name: &str, text: &str) -> Command {
+    Command::SetInput {
+        cell: cell(cell_name),
+        text: text.to_string(),
+    }
+}
+
+#[test]
+fn undo_and_redo_cell_edits() {
+    let mut grid = Grid::new(5, 5);
+    let mut history = History::new();
+    assert!(!history.can_undo());
+
+    history.execute(&mut grid, input("A1", "1")).unwrap();
+    history.execute(&mut grid, input("A2", "=A1*10")).unwrap();
+    history.execute(&mut grid, input("A1", "2")).unwrap();
+    grid.recalculate();
+    assert_eq!(grid.get_value(cell("A2")), CellValue::Int(20));
+
+    assert!(history.undo(&mut grid));
+    grid.recalculate();
+    assert_eq!(grid.get_value(cell("A2")), CellValue::Int(10));
+
+    assert!(history.undo(&mut grid));
+    grid.recalculate();
+    assert_eq!(grid.get_formula(cell("A2")), None);
+    assert!(history.can_redo());
+
+    assert!(history.redo(&mut grid));
+    grid.recalculate();
+    assert_eq!(grid.edit_text(cell("A2")), "=A1*10");
+    assert_eq!(grid.get_value(cell("A2")), CellValue::Int(10));
+
+    // A new edit forgets what was undone.
+    history.execute(&mut grid, input("B1", "x")).unwrap();
+    assert!(!history.can_redo());
+    assert!(!history.redo(&mut grid));
+}
+
+#[test]
+fn a_paste_is_undone_in_one_step() {
+    let mut grid = Grid::new(5, 5);
+    let mut history = History::new();
+    history.execute(&mut grid, input("B2", "keep")).unwrap();
+
+    let rows = vec![
+        vec!["1".to_string(), "2".to_string()],
+        vec!["3".to_string(), "4".to_string()],
+    ];
+    history.begin_group();
+    for command in paste_commands(&grid, cell("A1"), &rows) {
+        history.execute(&mut grid, command).unwrap();
+    }
+    history.end_group();
+    assert_eq!(grid.get_value(cell("B2")), CellValue::Int(4));
+
+    history.undo(&mut grid);
+    assert_eq!(grid.get_value(cell("A1")), CellValue::Empty);
+    assert_eq!(grid.get_value(cell("B2")), CellValue::String("keep".into()));
+    assert!(history.can_undo());
+
+    history.redo(&mut grid);
+    assert_eq!(grid.get_value(cell("A2")), CellValue::Int(3));
+    assert_eq!(grid.get_value(cell("B2")), CellValue::Int(4));
+}
+
+#[test]
+fn failed_commands_are_not_recorded() {
+    let mut grid = Grid::new(5, 5);
+    let mut history = History::new();
+    history
+        .execute(
+            &mut grid,
+            Command::SetColumnType {
+                column: 0,
+                column_type: ColumnType::Int,
+            },
+        )
+        .unwrap();
+    assert!(history.execute(&mut grid, input("A1", "abc")).is_err());
+    assert!(history.execute(&mut grid, input("Z99", "1")).is_err());
+
+    history.undo(&mut grid);
+    assert!(!history.can_undo());
+    assert_eq!(grid.column_type(0), ColumnType::General);
+}
+
+#[test]
+fn undoing_a_column_type_change_restores_the_old_values() {
+    let mut grid = Grid::new(5, 5);
+    let mut history = History::new();
+    history.execute(&mut grid, input("A1", "1.5")).unwrap();
+    history.execute(&mut grid, input("A2", "7")).unwrap();
+
+    let failed = history
+        .execute(
+            &mut grid,
+            Command::SetColumnType {
+                column: 0,
+                column_type: ColumnType::String,
+            },
+        )
+        .unwrap();
+    assert!(failed.is_empty());
+    assert_eq!(grid.get_value(cell("A1")), CellValue::String("1.5".into()));
+
+    history.undo(&mut grid);
+    assert_eq!(grid.column_type(0), ColumnType::General);
+    assert_eq!(grid.get_value(cell("A1")), CellValue::Float(1.5));
+    assert_eq!(grid.get_value(cell("A2")), CellValue::Int(7));
+}
+
+#[test]
+fn undo_restores_sizes_and_hidden_state() {
+    let mut grid = Grid::new(5, 5);
+    let mut history = History::new();
+    history
+        .execute(
+            &mut grid,
+            Command::SetColumnWidth {
+                column: 1,
+                width: 120.0,
+            },
+        )
+        .unwrap();
+    history
+        .execute(
+            &mut grid,
+            Command::SetColumnWidth {
+                column: 1,
+                width: 0.0,
+            },
+        )
+        .unwrap();
+    assert!(grid.column_widths().is_hidden(1));
+
+    history.undo(&mut grid);
+    assert!(!grid.column_widths().is_hidden(1));
+    assert_eq!(grid.column_width(1), 120.0);
+    history.undo(&mut grid);
+    assert_eq!(grid.column_width(1), grid.column_widths().default_size());
+}
+
+#[test]
+fn undoing_a_move_puts_references_back() {
+    let mut grid = Grid::new(10, 5);
+    let mut history = History::new();
+    history.execute(&mut grid, input("A1", "4")).unwrap();
+    history.execute(&mut grid, input("B1", "=A1*2")).unwrap();
+    history.execute(&mut grid, input("D4", "old")).unwrap();
+    history
+        .execute(
+            &mut grid,
+            Command::MoveArea {
+                source: area("A1"),
+                dest: cell("D4"),
+            },
+        )
+        .unwrap();
+    grid.recalculate();
+    assert_eq!(grid.edit_text(cell("B1")), "=D4*2");
+    assert_eq!(grid.get_value(cell("B1")), CellValue::Int(8));
+
+    history.undo(&mut grid);
+    grid.recalculate();
+    assert_eq!(grid.edit_text(cell("B1")), "=A1*2");
+    assert_eq!(grid.get_value(cell("A1")), CellValue::Int(4));
+    assert_eq!(grid.get_value(cell("D4")), CellValue::String("old".into()));
+    assert_eq!(grid.get_value(cell("B1")), CellValue::Int(8));
+}
+
+#[test]
+fn only_the_latest_steps_are_kept() {
+    let mut grid = Grid::new(5, 5);
+    let mut history = History::new();
+    for i in 0..MAX_UNDO_STEPS + 5 {
+        history
+            .execute(&mut grid, input("A1", &i.to_string()))
+            .unwrap();
+    }
+    let mut undone = 0;
+    while history.undo(&mut grid) {
+        undone += 1;
+    }
+    assert_eq!(undone, MAX_UNDO_STEPS);
+    assert_eq!(grid.get_value(cell("A1")), CellValue::Int(4));
+}
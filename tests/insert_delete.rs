@@ -0,0 +1,155 @@
+use wasm_spreadsheet::{
+    Area, CellRef, CellValue, ColumnType, Command, ErrorKind, Grid, GridError, History,
+};
+
+fn cell(s: &str) -> CellRef {
+    s.parse().unwrap()
+}
+
+fn area(s: &str) -> Area {
+    s.parse().unwrap()
+}
+
+#[test]
+fn areas_follow_inserted_and_deleted_rows() {
+    let a = area("$B$2:C5");
+    assert_eq!(a.insert_rows(1, 2), Some(area("$B$4:C7")));
+    // Inserting inside the area stretches it.
+    assert_eq!(a.insert_rows(3, 2), Some(area("$B$2:C7")));
+    assert_eq!(a.insert_rows(5, 2), Some(a));
+    assert_eq!(area("A:A").insert_rows(0, 5), Some(area("A:A")));
+
+    assert_eq!(a.delete_rows(0, 1), Some(area("$B$1:C4")));
+    assert_eq!(a.delete_rows(2, 2), Some(area("$B$2:C3")));
+    // A deleted corner closes in on what is left.
+    assert_eq!(a.delete_rows(0, 3), Some(area("$B$1:C2")));
+    assert_eq!(a.delete_rows(4, 5), Some(area("$B$2:C4")));
+    assert_eq!(a.delete_rows(1, 4), None);
+
+    assert_eq!(a.insert_columns(0, 1), Some(area("$C$2:D5")));
+    assert_eq!(a.delete_columns(1, 2), None);
+}
+
+#[test]
+fn inserting_rows_moves_cells_and_references() {
+    let mut grid = Grid::new(5, 3);
+    grid.set_input(cell("A1"), "1").unwrap();
+    grid.set_input(cell("A2"), "2").unwrap();
+    grid.set_input(cell("B3"), "=SUM(A1:A2)+$A$2").unwrap();
+    grid.set_row_height(1, 50.0).unwrap();
+
+    grid.insert_rows(1, 2).unwrap();
+    grid.recalculate();
+    assert_eq!(grid.num_rows(), 7);
+    assert_eq!(grid.get_value(cell("A4")), CellValue::Int(2));
+    assert_eq!(grid.get_value(cell("A2")), CellValue::Empty);
+    assert_eq!(grid.row_height(3), 50.0);
+    assert_eq!(grid.edit_text(cell("B5")), "=SUM(A1:A4)+$A$4");
+    assert_eq!(grid.get_value(cell("B5")), CellValue::Int(5));
+    let stored = grid.get_cell(4, 1).unwrap();
+    assert_eq!((stored.row_id, stored.column_id), (4, 1));
+}
+
+#[test]
+fn deleting_referenced_cells_gives_ref_errors() {
+    let mut grid = Grid::new(5, 3);
+    grid.set_input(cell("A1"), "1").unwrap();
+    grid.set_input(cell("A2"), "2").unwrap();
+    grid.set_input(cell("A3"), "3").unwrap();
+    grid.set_input(cell("B1"), "=A2*10").unwrap();
+    grid.set_input(cell("B2"), "=SUM(A1:A3)").unwrap();
+    grid.set_input(cell("C5"), "=A3").unwrap();
+
+    grid.delete_rows(1, 1).unwrap();
+    grid.recalculate();
+    assert_eq!(grid.num_rows(), 4);
+    assert_eq!(grid.edit_text(cell("B1")), "=#REF!*10");
+    assert_eq!(grid.get_value(cell("B1")), CellValue::Error(ErrorKind::Ref));
+    // B2 itself was deleted; C5 moved up and still finds A3's value.
+    assert_eq!(grid.edit_text(cell("C4")), "=A2");
+    assert_eq!(grid.get_value(cell("C4")), CellValue::Int(3));
+    assert_eq!(grid.get_value(cell("A2")), CellValue::Int(3));
+}
+
+#[test]
+fn columns_carry_their_type_and_width() {
+    let mut grid = Grid::new(3, 4);
+    grid.set_column_type(2, ColumnType::Percent).unwrap();
+    grid.set_column_width(2, 120.0).unwrap();
+    grid.set_input(cell("C1"), "50%").unwrap();
+    grid.set_input(cell("D1"), "=C1*2").unwrap();
+
+    grid.insert_columns(0, 1).unwrap();
+    grid.recalculate();
+    assert_eq!(grid.num_cols(), 5);
+    assert_eq!(grid.column_type(3), ColumnType::Percent);
+    assert_eq!(grid.get_column(3).unwrap().column_id, 3);
+    assert_eq!(grid.column_width(3), 120.0);
+    assert_eq!(grid.edit_text(cell("E1")), "=D1*2");
+
+    grid.delete_columns(3, 1).unwrap();
+    grid.recalculate();
+    assert_eq!(grid.column_type(3), ColumnType::General);
+    assert_eq!(grid.edit_text(cell("D1")), "=#REF!*2");
+    assert_eq!(grid.get_value(cell("D1")), CellValue::Error(ErrorKind::Ref));
+}
+
+#[test]
+fn bounds_and_limits_are_checked() {
+    let mut grid = Grid::new(3, 3);
+    assert_eq!(
+        grid.insert_rows(4, 1),
+        Err(GridError::OutOfBounds(CellRef::new(4, 0)))
+    );
+    assert_eq!(
+        grid.delete_columns(2, 2),
+        Err(GridError::OutOfBounds(CellRef::new(0, 2)))
+    );
+    assert_eq!(grid.delete_rows(0, 3), Err(GridError::DeleteAll));
+    assert_eq!(grid.insert_columns(3, 16_382), Err(GridError::SizeLimit));
+    // Appending at the end is allowed.
+    grid.insert_rows(3, 1).unwrap();
+    assert_eq!(grid.num_rows(), 4);
+}
+
+#[test]
+fn undo_restores_deleted_rows_and_formulas() {
+    let mut grid = Grid::new(5, 3);
+    let mut history = History::new();
+    let input = |cell_name: &str, text: &str| Command::SetInput {
+        cell: cell(cell_name),
+        text: text.to_string(),
+    };
+    history.execute(&mut grid, input("A2", "7")).unwrap();
+    history.execute(&mut grid, input("B1", "=A2+1")).unwrap();
+    history
+        .execute(
+            &mut grid,
+            Command::SetRowHeight {
+                row: 1,
+                height: 0.0,
+            },
+        )
+        .unwrap();
+
+    history
+        .execute(&mut grid, Command::DeleteRows { at: 1, count: 1 })
+        .unwrap();
+    grid.recalculate();
+    assert_eq!(grid.edit_text(cell("B1")), "=#REF!+1");
+
+    history.undo(&mut grid);
+    grid.recalculate();
+    assert_eq!(grid.num_rows(), 5);
+    assert_eq!(grid.edit_text(cell("B1")), "=A2+1");
+    assert_eq!(grid.get_value(cell("B1")), CellValue::Int(8));
+    assert!(grid.row_heights().is_hidden(1));
+
+    history
+        .execute(&mut grid, Command::InsertColumns { at: 0, count: 2 })
+        .unwrap();
+    assert_eq!(grid.edit_text(cell("D1")), "=C2+1");
+    history.undo(&mut grid);
+    assert_eq!(grid.num_cols(), 3);
+    assert_eq!(grid.edit_text(cell("B1")), "=A2+1");
+}
@@ -0,0 +1,126 @@
+use wasm_spreadsheet::{
+    layout_cell, CellRef, CellStyle, CellValue, Grid, HAlign, Overflow, TextLayout, TextMeasure,
+    VAlign, Viewport,
+};
+
+// Every character is 10px wide.
+struct Monospace;
+
+impl TextMeasure for Monospace {
+    fn text_width(&self, text: &str) -> f64 {
+        text.chars().count() as f64 * 10.0
+    }
+}
+
+fn cell(s: &str) -> CellRef {
+    s.parse().unwrap()
+}
+
+fn layout(grid: &Grid, at: &str) -> Option<TextLayout> {
+    let viewport = Viewport::new(800.0, 600.0);
+    layout_cell(grid, &viewport, cell(at), &Monospace)
+}
+
+fn style(h_align: HAlign, overflow: Overflow) -> CellStyle {
+    CellStyle {
+        h_align,
+        overflow,
+        ..CellStyle::default()
+    }
+}
+
+#[test]
+fn general_alignment_depends_on_the_value() {
+    let mut grid = Grid::new(5, 5);
+    grid.set_input(cell("A1"), "abc").unwrap();
+    grid.set_input(cell("A2"), "42").unwrap();
+    grid.set_input(cell("A3"), "TRUE").unwrap();
+
+    // Columns are 80px wide with 4px padding.
+    assert_eq!(layout(&grid, "A1").unwrap().x, 4.0);
+    assert_eq!(layout(&grid, "A2").unwrap().x, 80.0 - 4.0 - 20.0);
+    assert_eq!(layout(&grid, "A3").unwrap().x, (80.0 - 40.0) / 2.0);
+    assert_eq!(layout(&grid, "A4"), None);
+}
+
+#[test]
+fn explicit_alignment() {
+    let mut grid = Grid::new(5, 5);
+    grid.set_input(cell("B2"), "42").unwrap();
+    let mut s = style(HAlign::Left, Overflow::Overflow);
+    s.v_align = VAlign::Top;
+    grid.set_cell_style(cell("B2"), s).unwrap();
+
+    let text = layout(&grid, "B2").unwrap();
+    assert_eq!(text.x, 84.0);
+    assert_eq!(text.y, 30.0 + 4.0 + 7.0);
+    assert_eq!(text.clip, (80.0, 30.0, 80.0, 30.0));
+
+    s.v_align = VAlign::Middle;
+    s.h_align = HAlign::Center;
+    grid.set_cell_style(cell("B2"), s).unwrap();
+    let text = layout(&grid, "B2").unwrap();
+    assert_eq!((text.x, text.y), (80.0 + 30.0, 45.0));
+}
+
+#[test]
+fn long_text_spills_into_empty_neighbours() {
+    let mut grid = Grid::new(5, 5);
+    grid.set_input(cell("A1"), "a fairly long label").unwrap();
+    let text = layout(&grid, "A1").unwrap();
+    assert_eq!(text.text, "a fairly long label");
+    assert_eq!(text.clip, (0.0, 0.0, 240.0, 30.0));
+
+    // A non-empty neighbour stops the spill.
+    grid.set_input(cell("C1"), "x").unwrap();
+    assert_eq!(layout(&grid, "A1").unwrap().clip, (0.0, 0.0, 160.0, 30.0));
+    grid.set_input(cell("B1"), "y").unwrap();
+    assert_eq!(layout(&grid, "A1").unwrap().clip, (0.0, 0.0, 80.0, 30.0));
+
+    // Right-aligned text spills to the left.
+    grid.set_input(cell("D2"), "right aligned text").unwrap();
+    grid.set_cell_style(cell("D2"), style(HAlign::Right, Overflow::Overflow))
+        .unwrap();
+    let text = layout(&grid, "D2").unwrap();
+    assert_eq!(text.clip, (80.0, 30.0, 240.0, 30.0));
+}
+
+#[test]
+fn clip_and_ellipsis_keep_text_inside_the_cell() {
+    let mut grid = Grid::new(5, 5);
+    grid.set_input(cell("A1"), "a fairly long label").unwrap();
+    grid.set_cell_style(cell("A1"), style(HAlign::General, Overflow::Clip))
+        .unwrap();
+    let text = layout(&grid, "A1").unwrap();
+    assert_eq!(text.text, "a fairly long label");
+    assert_eq!(text.clip, (0.0, 0.0, 80.0, 30.0));
+
+    grid.set_cell_style(cell("A1"), style(HAlign::General, Overflow::Ellipsis))
+        .unwrap();
+    // 72px available: six characters and the ellipsis.
+    assert_eq!(layout(&grid, "A1").unwrap().text, "a fair…");
+}
+
+#[test]
+fn numbers_that_do_not_fit_show_hashes() {
+    let mut grid = Grid::new(5, 5);
+    grid.set_value(cell("A1"), CellValue::Int(1234567890))
+        .unwrap();
+    let text = layout(&grid, "A1").unwrap();
+    assert_eq!(text.text, "#######");
+    assert_eq!(text.clip, (0.0, 0.0, 80.0, 30.0));
+
+    grid.set_value(cell("A2"), CellValue::Int(1234567)).unwrap();
+    assert_eq!(layout(&grid, "A2").unwrap().text, "1234567");
+}
+
+#[test]
+fn text_uses_the_number_format_and_its_color() {
+    let mut grid = Grid::new(5, 5);
+    grid.set_value(cell("A1"), CellValue::Int(-5)).unwrap();
+    grid.set_cell_format(cell("A1"), Some("0;[Red](0)".parse().unwrap()))
+        .unwrap();
+    let text = layout(&grid, "A1").unwrap();
+    assert_eq!(text.text, "(5)");
+    assert_eq!(text.color.as_deref(), Some("Red"));
+}
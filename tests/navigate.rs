@@ -0,0 +1,108 @@
+use wasm_spreadsheet::{navigate, CellRef, Direction, Grid, Motion, Selection, Viewport};
+
+fn cell(s: &str) -> CellRef {
+    s.parse().unwrap()
+}
+
+fn go(grid: &Grid, from: &str, motion: Motion) -> String {
+    navigate(grid, cell(from), motion).to_string()
+}
+
+// B2:B4 and B7 hold data, as does E2.
+fn sample() -> Grid {
+    let mut grid = Grid::new(20, 10);
+    for at in ["B2", "B3", "B4", "B7", "E2"] {
+        grid.set_input(cell(at), "x").unwrap();
+    }
+    grid
+}
+
+#[test]
+fn arrows_step_and_stop_at_the_edges() {
+    let mut grid = sample();
+    assert_eq!(go(&grid, "B2", Motion::Step(Direction::Down)), "B3");
+    assert_eq!(go(&grid, "B2", Motion::Step(Direction::Right)), "C2");
+    assert_eq!(go(&grid, "A1", Motion::Step(Direction::Up)), "A1");
+    assert_eq!(go(&grid, "J20", Motion::Step(Direction::Right)), "J20");
+
+    // Hidden rows and columns are skipped.
+    grid.set_row_hidden(2, true).unwrap();
+    grid.set_column_hidden(2, true).unwrap();
+    assert_eq!(go(&grid, "B2", Motion::Step(Direction::Down)), "B4");
+    assert_eq!(go(&grid, "B2", Motion::Step(Direction::Right)), "D2");
+}
+
+#[test]
+fn ctrl_arrows_jump_across_data() {
+    let grid = sample();
+    // From inside a block to its end.
+    assert_eq!(go(&grid, "B2", Motion::Jump(Direction::Down)), "B4");
+    // From the end of a block to the next filled cell.
+    assert_eq!(go(&grid, "B4", Motion::Jump(Direction::Down)), "B7");
+    // From the last filled cell to the edge of the sheet.
+    assert_eq!(go(&grid, "B7", Motion::Jump(Direction::Down)), "B20");
+    assert_eq!(go(&grid, "B2", Motion::Jump(Direction::Right)), "E2");
+    assert_eq!(go(&grid, "A1", Motion::Jump(Direction::Left)), "A1");
+    assert_eq!(go(&grid, "B4", Motion::Jump(Direction::Up)), "B2");
+}
+
+#[test]
+fn home_end_and_paging() {
+    let grid = sample();
+    assert_eq!(go(&grid, "D2", Motion::RowStart), "A2");
+    assert_eq!(go(&grid, "A2", Motion::RowEnd), "E2");
+    assert_eq!(go(&grid, "C9", Motion::RowEnd), "C9");
+    assert_eq!(go(&grid, "D9", Motion::SheetStart), "A1");
+    assert_eq!(go(&grid, "A1", Motion::SheetEnd), "E7");
+    assert_eq!(go(&grid, "A1", Motion::Page(Direction::Down, 5)), "A6");
+    assert_eq!(go(&grid, "A18", Motion::Page(Direction::Down, 5)), "A20");
+    assert_eq!(go(&grid, "A3", Motion::Page(Direction::Up, 5)), "A1");
+}
+
+#[test]
+fn shift_moves_extend_from_the_focus() {
+    let mut selection = Selection::new(cell("B2"));
+    selection.move_to(cell("B3"), true);
+    selection.move_to(cell("C3"), true);
+    assert_eq!(selection.to_range().to_string(), "B2:C3");
+    assert_eq!(selection.focus(), cell("C3"));
+    assert_eq!(selection.active(), cell("B2"));
+
+    selection.move_to(cell("D4"), false);
+    assert_eq!(selection.to_range().to_string(), "D4");
+}
+
+#[test]
+fn tab_and_enter_cycle_inside_a_selection() {
+    let mut selection = Selection::new(cell("A1"));
+    assert!(!selection.advance(Direction::Right));
+
+    selection.extend_to(cell("B2"));
+    let mut visited = Vec::new();
+    for _ in 0..4 {
+        assert!(selection.advance(Direction::Right));
+        visited.push(selection.active().to_string());
+    }
+    assert_eq!(visited, ["B1", "A2", "B2", "A1"]);
+
+    assert!(selection.advance(Direction::Down));
+    assert_eq!(selection.active(), cell("A2"));
+    assert!(selection.advance(Direction::Up));
+    assert!(selection.advance(Direction::Up));
+    assert_eq!(selection.active(), cell("B2"));
+    assert_eq!(selection.to_range().to_string(), "A1:B2");
+}
+
+#[test]
+fn viewport_follows_the_active_cell() {
+    let grid = Grid::new(100, 100);
+    let mut viewport = Viewport::new(400.0, 300.0);
+
+    assert!(!viewport.scroll_into_view(&grid, cell("B2")));
+    assert!(viewport.scroll_into_view(&grid, cell("F20")));
+    assert_eq!(viewport.scroll_x(), 6.0 * 80.0 - 400.0);
+    assert_eq!(viewport.scroll_y(), 20.0 * 30.0 - 300.0);
+
+    assert!(viewport.scroll_into_view(&grid, cell("A1")));
+    assert_eq!((viewport.scroll_x(), viewport.scroll_y()), (0.0, 0.0));
+}
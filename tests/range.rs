@@ -0,0 +1,98 @@
+use wasm_spreadsheet::{Area, CellRange, CellRef, IterOrder, MAX_COLUMNS, MAX_ROWS};
+
+fn cell(s: &str) -> CellRef {
+    s.parse().unwrap()
+}
+
+fn area(s: &str) -> Area {
+    s.parse().unwrap()
+}
+
+#[test]
+fn parses_and_prints_areas() {
+    for s in ["A1:C10", "B:B", "$B:D", "3:3", "2:$5", "$A$1:B2", "C4"] {
+        assert_eq!(area(s).to_string(), s);
+    }
+
+    let columns = area("B:B");
+    assert_eq!((columns.top(), columns.bottom()), (0, MAX_ROWS - 1));
+    let rows = area("3:3");
+    assert_eq!((rows.left(), rows.right()), (0, MAX_COLUMNS - 1));
+
+    // Corners given out of order are normalised.
+    assert_eq!(area("C10:A1"), area("A1:C10"));
+    assert!("0:3".parse::<Area>().is_err());
+    assert!("A1:".parse::<Area>().is_err());
+}
+
+#[test]
+fn iterates_in_either_order() {
+    let block = area("A1:B2");
+
+    let rows: Vec<String> = block
+        .iter(IterOrder::RowMajor)
+        .map(|c| c.to_string())
+        .collect();
+    let cols: Vec<String> = block
+        .iter(IterOrder::ColumnMajor)
+        .map(|c| c.to_string())
+        .collect();
+
+    assert_eq!(rows, ["A1", "B1", "A2", "B2"]);
+    assert_eq!(cols, ["A1", "A2", "B1", "B2"]);
+    assert_eq!(
+        area("B:B").iter(IterOrder::ColumnMajor).count() as u32,
+        MAX_ROWS
+    );
+}
+
+#[test]
+fn intersection_and_containment() {
+    let a = area("A1:C10");
+
+    assert_eq!(a.intersect(&area("B5:E20")), Some(area("B5:C10")));
+    assert_eq!(a.intersect(&area("D1:D2")), None);
+    assert_eq!(a.intersect(&area("B:B")), Some(area("B1:B10")));
+    assert!(a.contains(cell("C10")));
+    assert!(!a.contains(cell("D1")));
+    assert!(area("3:3").contains_area(&area("A3:Z3")));
+}
+
+#[test]
+fn multi_area_ranges() {
+    let selection: CellRange = "A1:B2,B2:C3".parse().unwrap();
+
+    assert_eq!(selection.to_string(), "A1:B2,B2:C3");
+    assert_eq!(selection.bounding_box(), Some(area("A1:C3")));
+    assert_eq!(selection.iter(IterOrder::RowMajor).count(), 7);
+    assert!(selection.contains(cell("C3")));
+    assert!(!selection.contains(cell("A3")));
+
+    let other: CellRange = "B1:B10".parse().unwrap();
+    assert_eq!(selection.intersect(&other).to_string(), "B1:B2,B2:B3");
+}
+
+#[test]
+fn union_drops_covered_areas() {
+    let a: CellRange = "B2,D4".parse().unwrap();
+    let b: CellRange = "A1:C3".parse().unwrap();
+
+    let union = a.union(&b);
+    assert_eq!(union.to_string(), "D4,A1:C3");
+    assert!(union.contains_area(&area("A1:B2")));
+}
+
+#[test]
+fn containment_of_whole_columns_is_checked_by_bounds() {
+    let halves: CellRange = "A:H,I:XFD".parse().unwrap();
+    assert!(halves.contains_area(&area("A:XFD")));
+    assert!(halves.contains_area(&area("C5:K9")));
+
+    let gap: CellRange = "A:H,J:XFD".parse().unwrap();
+    assert!(!gap.contains_area(&area("A:XFD")));
+    assert!(gap.contains_area(&area("J1:K1048576")));
+
+    let quarters: CellRange = "A1:B2,C1:D2,A3:D4".parse().unwrap();
+    assert!(quarters.contains_area(&area("A1:D4")));
+    assert!(!quarters.contains_area(&area("A1:D5")));
+}
@@ -0,0 +1,73 @@
+use wasm_spreadsheet::{Area, CellRef, Selection};
+
+fn cell(s: &str) -> CellRef {
+    s.parse().unwrap()
+}
+
+fn areas(selection: &Selection) -> String {
+    selection.to_range().to_string()
+}
+
+#[test]
+fn click_selects_a_single_cell() {
+    let mut selection = Selection::default();
+    assert_eq!(areas(&selection), "A1");
+
+    selection.select_cell(cell("C4"));
+    assert_eq!(areas(&selection), "C4");
+    assert_eq!(selection.active(), cell("C4"));
+    assert!(selection.contains(cell("C4")));
+    assert!(!selection.contains(cell("C5")));
+}
+
+#[test]
+fn drag_and_shift_click_extend_from_the_anchor() {
+    let mut selection = Selection::new(cell("C4"));
+    selection.extend_to(cell("E6"));
+    assert_eq!(areas(&selection), "C4:E6");
+    // Extending the other way flips the area around the anchor.
+    selection.extend_to(cell("A2"));
+    assert_eq!(areas(&selection), "A2:C4");
+    assert_eq!(selection.active(), cell("C4"));
+    assert_eq!(selection.anchor(), cell("C4"));
+}
+
+#[test]
+fn ctrl_click_adds_areas() {
+    let mut selection = Selection::new(cell("A1"));
+    selection.extend_to(cell("B2"));
+    selection.add_cell(cell("D4"));
+    selection.extend_to(cell("D6"));
+
+    assert_eq!(areas(&selection), "A1:B2,D4:D6");
+    assert_eq!(selection.active(), cell("D4"));
+    assert_eq!(selection.current_area(), "D4:D6".parse::<Area>().unwrap());
+
+    selection.select_cell(cell("F1"));
+    assert_eq!(areas(&selection), "F1");
+}
+
+#[test]
+fn headers_select_whole_columns_and_rows() {
+    let mut selection = Selection::default();
+    selection.select_columns(1, 1, false);
+    assert_eq!(areas(&selection), "B:B");
+    assert_eq!(selection.active(), cell("B1"));
+
+    // Dragging across the header keeps selecting whole columns.
+    selection.extend_to(cell("D7"));
+    assert_eq!(areas(&selection), "B:D");
+
+    selection.select_rows(2, 2, true);
+    selection.extend_to(cell("A5"));
+    assert_eq!(areas(&selection), "B:D,3:5");
+    assert_eq!(selection.active(), cell("A3"));
+}
+
+#[test]
+fn select_range_replaces_everything() {
+    let mut selection = Selection::default();
+    selection.select_range(&"B2:C3,E5".parse().unwrap());
+    assert_eq!(areas(&selection), "B2:C3,E5");
+    assert_eq!(selection.active(), cell("B2"));
+}
@@ -0,0 +1,90 @@
+use std::cmp::Ordering;
+
+use wasm_spreadsheet::{CellValue, DateTime, ErrorKind, MAX_EXACT_INT};
+
+#[test]
+fn numbers_keep_precision() {
+    assert_eq!(CellValue::from_number(3.0), CellValue::Int(3));
+    assert_eq!(CellValue::from_number(0.1), CellValue::Float(0.1));
+    assert_eq!(
+        CellValue::from_number(f64::INFINITY),
+        CellValue::Error(ErrorKind::Num)
+    );
+    // Large IDs no longer overflow.
+    assert_eq!(CellValue::Int(9_007_199_254).to_string(), "9007199254");
+    assert_eq!(
+        CellValue::from_number(MAX_EXACT_INT as f64),
+        CellValue::Int(MAX_EXACT_INT)
+    );
+    assert_eq!(CellValue::Float(0.1 + 0.2).to_string(), "0.3");
+    assert_eq!(CellValue::Float(1234.5678).to_string(), "1234.5678");
+}
+
+#[test]
+fn sort_order_follows_spreadsheet_rules() {
+    let mut values = vec![
+        CellValue::Empty,
+        CellValue::Bool(true),
+        CellValue::String("b".into()),
+        CellValue::Error(ErrorKind::NA),
+        CellValue::Float(2.5),
+        CellValue::Bool(false),
+        CellValue::String("A".into()),
+        CellValue::Int(3),
+        CellValue::DateTime(DateTime::from_serial(1.0)),
+    ];
+    values.sort_by(|a, b| a.compare(b));
+
+    assert_eq!(
+        values,
+        vec![
+            CellValue::DateTime(DateTime::from_serial(1.0)),
+            CellValue::Float(2.5),
+            CellValue::Int(3),
+            CellValue::String("A".into()),
+            CellValue::String("b".into()),
+            CellValue::Bool(false),
+            CellValue::Bool(true),
+            CellValue::Error(ErrorKind::NA),
+            CellValue::Empty,
+        ]
+    );
+}
+
+#[test]
+fn equality_follows_spreadsheet_rules() {
+    assert!(CellValue::Int(1).equals(&CellValue::Float(1.0)));
+    assert!(CellValue::String("abc".into()).equals(&CellValue::String("ABC".into())));
+    assert!(CellValue::Empty.equals(&CellValue::Int(0)));
+    assert!(CellValue::Empty.equals(&CellValue::String(String::new())));
+    assert!(CellValue::Empty.equals(&CellValue::Bool(false)));
+    assert!(!CellValue::Int(1).equals(&CellValue::Bool(true)));
+    assert_eq!(
+        CellValue::Int(1).compare(&CellValue::String("1".into())),
+        Ordering::Less
+    );
+}
+
+#[test]
+fn dates_use_spreadsheet_serials() {
+    let date = DateTime::from_ymd(2023, 3, 15).unwrap();
+    assert_eq!(date.serial(), 45000.0);
+    assert_eq!(date.ymd(), (2023, 3, 15));
+    assert_eq!(date.weekday(), 3);
+    assert_eq!(DateTime::from_serial(1.0).ymd(), (1899, 12, 31));
+    assert_eq!(DateTime::from_ymd(2023, 2, 29), None);
+
+    let with_time = DateTime::from_ymd_hms(2024, 2, 29, 18, 30, 15).unwrap();
+    assert_eq!(with_time.hms(), (18, 30, 15));
+    assert_eq!(with_time.to_string(), "2024-02-29 18:30:15");
+    assert_eq!("2024-02-29T18:30:15".parse(), Ok(with_time));
+    assert_eq!(
+        "2024-02-29".parse::<DateTime>().unwrap().to_string(),
+        "2024-02-29"
+    );
+    assert!("2024-13-01".parse::<DateTime>().is_err());
+
+    let jan31 = DateTime::from_ymd(2024, 1, 31).unwrap();
+    assert_eq!(jan31.add_months(1).unwrap().ymd(), (2024, 2, 29));
+    assert_eq!(jan31.add_months(-2).unwrap().ymd(), (2023, 11, 30));
+}
@@ -0,0 +1,143 @@
+use wasm_spreadsheet::{Area, CellRef, Grid, Viewport, FILL_HANDLE_SIZE, MAX_SCROLL_SIZE};
+
+#[test]
+fn shows_only_cells_inside_the_canvas() {
+    let grid = Grid::new(12, 350);
+    let viewport = Viewport::new(200.0, 100.0);
+
+    // 200px of 80px columns is two and a half columns; 100px of 30px rows
+    // is three and a third rows.
+    assert_eq!(viewport.visible_columns(&grid), 0..3);
+    assert_eq!(viewport.visible_rows(&grid), 0..4);
+    assert_eq!(viewport.top_left(&grid), CellRef::new(0, 0));
+}
+
+#[test]
+fn scrolling_moves_the_visible_window() {
+    let grid = Grid::new(12, 350);
+    let mut viewport = Viewport::new(200.0, 100.0);
+
+    assert!(viewport.scroll_by(&grid, 100.0, 45.0));
+    assert_eq!(viewport.top_left(&grid), CellRef::new(1, 1));
+    assert_eq!(viewport.visible_columns(&grid), 1..4);
+    assert_eq!(viewport.visible_rows(&grid), 1..5);
+
+    let (x, y, width, height) = viewport.cell_rect(&grid, CellRef::new(1, 1));
+    assert_eq!((x, y, width, height), (-20.0, -15.0, 80.0, 30.0));
+    assert_eq!(
+        viewport.cell_at(&grid, 70.0, 20.0),
+        Some(CellRef::new(2, 2))
+    );
+    assert_eq!(viewport.cell_at(&grid, 250.0, 20.0), None);
+}
+
+#[test]
+fn scrolling_is_clamped_to_the_grid() {
+    let grid = Grid::new(12, 350);
+    let mut viewport = Viewport::new(200.0, 100.0);
+
+    viewport.scroll_to(&grid, 1e9, 1e9);
+    assert_eq!(viewport.scroll_x(), 350.0 * 80.0 - 200.0);
+    assert_eq!(viewport.scroll_y(), 12.0 * 30.0 - 100.0);
+    assert_eq!(viewport.visible_columns(&grid), 347..350);
+    assert_eq!(viewport.visible_rows(&grid), 8..12);
+    assert!(!viewport.scroll_by(&grid, 10.0, 10.0));
+
+    viewport.scroll_to(&grid, -5.0, -5.0);
+    assert_eq!((viewport.scroll_x(), viewport.scroll_y()), (0.0, 0.0));
+}
+
+#[test]
+fn viewport_larger_than_grid_does_not_scroll() {
+    let grid = Grid::new(2, 2);
+    let mut viewport = Viewport::new(800.0, 600.0);
+
+    assert!(!viewport.scroll_by(&grid, 50.0, 50.0));
+    assert_eq!(viewport.visible_columns(&grid), 0..2);
+    assert_eq!(viewport.visible_rows(&grid), 0..2);
+}
+
+#[test]
+fn native_scrollbar_is_scaled_for_huge_grids() {
+    let grid = Grid::new(1_048_576, 10);
+    let mut viewport = Viewport::new(800.0, 600.0);
+    let (_, spacer_h) = viewport.spacer_size(&grid);
+    assert_eq!(spacer_h, MAX_SCROLL_SIZE);
+
+    // The bottom of the scrollbar reaches the last row.
+    viewport.scroll_to_native(&grid, 0.0, MAX_SCROLL_SIZE - 600.0);
+    assert_eq!(viewport.visible_rows(&grid).end, 1_048_576);
+    let (_, top) = viewport.native_scroll(&grid);
+    assert!((top - (MAX_SCROLL_SIZE - 600.0)).abs() < 1e-6);
+
+    // Halfway down the scrollbar is halfway down the grid.
+    viewport.scroll_to_native(&grid, 0.0, (MAX_SCROLL_SIZE - 600.0) / 2.0);
+    let middle = viewport.top_left(&grid).row;
+    assert!((524_000..524_300).contains(&middle));
+}
+
+#[test]
+fn column_geometry() {
+    let mut grid = Grid::new(3, 3);
+    grid.set_column_width(1, 40.0).unwrap();
+
+    assert_eq!(grid.column_left(2), 120.0);
+    assert_eq!(grid.column_at(100.0), Some(1));
+    assert_eq!(grid.column_at(120.0), Some(2));
+    assert_eq!(grid.column_at(200.0), None);
+    assert_eq!(grid.row_at(59.0), Some(1));
+    assert_eq!(grid.row_at(90.0), None);
+}
+
+#[test]
+fn headers_stay_fixed_while_the_body_scrolls() {
+    let grid = Grid::new(100, 350);
+    let mut viewport = Viewport::with_headers(60.0 + 200.0, 24.0 + 90.0);
+
+    assert_eq!(
+        (viewport.body_width(), viewport.body_height()),
+        (200.0, 90.0)
+    );
+    assert_eq!(viewport.visible_columns(&grid), 0..3);
+    assert_eq!(viewport.visible_rows(&grid), 0..3);
+    assert_eq!(
+        viewport.cell_rect(&grid, CellRef::new(0, 0)),
+        (60.0, 24.0, 80.0, 30.0)
+    );
+
+    viewport.scroll_to(&grid, 80.0, 30.0);
+    assert_eq!(viewport.column_x(&grid, 1), 60.0);
+    assert_eq!(viewport.row_y(&grid, 1), 24.0);
+    assert_eq!(
+        viewport.cell_at(&grid, 61.0, 25.0),
+        Some(CellRef::new(1, 1))
+    );
+    // Over the headers there is no cell, only a row or column.
+    assert_eq!(viewport.cell_at(&grid, 10.0, 30.0), None);
+    assert_eq!(viewport.cell_at(&grid, 100.0, 10.0), None);
+    assert_eq!(viewport.column_header_at(&grid, 100.0), Some(1));
+    assert_eq!(viewport.row_header_at(&grid, 60.0), Some(2));
+    assert_eq!(viewport.column_header_at(&grid, 30.0), None);
+
+    // The scroll range covers the last column despite the header.
+    viewport.scroll_to(&grid, 1e9, 0.0);
+    assert_eq!(viewport.visible_columns(&grid).end, 350);
+    let (spacer_w, _) = viewport.spacer_size(&grid);
+    assert_eq!(spacer_w, 350.0 * 80.0 + 60.0);
+}
+
+#[test]
+fn fill_handle_sits_on_the_selection_corner() {
+    let grid = Grid::new(10, 10);
+    let viewport = Viewport::with_headers(60.0 + 400.0, 24.0 + 300.0);
+    let area = Area::new(CellRef::new(0, 0), CellRef::new(1, 1));
+
+    // B2 ends at x = 60 + 160, y = 24 + 60.
+    assert_eq!(
+        viewport.fill_handle_rect(&grid, &area),
+        (217.0, 81.0, FILL_HANDLE_SIZE, FILL_HANDLE_SIZE)
+    );
+    assert!(viewport.is_over_fill_handle(&grid, &area, 220.0, 84.0));
+    assert!(viewport.is_over_fill_handle(&grid, &area, 223.0, 86.0));
+    assert!(!viewport.is_over_fill_handle(&grid, &area, 200.0, 84.0));
+}